@@ -0,0 +1,156 @@
+//! Arbitrary output variables (AOVs): images of what the camera sees first, rather
+//! than of the light arriving from it. Denoisers use them to tell the edges of
+//! objects from noise, and they help with debugging a scene.
+//!
+//! They only depend on the first intersection of the camera rays, so they are
+//! computed in a separate, much cheaper pass than the render itself (see
+//! `Scene::render_aovs`):
+//! - the albedo and the normal are averaged over `SAMPLES_PER_PIXEL` rays spread
+//!   over each pixel, so that they are antialiased like the render;
+//! - the depth, position and object id are those of the ray through the pixel's
+//!   center, since averaging them across the edge of an object would describe a
+//!   surface that is not there.
+
+use crate::{
+    camera::Camera,
+    objects::object_group::ObjectGroup,
+    samplers::traits::SamplerLike,
+    utility::{image::{Image, Pixel, Resolution}, math::{float::Float, vector::{Color3, Point3, Vec3}}}
+};
+
+/// How many rays the albedo and normal of a pixel are averaged over.
+pub const SAMPLES_PER_PIXEL: u32 = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AovKind {
+    /// The color of the texture, black where nothing is hit.
+    Albedo,
+    /// The (unit) surface normal, in world space, zero where nothing is hit.
+    Normal,
+    /// The distance from the camera, infinite where nothing is hit.
+    Depth,
+    /// The point hit, in world space, zero where nothing is hit.
+    Position,
+    /// The index of the object hit in the scene file's list of objects, -1 where
+    /// nothing is hit.
+    ObjectId,
+}
+
+impl AovKind {
+    pub const ALL: [AovKind; 5] = [
+        AovKind::Albedo,
+        AovKind::Normal,
+        AovKind::Depth,
+        AovKind::Position,
+        AovKind::ObjectId
+    ];
+
+    /// What the AOV is called in the scene file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AovKind::Albedo => "albedo",
+            AovKind::Normal => "normal",
+            AovKind::Depth => "depth",
+            AovKind::Position => "position",
+            AovKind::ObjectId => "object id",
+        }
+    }
+}
+
+/// All the AOVs of an image. Single valued ones (depth and object id) are stored
+/// in every channel.
+pub struct Aovs {
+    pub albedo: Image,
+    pub normal: Image,
+    pub depth: Image,
+    pub position: Image,
+    pub object_id: Image,
+}
+
+impl Aovs {
+    pub fn new(resolution: Resolution) -> Self {
+        Self {
+            albedo: Image::new(resolution.clone()),
+            normal: Image::new(resolution.clone()),
+            depth: Image::new(resolution.clone()),
+            position: Image::new(resolution.clone()),
+            object_id: Image::new(resolution),
+        }
+    }
+
+    pub fn get(&self, kind: AovKind) -> &Image {
+        match kind {
+            AovKind::Albedo => &self.albedo,
+            AovKind::Normal => &self.normal,
+            AovKind::Depth => &self.depth,
+            AovKind::Position => &self.position,
+            AovKind::ObjectId => &self.object_id,
+        }
+    }
+
+    pub fn set_pixel(&mut self, pixel: &Pixel, values: PixelAovs) {
+        let splat = |value: Float| Color3::new(value, value, value);
+        let object_id = values.object_id.map_or(-1.0, |index| index as Float);
+
+        self.albedo.set_pixel_color(pixel, values.albedo);
+        self.normal.set_pixel_color(pixel, values.normal);
+        self.depth.set_pixel_color(pixel, splat(values.depth));
+        self.position.set_pixel_color(pixel, values.position);
+        self.object_id.set_pixel_color(pixel, splat(object_id));
+    }
+}
+
+/// The AOVs of a single pixel.
+pub struct PixelAovs {
+    pub albedo: Color3,
+    pub normal: Vec3,
+    pub depth: Float,
+    pub position: Point3,
+    pub object_id: Option<usize>,
+}
+
+impl PixelAovs {
+    /// Traces the first intersections of the camera rays through `pixel`. The
+    /// sampler is restarted for each ray, starting from the pixel's first sample.
+    pub fn new(camera: &Camera, objects: &ObjectGroup, pixel: &Pixel, sampler: &mut dyn SamplerLike) -> Self {
+        let mut albedo = Color3::new(0.0, 0.0, 0.0);
+        let mut normal = Vec3::new(0.0, 0.0, 0.0);
+        for sample_index in 0..SAMPLES_PER_PIXEL {
+            sampler.start_pixel_sample(pixel, sample_index);
+            let offset = sampler.get_2d();
+            let ray = camera.generate_ray(
+                (pixel.x as Float) + offset.0,
+                (pixel.y as Float) + offset.1,
+                sampler
+            );
+
+            let intersection = objects.intersect(&ray);
+            if let Some(object) = &intersection.intersected_object {
+                let shape_intersection = &intersection.shape_intersection_info;
+                albedo = albedo + object.albedo(&ray, shape_intersection);
+                normal = normal + shape_intersection.shading_normal.clone().normalize();
+            }
+        }
+        let scale = 1.0 / (SAMPLES_PER_PIXEL as Float);
+
+        sampler.start_pixel_sample(pixel, 0);
+        let _offset = sampler.get_2d();
+        let center_ray = camera.generate_ray((pixel.x as Float) + 0.5, (pixel.y as Float) + 0.5, sampler);
+        let center = objects.intersect(&center_ray);
+        let (depth, position) = match center.intersected_object {
+            Some(_) => {
+                let point = center.shape_intersection_info.point;
+                ((&point - &center_ray.origin).length(), point)
+            },
+            None => (Float::INFINITY, Point3::origin()),
+        };
+
+        Self {
+            albedo: scale * albedo,
+            normal: scale * normal,
+            depth,
+            position,
+            object_id: center.object_index,
+        }
+    }
+}
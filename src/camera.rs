@@ -13,8 +13,9 @@ use crate::{
             float::Float, 
             angle::{Angle, AngleUnits}
         }, 
-        rng::RandomNumberGenerator, image::Resolution, 
+        image::Resolution, 
     },
+    samplers::traits::SamplerLike,
     sampler
 };
 
@@ -91,8 +92,8 @@ impl Camera {
     /// Returns a ray, in world space, from the camera that represents a 
     /// contribution to the pixel (pixel_x,pixel_y). Following the Mirth 
     /// convention, the bottom left pixel is (0,0), and the top right pixel is 
-    /// (width-1,height-1).
-    pub fn generate_ray(&self, pixel_x: Float, pixel_y: Float, rng: &mut RandomNumberGenerator) -> Ray3 {
+    /// (width-1,height-1). The point on the lens is the next 2D sample of `sampler`.
+    pub fn generate_ray(&self, pixel_x: Float, pixel_y: Float, sampler: &mut dyn SamplerLike) -> Ray3 {
         // --- Defocus blur ---
         // To achieve this effect, we offset the origin of the ray to represent
         // light passing through the lens from, potentially, somewhere other 
@@ -108,7 +109,7 @@ impl Camera {
         let tx = pixel_x / (self.resolution.width as Float);
         let ty = pixel_y / (self.resolution.height as Float);
 
-        let local_ray_origin = self.aperture_radius * sampler::uniform_in_1sphere(rng).point;
+        let local_ray_origin = self.aperture_radius * sampler::uniform_in_1sphere_from_uniform(sampler.get_2d()).point;
         let local_ray_direction = {
             let pixel_in_image_plane = 
                 &self.bottom_left_corner_of_image_plane 
@@ -1,22 +1,31 @@
 
-use tracing::{error, warn};
+use tracing::warn;
 
 static MIRTH_CONFIG: Config = Config {
     acceleration_structure: AccelerationStructure {
         kind: AccStructureKind::BBH,
-        axis_selection_method: AccStructureAxisSelectionMethod::Random,
+        axis_selection_method: AccStructureAxisSelectionMethod::LargestExtent,
     },
 };
 
 pub fn validate_config() {
-    if (matches!(
-        MIRTH_CONFIG.acceleration_structure.kind,
+    validate_acceleration_structure(&MIRTH_CONFIG.acceleration_structure);
+}
+
+/// The acceleration structure to use when a scene does not specify one.
+pub fn default_acceleration_structure() -> AccelerationStructure {
+    MIRTH_CONFIG.acceleration_structure.clone()
+}
+
+pub fn validate_acceleration_structure(acceleration_structure: &AccelerationStructure) {
+    if matches!(
+        acceleration_structure.kind,
         AccStructureKind::BBH
-    ) && !matches!(
-        MIRTH_CONFIG.acceleration_structure.axis_selection_method,
-        AccStructureAxisSelectionMethod::LargestExtent
-    )) {
-        warn!("using 'BBH' acceleration structure with suboptimal axis determination method ('LargestExtent' is the intended method)");
+    ) && matches!(
+        acceleration_structure.axis_selection_method,
+        AccStructureAxisSelectionMethod::Random | AccStructureAxisSelectionMethod::Alternating
+    ) {
+        warn!("using 'BBH' acceleration structure with suboptimal axis determination method ('LargestExtent' or 'SurfaceAreaHeuristic' are the intended methods)");
     }
 }
 
@@ -24,18 +33,31 @@ struct Config {
     acceleration_structure: AccelerationStructure,
 }
 
-struct AccelerationStructure {
-    kind: AccStructureKind,
-    axis_selection_method: AccStructureAxisSelectionMethod,
+#[derive(Clone, Debug)]
+pub struct AccelerationStructure {
+    pub kind: AccStructureKind,
+    pub axis_selection_method: AccStructureAxisSelectionMethod,
 }
 
-enum AccStructureKind {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccStructureKind {
+    /// Test the ray against every object.
     Nothing,
+    /// A bounding (box) volume hierarchy.
     BBH,
 }
 
-enum AccStructureAxisSelectionMethod {
+/// How a node of the hierarchy chooses the axis along which to split its objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccStructureAxisSelectionMethod {
+    /// A uniformly random axis, splitting at the median object.
     Random,
+    /// Cycle through x, y, z as we go deeper, splitting at the median object.
     Alternating,
+    /// The axis along which the objects' centroids are most spread out, splitting at
+    /// the median object.
     LargestExtent,
+    /// The split (over all axes) minimizing the expected cost of tracing a ray,
+    /// estimated using the surface areas of the resulting boxes.
+    SurfaceAreaHeuristic,
 }
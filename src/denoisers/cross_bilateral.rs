@@ -0,0 +1,67 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    aovs::Aovs,
+    utility::{image::{Image, Pixel}, math::float::Float}
+};
+use super::{traits::DenoiserLike, guides::{FeatureSigmas, Guides}};
+
+// E==== IMPORTS }}}1
+
+/// Replaces each pixel by a weighted average of the pixels within `radius`, with
+/// weights that fall off with the distance (with standard deviation
+/// `sigma_spatial`) and with how different the pixels' features are. Only the
+/// features decide what is an edge, so the shapes of shadows and other changes in
+/// lighting on a single surface are blurred.
+pub struct CrossBilateralDenoiser {
+    radius: u32,
+    sigma_spatial: Float,
+    sigmas: FeatureSigmas,
+}
+
+impl CrossBilateralDenoiser {
+    pub fn new(radius: u32, sigma_spatial: Float, sigmas: FeatureSigmas) -> Self {
+        Self { radius, sigma_spatial, sigmas }
+    }
+}
+
+impl DenoiserLike for CrossBilateralDenoiser {
+    fn denoise(&self, image: &Image, aovs: &Aovs) -> Image {
+        let guides = Guides::new(aovs);
+        let irradiance = guides.demodulate(image);
+        let resolution = guides.resolution().clone();
+        let radius = self.radius as i64;
+
+        let denoised: Vec<[Float; 3]> = resolution.clone()
+            .into_iter()
+            .map(|pixel| {
+                let p = resolution.pixel_index(&pixel);
+                let mut sum = [0.0; 3];
+                let mut weight_sum = 0.0;
+
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        let (x, y) = (pixel.x as i64 + dx, pixel.y as i64 + dy);
+                        if x < 0 || y < 0 || x >= resolution.width as i64 || y >= resolution.height as i64 {
+                            continue;
+                        }
+
+                        let q = resolution.pixel_index(&Pixel { x: x as u32, y: y as u32 });
+                        let squared_distance = (dx * dx + dy * dy) as Float;
+                        let weight = (-squared_distance / (2.0 * self.sigma_spatial * self.sigma_spatial)).exp()
+                            * guides.weight(p, q, &self.sigmas);
+                        for (channel, value) in sum.iter_mut().zip(irradiance[q]) {
+                            *channel += weight * value;
+                        }
+                        weight_sum += weight;
+                    }
+                }
+
+                // The pixel itself always has weight 1.
+                sum.map(|channel| channel / weight_sum)
+            })
+            .collect();
+
+        guides.remodulate(&denoised)
+    }
+}
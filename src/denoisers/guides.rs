@@ -0,0 +1,116 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    aovs::Aovs,
+    utility::{image::{Image, Resolution}, math::{float::Float, vector::Color3}}
+};
+
+// E==== IMPORTS }}}1
+
+/// Below this, a channel of the albedo is treated as black and not divided out.
+const MIN_ALBEDO: Float = 0.01;
+
+/// How different the features of two pixels may be before one stops contributing to
+/// the other: the weight falls off like a Gaussian with these standard deviations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeatureSigmas {
+    pub albedo: Float,
+    pub normal: Float,
+    /// Relative to the depth of the pixel being denoised.
+    pub depth: Float,
+}
+
+/// The features of an image, from its AOVs, in flat buffers indexed by
+/// `Resolution::pixel_index`.
+pub struct Guides {
+    resolution: Resolution,
+    albedo: Vec<[Float; 3]>,
+    normal: Vec<[Float; 3]>,
+    depth: Vec<Float>,
+}
+
+impl Guides {
+    pub fn new(aovs: &Aovs) -> Self {
+        let resolution = aovs.albedo.resolution().clone();
+        let channels = |image: &Image| -> Vec<[Float; 3]> {
+            resolution.clone()
+                .into_iter()
+                .map(|pixel| {
+                    let color = image.get_pixel_color(&pixel);
+                    [color.x(), color.y(), color.z()]
+                })
+                .collect()
+        };
+
+        Self {
+            albedo: channels(&aovs.albedo),
+            normal: channels(&aovs.normal),
+            depth: channels(&aovs.depth).iter().map(|depth| depth[0]).collect(),
+            resolution,
+        }
+    }
+
+    pub fn resolution(&self) -> &Resolution {
+        &self.resolution
+    }
+
+    /// The weight, in $[0,1]$, with which pixel `q` contributes to pixel `p` (both
+    /// given by their `Resolution::pixel_index`) as far as their features go.
+    pub fn weight(&self, p: usize, q: usize, sigmas: &FeatureSigmas) -> Float {
+        let (depth_p, depth_q) = (self.depth[p], self.depth[q]);
+        let relative_depth = if depth_p.is_infinite() || depth_q.is_infinite() {
+            // Both are background, or one is and the other is not.
+            if depth_p == depth_q { 0.0 } else { return 0.0; }
+        } else {
+            (depth_p - depth_q) / Float::max(depth_p, 1e-4)
+        };
+
+        let exponent = squared_distance(&self.albedo[p], &self.albedo[q]) / (2.0 * sigmas.albedo * sigmas.albedo)
+            + squared_distance(&self.normal[p], &self.normal[q]) / (2.0 * sigmas.normal * sigmas.normal)
+            + relative_depth * relative_depth / (2.0 * sigmas.depth * sigmas.depth);
+        (-exponent).exp()
+    }
+
+    /// Divides the albedo out of `image`, leaving (roughly) the light arriving at the
+    /// surfaces. Unlike the image, it does not change at the edges of textures, so
+    /// denoising it does not blur them.
+    pub fn demodulate(&self, image: &Image) -> Vec<[Float; 3]> {
+        self.resolution.clone()
+            .into_iter()
+            .map(|pixel| {
+                let color = image.get_pixel_color(&pixel);
+                let albedo = &self.albedo[self.resolution.pixel_index(&pixel)];
+                [
+                    color.x() / divisor(albedo[0]),
+                    color.y() / divisor(albedo[1]),
+                    color.z() / divisor(albedo[2]),
+                ]
+            })
+            .collect()
+    }
+
+    /// The inverse of `demodulate`.
+    pub fn remodulate(&self, irradiance: &[[Float; 3]]) -> Image {
+        let mut to_return = Image::new(self.resolution.clone());
+        for pixel in self.resolution.clone() {
+            let index = self.resolution.pixel_index(&pixel);
+            let (value, albedo) = (&irradiance[index], &self.albedo[index]);
+            let color = Color3::new(
+                value[0] * divisor(albedo[0]),
+                value[1] * divisor(albedo[1]),
+                value[2] * divisor(albedo[2]),
+            );
+            to_return.set_pixel_color(&pixel, color);
+        }
+
+        to_return
+    }
+}
+
+fn divisor(albedo: Float) -> Float {
+    if albedo > MIN_ALBEDO { albedo } else { 1.0 }
+}
+
+fn squared_distance(a: &[Float; 3], b: &[Float; 3]) -> Float {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
@@ -0,0 +1,87 @@
+//! Denoisers, which remove the noise left in a render with few samples per pixel.
+//! They are guided by the AOVs of the image (see `aovs`), which are free of noise:
+//! neighboring pixels are only averaged if they see a similar surface, so that the
+//! edges of objects stay sharp. The albedo is divided out of the image before it is
+//! filtered and multiplied back in after, which keeps textures sharp too.
+
+pub mod traits;
+pub mod guides;
+pub mod cross_bilateral;
+pub mod non_local_means;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aovs::{Aovs, PixelAovs},
+        utility::{image::{Image, Pixel, Resolution}, math::{float::Float, vector::{Color3, Point3, Vec3}}, rng::RandomNumberGenerator}
+    };
+    use super::{
+        traits::DenoiserLike,
+        guides::FeatureSigmas,
+        cross_bilateral::CrossBilateralDenoiser,
+        non_local_means::NonLocalMeansDenoiser
+    };
+
+    const SIZE: u32 = 32;
+
+    /// A wall under uniform light, whose left half is dark and right half is bright.
+    fn albedo(pixel: &Pixel) -> Float {
+        if pixel.x < SIZE / 2 { 0.2 } else { 0.8 }
+    }
+
+    /// The wall and its noisy render, whose pixels are off by up to 50%.
+    fn noisy_wall() -> (Image, Aovs) {
+        let resolution = Resolution { width: SIZE, height: SIZE };
+        let mut rng = RandomNumberGenerator::from_seed(3);
+        let mut image = Image::new(resolution.clone());
+        let mut aovs = Aovs::new(resolution.clone());
+        for pixel in resolution {
+            let value = albedo(&pixel) * (0.5 + rng.next_float());
+            image.set_pixel_color(&pixel, Color3::new(value, value, value));
+            aovs.set_pixel(&pixel, PixelAovs {
+                albedo: Color3::new(albedo(&pixel), albedo(&pixel), albedo(&pixel)),
+                normal: Vec3::new(0.0, 0.0, 1.0),
+                depth: 2.0,
+                position: Point3::origin(),
+                object_id: Some(0),
+            });
+        }
+
+        (image, aovs)
+    }
+
+    /// The root mean square error of `image`, and that of its two columns next to the
+    /// edge of the albedo.
+    fn errors(image: &Image) -> (Float, Float) {
+        let squared_error = |pixel: &Pixel| {
+            let error = image.get_pixel_color(pixel).y() - albedo(pixel);
+            error * error
+        };
+        let resolution = image.resolution().clone();
+        let all: Float = resolution.clone().into_iter().map(|pixel| squared_error(&pixel)).sum();
+        let edge: Float = resolution.into_iter()
+            .filter(|pixel| pixel.x == SIZE / 2 - 1 || pixel.x == SIZE / 2)
+            .map(|pixel| squared_error(&pixel))
+            .sum();
+
+        ((all / (SIZE * SIZE) as Float).sqrt(), (edge / (2 * SIZE) as Float).sqrt())
+    }
+
+    #[test]
+    fn denoisers_remove_noise_but_keep_edges() {
+        let (noisy, aovs) = noisy_wall();
+        let (noisy_error, _) = errors(&noisy);
+
+        let sigmas = FeatureSigmas { albedo: 0.1, normal: 0.2, depth: 0.1 };
+        let denoisers: [Box<dyn DenoiserLike>; 2] = [
+            Box::new(CrossBilateralDenoiser::new(5, 2.5, sigmas)),
+            Box::new(NonLocalMeansDenoiser::new(7, 1, 1.0, sigmas)),
+        ];
+        for denoiser in denoisers {
+            let (error, edge_error) = errors(&denoiser.denoise(&noisy, &aovs));
+            assert!(error < 0.4 * noisy_error, "{} vs {}", error, noisy_error);
+            // Were the edge blurred, its pixels would be off by about 0.3.
+            assert!(edge_error < 0.5 * noisy_error, "{} vs {}", edge_error, noisy_error);
+        }
+    }
+}
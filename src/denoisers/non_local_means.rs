@@ -0,0 +1,133 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    aovs::Aovs,
+    utility::{image::Image, math::float::Float}
+};
+use super::{traits::DenoiserLike, guides::{FeatureSigmas, Guides}};
+
+// E==== IMPORTS }}}1
+
+/// Keeps the distance between dark colors from vanishing in `relative_distance`.
+const EPSILON: Float = 1e-3;
+
+/// Replaces each pixel by a weighted average of the pixels within `search_radius`,
+/// weighted both by how different their features are and by how similar the
+/// patches of `patch_radius` around them look. Unlike the cross bilateral
+/// denoiser, this keeps the edges of shadows, at the cost of being slower and
+/// removing less noise. A larger `strength` averages over less similar patches,
+/// removing more noise but also more detail.
+pub struct NonLocalMeansDenoiser {
+    search_radius: u32,
+    patch_radius: u32,
+    strength: Float,
+    sigmas: FeatureSigmas,
+}
+
+impl NonLocalMeansDenoiser {
+    pub fn new(search_radius: u32, patch_radius: u32, strength: Float, sigmas: FeatureSigmas) -> Self {
+        Self { search_radius, patch_radius, strength, sigmas }
+    }
+}
+
+impl DenoiserLike for NonLocalMeansDenoiser {
+    /// Rather than comparing the patches around each pair of pixels separately, goes
+    /// through the offsets between pairs: the distances between the pixels and the
+    /// pixels at some offset are computed once, and then summed over the patches
+    /// with a box filter.
+    fn denoise(&self, image: &Image, aovs: &Aovs) -> Image {
+        let guides = Guides::new(aovs);
+        let irradiance = guides.demodulate(image);
+        let (width, height) = (guides.resolution().width as i64, guides.resolution().height as i64);
+        let radius = self.search_radius as i64;
+
+        let mut sums = vec![[0.0; 3]; irradiance.len()];
+        let mut weight_sums = vec![0.0; irradiance.len()];
+        let mut distances = vec![0.0; irradiance.len()];
+        let mut valid = vec![false; irradiance.len()];
+
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                // The pixels whose neighbor at this offset is inside the image.
+                let is_inside = |x: i64, y: i64| {
+                    (0..width).contains(&(x + dx)) && (0..height).contains(&(y + dy))
+                };
+                let neighbor = |p: usize| ((p as i64) + dy * width + dx) as usize;
+
+                for y in 0..height {
+                    for x in 0..width {
+                        let p = (y * width + x) as usize;
+                        valid[p] = is_inside(x, y);
+                        distances[p] = if valid[p] {
+                            relative_distance(&irradiance[p], &irradiance[neighbor(p)])
+                        } else {
+                            0.0
+                        };
+                    }
+                }
+                let patch_distances = box_average(&distances, &valid, width, height, self.patch_radius as i64);
+
+                for (p, patch_distance) in patch_distances.iter().enumerate() {
+                    if !valid[p] { continue; }
+
+                    let q = neighbor(p);
+                    let weight = (-patch_distance / (self.strength * self.strength)).exp()
+                        * guides.weight(p, q, &self.sigmas);
+                    for (channel, value) in sums[p].iter_mut().zip(irradiance[q]) {
+                        *channel += weight * value;
+                    }
+                    weight_sums[p] += weight;
+                }
+            }
+        }
+
+        // The pixel itself always has weight 1.
+        let denoised: Vec<[Float; 3]> = sums.iter()
+            .zip(weight_sums)
+            .map(|(sum, weight_sum)| sum.map(|channel| channel / weight_sum))
+            .collect();
+        guides.remodulate(&denoised)
+    }
+}
+
+/// The squared difference of two colors, relative to their brightness so that the
+/// same `strength` works in dark and bright parts of the image, averaged over the
+/// channels.
+fn relative_distance(a: &[Float; 3], b: &[Float; 3]) -> Float {
+    let sum: Float = (0..3)
+        .map(|i| (a[i] - b[i]) * (a[i] - b[i]) / (EPSILON + a[i] * a[i] + b[i] * b[i]))
+        .sum();
+    sum / 3.0
+}
+
+/// The average of the valid `values` within `radius` (along either axis) of each
+/// pixel, one axis after the other.
+fn box_average(values: &[Float], valid: &[bool], width: i64, height: i64, radius: i64) -> Vec<Float> {
+    let pass = |sums: &[Float], counts: &[Float], step: i64, length: i64| -> (Vec<Float>, Vec<Float>) {
+        let mut new_sums = vec![0.0; sums.len()];
+        let mut new_counts = vec![0.0; counts.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let p = y * width + x;
+                let position = if step == 1 { x } else { y };
+                for offset in -radius..=radius {
+                    if !(0..length).contains(&(position + offset)) { continue; }
+                    let q = (p + offset * step) as usize;
+                    new_sums[p as usize] += sums[q];
+                    new_counts[p as usize] += counts[q];
+                }
+            }
+        }
+        (new_sums, new_counts)
+    };
+
+    let sums: Vec<Float> = values.iter().zip(valid).map(|(v, ok)| if *ok { *v } else { 0.0 }).collect();
+    let counts: Vec<Float> = valid.iter().map(|ok| if *ok { 1.0 } else { 0.0 }).collect();
+    let (sums, counts) = pass(&sums, &counts, 1, width);
+    let (sums, counts) = pass(&sums, &counts, width, height);
+
+    sums.iter()
+        .zip(counts)
+        .map(|(sum, count)| if count > 0.0 { sum / count } else { 0.0 })
+        .collect()
+}
@@ -0,0 +1,7 @@
+use crate::{aovs::Aovs, utility::image::Image};
+
+pub trait DenoiserLike: Send + Sync {
+    /// Removes the noise from a rendered `image`, telling the edges of objects and
+    /// textures from noise with the help of its `aovs`.
+    fn denoise(&self, image: &Image, aovs: &Aovs) -> Image;
+}
@@ -0,0 +1,28 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::math::vector::Vec3,
+    light::Spectrum
+};
+use super::traits::EnvironmentLike;
+
+// E==== IMPORTS }}}1
+
+/// The same light from every direction, e.g. a plain background color.
+pub struct ConstantEnvironment {
+    radiance: Spectrum,
+}
+
+impl ConstantEnvironment {
+    pub fn new(radiance: Spectrum) -> Self {
+        Self {
+            radiance
+        }
+    }
+}
+
+impl EnvironmentLike for ConstantEnvironment {
+    fn radiance(&self, _direction: &Vec3) -> Spectrum {
+        self.radiance.clone()
+    }
+}
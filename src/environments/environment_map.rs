@@ -0,0 +1,163 @@
+//! Environments given by an image covering every direction, in the equirectangular 
+//! (latitude-longitude) layout of most HDR environment maps.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::math::{
+        vector::Vec3, 
+        float::{Float, FloatConstants}, 
+        distribution::Distribution2D
+    },
+    objects::textures::image_texture::ImageTexture,
+    light::{Spectrum, luminance}
+};
+use super::traits::{EnvironmentLike, EnvironmentSample};
+
+// E==== IMPORTS }}}1
+
+/// The top row of the image is straight up ($+y$), the bottom row straight down, 
+/// and the horizontal center of the image is in the direction of $+x$. This matches 
+/// the texture coordinates of a `Sphere` seen from the inside.
+///
+/// We sample directions with probability proportional to the brightness of the 
+/// texel they fall into, so that small bright regions (e.g. the sun) are found by 
+/// light samples rather than by chance.
+pub struct EnvironmentMap {
+    image: ImageTexture,
+    /// Every texel is multiplied by this.
+    strength: Float,
+    /// On $[0,1)^2$, where $(x,y)=(0,0)$ is the top left corner of the image.
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: ImageTexture, strength: Float) -> Self {
+        let (width, height) = (image.width(), image.height());
+
+        // The rows near the poles cover less solid angle, so they are sampled less.
+        let weights: Vec<Float> = image.texels()
+            .iter()
+            .enumerate()
+            .map(|(index, texel)| {
+                let theta = Float::get_pi() * (((index / width) as Float) + 0.5) / (height as Float);
+                Float::max(luminance(texel), 0.0) * Float::sin(theta)
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width, height);
+
+        Self {
+            image,
+            strength,
+            distribution,
+        }
+    }
+
+    /// The point of the distribution's domain corresponding to the unit vector 
+    /// `direction`, and the sine of the angle between it and $+y$.
+    fn direction_to_image(direction: &Vec3) -> ((Float, Float), Float) {
+        let theta = Float::acos(Float::clamp(direction.y(), -1.0, 1.0));
+        let phi = Float::atan2(-direction.z(), direction.x()) + Float::get_pi();
+
+        ((phi / (2.0 * Float::get_pi()), theta * Float::get_1_pi()), Float::sin(theta))
+    }
+
+    /// The inverse of `direction_to_image()`.
+    fn image_to_direction((x, y): (Float, Float)) -> (Vec3, Float) {
+        let (sin_theta, cos_theta) = Float::sin_cos(Float::get_pi() * y);
+        let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * x - Float::get_pi());
+
+        (Vec3::new(sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi), sin_theta)
+    }
+
+    /// Converts a density on the image to one wrt solid angle. The texel at height 
+    /// $\theta$ covers a solid angle $2\pi^2 \sin\theta$ times its area.
+    fn image_pdf_to_solid_angle(pdf: Float, sin_theta: Float) -> Float {
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        pdf / (2.0 * Float::get_pi() * Float::get_pi() * sin_theta)
+    }
+}
+
+impl EnvironmentLike for EnvironmentMap {
+    fn radiance(&self, direction: &Vec3) -> Spectrum {
+        let ((x, y), _) = Self::direction_to_image(&direction.clone().normalize());
+        self.strength * self.image.color_at(x, 1.0 - y)
+    }
+
+    fn is_sampleable(&self) -> bool {
+        true
+    }
+
+    fn sample(&self, u: (Float, Float)) -> Option<EnvironmentSample> {
+        let (point, image_pdf) = self.distribution.sample(u);
+        let (direction, sin_theta) = Self::image_to_direction(point);
+        let pdf = Self::image_pdf_to_solid_angle(image_pdf, sin_theta);
+        if pdf == 0.0 {
+            return None;
+        }
+
+        Some(EnvironmentSample {
+            radiance: self.radiance(&direction),
+            direction,
+            pdf,
+        })
+    }
+
+    fn pdf(&self, direction: &Vec3) -> Float {
+        let (point, sin_theta) = Self::direction_to_image(&direction.clone().normalize());
+        Self::image_pdf_to_solid_angle(self.distribution.pdf(point), sin_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        objects::textures::image_texture::{ImageTextureInfo, TextureFilter, TextureWrap},
+        utility::{math::vector::Color3, rng::RandomNumberGenerator}
+    };
+
+    #[test]
+    fn samples_find_the_sun() {
+        // A dark 8x4 sky with one bright texel above the horizon.
+        let (width, height) = (8, 4);
+        let mut texels = vec![Color3::new(0.01, 0.01, 0.01); width * height];
+        texels[width + 5] = Color3::new(1000.0, 1000.0, 1000.0);
+        let image = ImageTexture::new(ImageTextureInfo {
+            width,
+            height,
+            texels,
+            filter: TextureFilter::Nearest,
+            wrap: TextureWrap::Repeat,
+        });
+        let environment = EnvironmentMap::new(image, 1.0);
+
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let num_samples = 1000;
+        let mut num_in_sun = 0;
+        // The average of radiance / pdf estimates the integral of the radiance over 
+        // all directions, which is dominated by the sun.
+        let mut estimate = 0.0;
+        for _ in 0..num_samples {
+            let sample = environment.sample((rng.next_float(), rng.next_float())).unwrap();
+            assert!((environment.pdf(&sample.direction) / sample.pdf - 1.0).abs() < 1e-2);
+            if sample.radiance.x() > 1.0 {
+                num_in_sun += 1;
+                assert!(sample.direction.y() > 0.0);
+            }
+            estimate += sample.radiance.x() / sample.pdf;
+        }
+        estimate /= num_samples as Float;
+
+        assert!(num_in_sun > 900);
+        // The sun's texel spans a quarter of the height and an eighth of the 
+        // longitude, from $\theta=\pi/4$ to $\pi/2$.
+        let sun = 1000.0 * (2.0 * Float::get_pi() / 8.0) * Float::cos(Float::get_pi() / 4.0);
+        let sky = 0.01 * 4.0 * Float::get_pi();
+        let expected = sun + sky - 0.01 * (2.0 * Float::get_pi() / 8.0) * Float::cos(Float::get_pi() / 4.0);
+        assert!((estimate / expected - 1.0).abs() < 0.05, "estimate = {}, expected {}", estimate, expected);
+    }
+}
@@ -0,0 +1,7 @@
+//! The environment is the light arriving from infinitely far away, e.g. the sky. 
+//! It is what rays that escape the scene see.
+
+pub mod traits;
+pub mod constant;
+pub mod sky;
+pub mod environment_map;
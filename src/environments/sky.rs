@@ -0,0 +1,45 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::math::vector::Vec3,
+    light::Spectrum
+};
+use super::traits::EnvironmentLike;
+
+// E==== IMPORTS }}}1
+
+/// A simple sky: above the horizon the color is interpolated between `horizon` and 
+/// `zenith` by the height of the direction, i.e. its $y$-component, and below the 
+/// horizon it is `ground`.
+pub struct SkyEnvironment {
+    zenith: Spectrum,
+    horizon: Spectrum,
+    ground: Spectrum,
+}
+
+pub struct SkyEnvironmentInfo {
+    pub zenith: Spectrum,
+    pub horizon: Spectrum,
+    pub ground: Spectrum,
+}
+
+impl SkyEnvironment {
+    pub fn new(info: SkyEnvironmentInfo) -> Self {
+        Self {
+            zenith: info.zenith,
+            horizon: info.horizon,
+            ground: info.ground,
+        }
+    }
+}
+
+impl EnvironmentLike for SkyEnvironment {
+    fn radiance(&self, direction: &Vec3) -> Spectrum {
+        let height = direction.clone().normalize().y();
+        if height < 0.0 {
+            return self.ground.clone();
+        }
+
+        ((1.0 - height) * &self.horizon) + (height * &self.zenith)
+    }
+}
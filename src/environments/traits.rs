@@ -0,0 +1,37 @@
+use crate::{
+    utility::math::{vector::Vec3, float::Float},
+    light::Spectrum
+};
+
+pub struct EnvironmentSample {
+    /// The (unit) direction the light arrives from, pointing away from the scene.
+    pub direction: Vec3,
+    pub radiance: Spectrum,
+    /// The probability density, wrt solid angle, of `direction`.
+    pub pdf: Float,
+}
+
+/// Environments are shared between the threads rendering a scene, hence `Send + Sync`.
+///
+/// As with `SampleableShape`, an environment may support being sampled as a light 
+/// source; by default it does not, and is only seen by rays that happen to escape.
+pub trait EnvironmentLike: Send + Sync {
+    /// The light arriving from `direction`, which points away from the scene and need 
+    /// not be normalized.
+    fn radiance(&self, direction: &Vec3) -> Spectrum;
+
+    fn is_sampleable(&self) -> bool {
+        false
+    }
+
+    /// Chooses a direction, ideally where the environment is bright, where `u` is 
+    /// uniformly distributed in $[0,1)^2$.
+    fn sample(&self, _u: (Float, Float)) -> Option<EnvironmentSample> {
+        None
+    }
+
+    /// The density (wrt solid angle) with which `sample()` picks `direction`.
+    fn pdf(&self, _direction: &Vec3) -> Float {
+        0.0
+    }
+}
@@ -0,0 +1,30 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::math::float::Float;
+use super::traits::FilterLike;
+
+// E==== IMPORTS }}}1
+
+/// Weighs every sample within the radius equally. With a radius of half a pixel, 
+/// each pixel is the plain average of the samples that fell inside it.
+pub struct BoxFilter {
+    radius: Float,
+}
+
+impl BoxFilter {
+    pub fn new(radius: Float) -> Self {
+        Self {
+            radius
+        }
+    }
+}
+
+impl FilterLike for BoxFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        if x.abs() <= self.radius && y.abs() <= self.radius { 1.0 } else { 0.0 }
+    }
+}
@@ -0,0 +1,46 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::math::float::Float;
+use super::traits::FilterLike;
+
+// E==== IMPORTS }}}1
+
+/// A Gaussian with standard deviation `sigma`, shifted down so that it reaches zero 
+/// at the radius rather than being cut off abruptly. Slightly blurs the image.
+pub struct GaussianFilter {
+    radius: Float,
+    sigma: Float,
+    /// The value of the unshifted Gaussian at the radius.
+    at_radius: Float,
+}
+
+impl GaussianFilter {
+    pub fn new(radius: Float, sigma: Float) -> Self {
+        let mut to_return = Self {
+            radius,
+            sigma,
+            at_radius: 0.0,
+        };
+        to_return.at_radius = to_return.gaussian(radius);
+
+        to_return
+    }
+
+    fn gaussian(&self, x: Float) -> Float {
+        (-x * x / (2.0 * self.sigma * self.sigma)).exp()
+    }
+
+    fn evaluate_1d(&self, x: Float) -> Float {
+        Float::max(self.gaussian(x) - self.at_radius, 0.0)
+    }
+}
+
+impl FilterLike for GaussianFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+}
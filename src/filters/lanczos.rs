@@ -0,0 +1,47 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::math::float::{Float, FloatConstants};
+use super::traits::FilterLike;
+
+// E==== IMPORTS }}}1
+
+/// The sinc function, i.e. the ideal low-pass filter, windowed by a wider sinc so 
+/// that it ends at the radius: $\operatorname{sinc}(x) \operatorname{sinc}(x/r)$. 
+/// Sharp, but rings around edges.
+pub struct LanczosFilter {
+    radius: Float,
+}
+
+impl LanczosFilter {
+    pub fn new(radius: Float) -> Self {
+        Self {
+            radius
+        }
+    }
+
+    fn evaluate_1d(&self, x: Float) -> Float {
+        if x.abs() > self.radius {
+            return 0.0;
+        }
+        sinc(x) * sinc(x / self.radius)
+    }
+}
+
+/// The normalized sinc function, $\sin(\pi x) / (\pi x)$.
+fn sinc(x: Float) -> Float {
+    let pi_x = Float::get_pi() * x;
+    if pi_x.abs() < 1e-5 {
+        return 1.0;
+    }
+    pi_x.sin() / pi_x
+}
+
+impl FilterLike for LanczosFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+}
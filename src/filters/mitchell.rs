@@ -0,0 +1,81 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::math::float::Float;
+use super::traits::FilterLike;
+
+// E==== IMPORTS }}}1
+
+/// The cubic filter of Mitchell and Netravali ("Reconstruction Filters in Computer 
+/// Graphics", 1988). `b` and `c` trade blurring against ringing; the authors 
+/// recommend $b = c = 1/3$, and they should satisfy $b + 2c = 1$. The filter is 
+/// negative near its radius, which sharpens the image.
+pub struct MitchellFilter {
+    radius: Float,
+    b: Float,
+    c: Float,
+}
+
+impl MitchellFilter {
+    pub fn new(radius: Float, b: Float, c: Float) -> Self {
+        Self {
+            radius,
+            b,
+            c
+        }
+    }
+
+    /// The filter as originally defined, on $[-2, 2]$.
+    fn mitchell_1d(&self, x: Float) -> Float {
+        let (b, c) = (self.b, self.c);
+        let x = x.abs();
+
+        let value = if x < 1.0 {
+            (12.0 - 9.0 * b - 6.0 * c) * x * x * x 
+                + (-18.0 + 12.0 * b + 6.0 * c) * x * x 
+                + (6.0 - 2.0 * b)
+        } else if x < 2.0 {
+            (-b - 6.0 * c) * x * x * x 
+                + (6.0 * b + 30.0 * c) * x * x 
+                + (-12.0 * b - 48.0 * c) * x 
+                + (8.0 * b + 24.0 * c)
+        } else {
+            0.0
+        };
+
+        value / 6.0
+    }
+}
+
+impl FilterLike for MitchellFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        let scale = 2.0 / self.radius;
+        self.mitchell_1d(x * scale) * self.mitchell_1d(y * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrates_to_one_and_is_continuous() {
+        let filter = MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0);
+
+        let n = 4000;
+        let dx = 4.0 / (n as Float);
+        let integral: Float = (0..n)
+            .map(|i| filter.mitchell_1d(-2.0 + (i as Float + 0.5) * dx) * dx)
+            .sum();
+        assert!((integral - 1.0).abs() < 1e-3);
+
+        let epsilon = 1e-4;
+        for x in [1.0, 2.0] {
+            assert!((filter.mitchell_1d(x - epsilon) - filter.mitchell_1d(x + epsilon)).abs() < 1e-3);
+        }
+        assert!(filter.mitchell_1d(1.5) < 0.0);
+    }
+}
@@ -0,0 +1,10 @@
+//! Reconstruction filters, which turn the samples taken at arbitrary points of the 
+//! image plane into pixel values: each pixel is the average of the nearby samples, 
+//! weighted by the filter at their offset from the pixel's center.
+
+pub mod traits;
+pub mod box_filter;
+pub mod tent;
+pub mod gaussian;
+pub mod mitchell;
+pub mod lanczos;
@@ -0,0 +1,30 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::math::float::Float;
+use super::traits::FilterLike;
+
+// E==== IMPORTS }}}1
+
+/// Weighs samples by how close they are to the pixel's center, falling off linearly 
+/// to zero at the radius.
+pub struct TentFilter {
+    radius: Float,
+}
+
+impl TentFilter {
+    pub fn new(radius: Float) -> Self {
+        Self {
+            radius
+        }
+    }
+}
+
+impl FilterLike for TentFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        Float::max(self.radius - x.abs(), 0.0) * Float::max(self.radius - y.abs(), 0.0)
+    }
+}
@@ -0,0 +1,11 @@
+use crate::utility::math::float::Float;
+
+pub trait FilterLike: Send + Sync {
+    /// Samples further than this from a pixel's center, along either axis, do not 
+    /// contribute to the pixel.
+    fn radius(&self) -> Float;
+
+    /// The weight of a sample at offset $(x, y)$ from a pixel's center, in pixels. 
+    /// Zero outside `radius()`, and possibly negative for filters that sharpen.
+    fn evaluate(&self, x: Float, y: Float) -> Float;
+}
@@ -2,9 +2,12 @@
 // S==== IMPORTS {{{1
 
 use crate::{
-    utility::{math::ray::Ray3, rng::RandomNumberGenerator}, 
+    utility::math::{ray::Ray3, float::Float}, 
+    samplers::traits::SamplerLike,
     light::{Spectrum, ColorConstantsQueryable}, 
-    objects::{object_group::ObjectGroup, object::SampleNewRayInfo}
+    objects::object_group::ObjectGroup,
+    environments::traits::EnvironmentLike,
+    spectral
 };
 use super::traits::IntegratorLike;
 
@@ -13,35 +16,44 @@ use super::traits::IntegratorLike;
 pub struct AmbientOcclusionIntegrator {}
 
 impl IntegratorLike for AmbientOcclusionIntegrator {
-    fn spectrum_from_ray(&self, object_group: &ObjectGroup, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum {
+    fn spectrum_from_ray(
+        &self, 
+        object_group: &ObjectGroup, 
+        environment: &dyn EnvironmentLike, 
+        ray: &Ray3, 
+        sampler: &mut dyn SamplerLike
+    ) -> Spectrum {
         /* Check if ray intersects any objects */
         
         let intersection_info = object_group.intersect(ray);
-        if let None = intersection_info.intersected_object {
-            return Spectrum::black();
+        if intersection_info.intersected_object.is_none() {
+            return spectral::illuminant(ray.wavelengths.as_ref(), &environment.radiance(&ray.direction));
         }
 
         let intersected_object = intersection_info.intersected_object.unwrap();
+        let emitted = intersected_object.emitted(ray, &intersection_info.shape_intersection_info);
 
         /* Sample one more ray to see if the intersected point lies in shadow */
 
-        let sample_result = {
-            let info = SampleNewRayInfo {
-                incoming_ray: ray,
-                shape_intersection: &intersection_info.shape_intersection_info,
-                rng,
-            };
-
-            intersected_object.sample_new_ray(info)
+        let shadow_ray = {
+            let shape_intersection = &intersection_info.shape_intersection_info;
+            let wo = (-1 as Float) * ray.direction.clone().normalize();
+            let sample = intersected_object
+                .bsdf(ray, shape_intersection)
+                .and_then(|bsdf| bsdf.sample(&wo, sampler.get_1d(), sampler.get_2d()));
+
+            match sample {
+                Some(sample) => shape_intersection.spawn_ray(sample.wi),
+                None => { return emitted; }
+            }
         };
 
-        let shadow_ray = sample_result.scattered_ray;
         let shadow_intersection = object_group.intersect(&shadow_ray);
 
         if let Some(_) = shadow_intersection.intersected_object {
-            return Spectrum::black();
+            return emitted;
         } else {
-            return Spectrum::white();
+            return emitted + spectral::illuminant(ray.wavelengths.as_ref(), &Spectrum::white());
         }
     }
 }
@@ -0,0 +1,389 @@
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    utility::math::{ray::Ray3, float::Float, vector::{dot, Vec3}},
+    samplers::traits::SamplerLike,
+    light::{Spectrum, ColorConstantsQueryable},
+    objects::{object_group::ObjectGroup, shapes::traits::ShapeIntersectionInfo, materials::bsdf::Bsdf},
+    environments::traits::EnvironmentLike,
+    spectral::{self, wavelengths::{SampledWavelengths, NUM_WAVELENGTHS}}
+};
+use super::traits::IntegratorLike;
+
+// E==== IMPORTS }}}1
+
+/// A unidirectional path tracer. At each intersection we add the light emitted by the
+/// object, sample a single scattered ray from the BSDF of the object's material and 
+/// recursively estimate the light arriving along it. Rays that escape the scene see the environment.
+///
+/// With next event estimation, we additionally sample a point on a light (or a 
+/// direction of the environment, if it can be sampled) at each (non-specular) 
+/// intersection and cast a shadow ray towards it. Light reaching the 
+/// surface directly from a light is then estimated twice, by the light sample and 
+/// by the scattered ray hitting the light, and the two estimates are combined by 
+/// multiple importance sampling. This greatly reduces noise for small lights.
+///
+/// In spectral mode, the rays of a path carry the wavelengths of the camera ray, 
+/// until it meets a dispersive surface: from there on only the hero wavelength is 
+/// followed.
+pub struct PathTracerIntegrator {
+    /// Paths are terminated (contribute nothing further) once they have bounced
+    /// this many times.
+    recursion_limit: u32,
+    next_event_estimation: bool,
+}
+
+impl PathTracerIntegrator {
+    pub fn new(recursion_limit: u32, next_event_estimation: bool) -> Self {
+        Self {
+            recursion_limit,
+            next_event_estimation,
+        }
+    }
+
+    /// `bsdf_pdf` is the density with which `ray` was sampled from the BSDF at its 
+    /// origin, if we also sampled the lights there.
+    fn spectrum_from_ray_at_depth(
+        &self,
+        object_group: &ObjectGroup,
+        environment: &dyn EnvironmentLike,
+        ray: &Ray3,
+        sampler: &mut dyn SamplerLike,
+        depth: u32,
+        bsdf_pdf: Option<Float>
+    ) -> Spectrum {
+        if depth >= self.recursion_limit {
+            return Spectrum::black();
+        }
+
+        let intersection_info = object_group.intersect(ray);
+        let intersected_object = match intersection_info.intersected_object {
+            Some(object) => object,
+            None => {
+                let radiance = spectral::illuminant(ray.wavelengths.as_ref(), &environment.radiance(&ray.direction));
+                return match bsdf_pdf {
+                    Some(bsdf_pdf) if environment.is_sampleable() => {
+                        let light_pdf = light_selection_pdf(object_group, environment) 
+                            * environment.pdf(&ray.direction);
+                        power_heuristic(bsdf_pdf, light_pdf) * radiance
+                    },
+                    _ => radiance,
+                };
+            }
+        };
+        let shape_intersection = &intersection_info.shape_intersection_info;
+
+        if let Some(wavelengths) = ray.wavelengths.as_ref().filter(|_| intersected_object.is_dispersive()) {
+            if !wavelengths.is_secondary_terminated() {
+                // Trace the path again with the hero wavelength alone. It now stands 
+                // for all of the wavelengths of the camera ray, whose average 
+                // `SampledWavelengths::to_rgb()` takes, so it counts for all of them 
+                // (see `SampledWavelengths::terminate_secondary()`).
+                let mut hero_ray = ray.clone();
+                hero_ray.wavelengths = Some(wavelengths.terminate_secondary());
+                let hero = self.spectrum_from_ray_at_depth(
+                    object_group, environment, &hero_ray, sampler, depth, bsdf_pdf
+                );
+                return Spectrum::new((NUM_WAVELENGTHS as Float) * hero.x(), 0.0, 0.0);
+            }
+        }
+
+        let emitted = {
+            let emitted = intersected_object.emitted(ray, shape_intersection);
+            match bsdf_pdf {
+                Some(bsdf_pdf) if intersected_object.is_light() => {
+                    let light_pdf = light_selection_pdf(object_group, environment) 
+                        * intersected_object.pdf_from(&ray.origin, &ray.direction);
+                    power_heuristic(bsdf_pdf, light_pdf) * emitted
+                },
+                _ => emitted,
+            }
+        };
+
+        let bsdf = match intersected_object.bsdf(ray, shape_intersection) {
+            Some(bsdf) => bsdf,
+            None => { return emitted; }
+        };
+        let wo = (-1 as Float) * ray.direction.clone().normalize();
+
+        // A light sample extends the path by a bounce, just like the scattered ray.
+        let sample_lights = self.next_event_estimation 
+            && !bsdf.is_specular() 
+            && depth + 1 < self.recursion_limit;
+        let direct = if sample_lights {
+            sample_light(object_group, environment, shape_intersection, &bsdf, &wo, ray.wavelengths.as_ref(), sampler)
+        } else {
+            Spectrum::black()
+        };
+
+        let sample = match bsdf.sample(&wo, sampler.get_1d(), sampler.get_2d()) {
+            Some(sample) if sample.pdf > (0 as Float) => sample,
+            _ => { return emitted + direct; }
+        };
+
+        let cos_theta = Float::abs(dot(&sample.wi, &shape_intersection.surface_normal));
+        let mut scattered_ray = shape_intersection.spawn_ray(sample.wi);
+        scattered_ray.wavelengths = ray.wavelengths;
+        let scattered_bsdf_pdf = (sample_lights && !sample.is_specular).then_some(sample.pdf);
+        let incoming = self.spectrum_from_ray_at_depth(
+            object_group, environment, &scattered_ray, sampler, depth + 1, scattered_bsdf_pdf
+        );
+
+        // Monte Carlo estimate of the rendering equation with a single sample:
+        // f * (incoming light) * |cos| / (sampling density).
+        emitted + direct + (cos_theta / sample.pdf) * (sample.value * incoming)
+    }
+}
+
+impl IntegratorLike for PathTracerIntegrator {
+    fn spectrum_from_ray(
+        &self, 
+        object_group: &ObjectGroup, 
+        environment: &dyn EnvironmentLike, 
+        ray: &Ray3, 
+        sampler: &mut dyn SamplerLike
+    ) -> Spectrum {
+        self.spectrum_from_ray_at_depth(object_group, environment, ray, sampler, 0, None)
+    }
+}
+
+// S==== HELPERS {{{1
+
+/// The lights are the objects that are lights, followed by the environment if it 
+/// can be sampled.
+fn num_lights(object_group: &ObjectGroup, environment: &dyn EnvironmentLike) -> usize {
+    object_group.lights().len() + (environment.is_sampleable() as usize)
+}
+
+/// The probability of choosing any particular light, as we choose uniformly.
+fn light_selection_pdf(object_group: &ObjectGroup, environment: &dyn EnvironmentLike) -> Float {
+    1.0 / (num_lights(object_group, environment) as Float)
+}
+
+/// Veach's power heuristic (with exponent 2): the weight of a sample taken with 
+/// density `pdf` when the same light could also have been sampled with density 
+/// `other_pdf`.
+fn power_heuristic(pdf: Float, other_pdf: Float) -> Float {
+    let (pdf2, other_pdf2) = (pdf * pdf, other_pdf * other_pdf);
+    if pdf2.is_infinite() {
+        return 1.0;
+    }
+    if pdf2 + other_pdf2 == 0.0 {
+        return 0.0;
+    }
+
+    pdf2 / (pdf2 + other_pdf2)
+}
+
+/// The light arriving at the intersection directly from a randomly chosen light 
+/// and scattered towards `wo`, weighted for multiple importance sampling.
+fn sample_light(
+    object_group: &ObjectGroup,
+    environment: &dyn EnvironmentLike,
+    shape_intersection: &ShapeIntersectionInfo,
+    bsdf: &Bsdf,
+    wo: &Vec3,
+    wavelengths: Option<&SampledWavelengths>,
+    sampler: &mut dyn SamplerLike
+) -> Spectrum {
+    let num_lights = num_lights(object_group, environment);
+    if num_lights == 0 {
+        return Spectrum::black();
+    }
+
+    let index = ((sampler.get_1d() * (num_lights as Float)) as usize).min(num_lights - 1);
+    let u = sampler.get_2d();
+    let incident = if index < object_group.lights().len() {
+        sample_object_light(object_group, index, shape_intersection, wavelengths, u)
+    } else {
+        sample_environment(object_group, environment, shape_intersection, wavelengths, u)
+    };
+    let (wi, emitted, pdf) = match incident {
+        Some(incident) => incident,
+        None => { return Spectrum::black(); }
+    };
+
+    let f = bsdf.eval(wo, &wi);
+    let cos_theta = Float::abs(dot(&wi, &shape_intersection.surface_normal));
+    let light_pdf = light_selection_pdf(object_group, environment) * pdf;
+    let weight = power_heuristic(light_pdf, bsdf.pdf(wo, &wi));
+
+    (weight * cos_theta / light_pdf) * (f * emitted)
+}
+
+/// Samples a point on the `index`th light. If it is visible from the intersection, 
+/// returns the (unit) direction towards it, the light it emits towards the 
+/// intersection and the density of the direction.
+fn sample_object_light(
+    object_group: &ObjectGroup,
+    index: usize,
+    shape_intersection: &ShapeIntersectionInfo,
+    wavelengths: Option<&SampledWavelengths>,
+    u: (Float, Float)
+) -> Option<(Vec3, Spectrum, Float)> {
+    let light = &object_group.lights()[index];
+    let light_sample = light.sample_from(&shape_intersection.point, u).filter(|sample| sample.pdf > 0.0)?;
+
+    // The light is at t=1 along the shadow ray, and is visible if nothing comes first.
+    let mut shadow_ray = shape_intersection.spawn_ray_to(&light_sample.point);
+    shadow_ray.wavelengths = wavelengths.copied();
+    let shadow_intersection = object_group.intersect(&shadow_ray);
+    let is_visible = shadow_intersection.intersected_object
+        .as_ref()
+        .is_some_and(|object| Arc::ptr_eq(object, light));
+    if !is_visible {
+        return None;
+    }
+
+    let emitted = light.emitted(&shadow_ray, &shadow_intersection.shape_intersection_info);
+    Some((shadow_ray.direction.normalize(), emitted, light_sample.pdf))
+}
+
+/// Like `sample_object_light()`, for the environment: it is visible if the shadow 
+/// ray escapes the scene.
+fn sample_environment(
+    object_group: &ObjectGroup,
+    environment: &dyn EnvironmentLike,
+    shape_intersection: &ShapeIntersectionInfo,
+    wavelengths: Option<&SampledWavelengths>,
+    u: (Float, Float)
+) -> Option<(Vec3, Spectrum, Float)> {
+    let sample = environment.sample(u).filter(|sample| sample.pdf > 0.0)?;
+
+    let shadow_ray = shape_intersection.spawn_ray(sample.direction.clone());
+    if object_group.intersect(&shadow_ray).intersected_object.is_some() {
+        return None;
+    }
+
+    let radiance = spectral::illuminant(wavelengths, &sample.radiance);
+    Some((sample.direction, radiance, sample.pdf))
+}
+
+// E==== HELPERS }}}1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        environments::constant::ConstantEnvironment,
+        objects::{
+            object::{Object, ObjectInfo},
+            object_group::ObjectGroupMember,
+            materials::{traits::MaterialLike, lambertian::Lambertian, diffuse_light::DiffuseLight},
+            textures::constant::ConstantTexture,
+            shapes::{sphere::{Sphere, SphereInfo}, transform::Transform}
+        },
+        samplers::independent::IndependentSampler,
+        scene_parsing,
+        utility::math::vector::{Point3, Color3}
+    };
+
+    #[test]
+    fn recursion_limit_counts_bounces() {
+        // A light in front of the origin and a matte ball behind it, under a grey sky.
+        let ball = |z: Float, material: Arc<dyn MaterialLike>| ObjectGroupMember::Object(Arc::new(Object::new(ObjectInfo {
+            shape: Arc::new(Sphere::new(SphereInfo {
+                center: Point3::new(0.0, 0.0, z),
+                radius: 1.0,
+                transform: Transform::default(),
+            })),
+            texture: Arc::new(ConstantTexture::new_from_rgb(Color3::new(0.5, 0.5, 0.5))),
+            material,
+        })));
+        let objects = ObjectGroup::new_from_vector(vec![
+            ball(-3.0, Arc::new(DiffuseLight::new(Spectrum::new(4.0, 4.0, 4.0)))),
+            ball(3.0, Arc::new(Lambertian {})),
+        ]);
+        let environment = ConstantEnvironment::new(Spectrum::new(0.5, 0.5, 0.5));
+        let mut sampler = IndependentSampler::new(1);
+        let mut radiance = |recursion_limit: u32, direction: Vec3| {
+            let ray = Ray3::new(Point3::new(0.0, 0.0, 0.0), direction);
+            PathTracerIntegrator::new(recursion_limit, true)
+                .spectrum_from_ray(&objects, &environment, &ray, &mut sampler)
+                .x()
+        };
+        let (to_light, to_ball, to_sky) = (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Without any bounces, nothing is seen at all.
+        for direction in [&to_light, &to_ball, &to_sky] {
+            assert_eq!(radiance(0, direction.clone()), 0.0);
+        }
+
+        // With one, only what the camera ray hits directly: the light and the sky 
+        // but not the ball, which is only lit by light that bounced off it.
+        assert_eq!(radiance(1, to_light.clone()), 4.0);
+        assert_eq!(radiance(1, to_sky.clone()), 0.5);
+        assert_eq!(radiance(1, to_ball.clone()), 0.0);
+        assert!(radiance(2, to_ball) > 0.0);
+    }
+
+    /// The average color of a glass ball filling the view, in spectral mode, under 
+    /// a white sky. `abbe_number` is `None` for glass without dispersion.
+    fn glass_ball_under_white_sky(abbe_number: Option<Float>) -> Color3 {
+        let abbe_number = match abbe_number {
+            Some(abbe_number) => format!(r#", "abbe number": {}"#, abbe_number),
+            None => String::new(),
+        };
+        let json = format!(r#"
+            {{
+                "camera": {{
+                    "resolution": [16, 16],
+                    "focal distance": 1,
+                    "vertical fov": 30,
+                    "aperture radius": 0,
+                    "transform": {{
+                        "viewer": {{
+                            "look_from": [0, 0, 4],
+                            "look_at": [0, 0, 0],
+                            "up_direction": [0, 1, 0]
+                        }}
+                    }}
+                }},
+                "integrator": {{
+                    "kind": "path tracer",
+                    "number of samples": 128,
+                    "ray recursion limit": 32,
+                    "number of threads": 1,
+                    "spectral": true
+                }},
+                "environment": {{ "kind": "constant", "rgb color": [1, 1, 1] }},
+                "textures": [
+                    {{ "name": "white", "kind": "constant", "rgb color": [1, 1, 1] }}
+                ],
+                "materials": [
+                    {{ "name": "glass", "kind": "dielectric", "index of refraction": 1.5{} }}
+                ],
+                "objects": [
+                    {{
+                        "shape": {{ "kind": "sphere", "center": [0, 0, 0], "radius": 1 }},
+                        "texture": "white",
+                        "material": "glass"
+                    }}
+                ]
+            }}"#, abbe_number);
+
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let mut scene = scene_parsing::parse_json(&json, std::path::Path::new("")).unwrap();
+        let image = scene.ray_trace();
+        let resolution = scene.new_image_buffer().resolution().clone();
+        let num_pixels = (resolution.width * resolution.height) as Float;
+        let sum = resolution
+            .into_iter()
+            .fold(Color3::new(0.0, 0.0, 0.0), |sum, pixel| sum + image.get_pixel_color(&pixel));
+
+        (1.0 / num_pixels) * sum
+    }
+
+    #[test]
+    fn dispersion_keeps_white_light_white() {
+        // Glass neither absorbs nor emits light, so under a white sky it is as white 
+        // as the sky, whether or not it splits the light into its colors.
+        let plain = glass_ball_under_white_sky(None);
+        let dispersive = glass_ball_under_white_sky(Some(15.0));
+        for (plain, dispersive) in [(plain.x(), dispersive.x()), (plain.y(), dispersive.y()), (plain.z(), dispersive.z())] {
+            assert!((plain - 1.0).abs() < 0.03, "plain glass: {:?}", plain);
+            assert!((dispersive - 1.0).abs() < 0.03, "dispersive glass: {:?}", dispersive);
+        }
+    }
+}
@@ -1,12 +1,23 @@
 
 use crate::{
-    utility::{math::ray::Ray3, rng::RandomNumberGenerator}, 
+    utility::math::ray::Ray3, 
+    samplers::traits::SamplerLike,
     light::Spectrum, 
-    objects::object_group::ObjectGroup
+    objects::object_group::ObjectGroup,
+    environments::traits::EnvironmentLike
 };
 
 
-pub trait IntegratorLike {
-    fn spectrum_from_ray(&self, object_group: &ObjectGroup, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum;
+/// A single integrator is used by every thread rendering the scene, hence `Send + Sync`.
+pub trait IntegratorLike: Send + Sync {
+    /// The light arriving at the origin of `ray` from its direction. Rays that escape
+    /// the objects see `environment`. The random decisions are driven by `sampler`.
+    fn spectrum_from_ray(
+        &self, 
+        object_group: &ObjectGroup, 
+        environment: &dyn EnvironmentLike, 
+        ray: &Ray3, 
+        sampler: &mut dyn SamplerLike
+    ) -> Spectrum;
 }
 
@@ -1,38 +1,12 @@
 use crate::utility::math::{vector::Color3, float::Float};
 
 
-pub type Light = Color3;
-
-enum KindOfLight {
-    RGB,
-}
-
-trait KindOfLightCheckable {
-    fn kind_of_light() -> KindOfLight;
-}
-
-impl KindOfLightCheckable for Color3 {
-    fn kind_of_light() -> KindOfLight {
-        KindOfLight::RGB
-    }
-}
-
+/// Light, or how much of it is reflected. Its three components are the red, green
+/// and blue channels, or, in spectral mode, the values at the three wavelengths the
+/// ray carries (see `spectral::wavelengths::SampledWavelengths`). Either way
+/// spectra are multiplied and added componentwise.
 pub type Spectrum = Color3;
 
-pub trait ConvertableToLight {
-    fn to_light(&self, wavelength: Float) -> Light;
-}
-
-impl ConvertableToLight for Color3 {
-    fn to_light(&self, wavelength: Float) -> Light {
-        match Light::kind_of_light() {
-            KindOfLight::RGB => {
-                return self.clone();
-            }
-        }
-    }
-}
-
 pub trait ColorConstantsQueryable {
     fn black() -> Self;
     fn white() -> Self;
@@ -48,3 +22,28 @@ impl ColorConstantsQueryable for Color3 {
     }
 }
 
+
+/// Decodes a component of an sRGB encoded color (e.g. from an 8-bit image) to the 
+/// linear values we do our computations with. Both are in $[0,1]$.
+pub fn srgb_to_linear(encoded: Float) -> Float {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        Float::powf((encoded + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// Encodes a linear component in $[0,1]$ with the sRGB transfer function, the 
+/// inverse of `srgb_to_linear`.
+pub fn linear_to_srgb(linear: Float) -> Float {
+    if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * Float::powf(linear, 1.0 / 2.4) - 0.055
+    }
+}
+
+/// The brightness of a linear color as perceived by the eye (Rec. 709 weights).
+pub fn luminance(color: &Color3) -> Float {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
@@ -1,19 +1,94 @@
 #![allow(dead_code)]
 
 use tracing::{debug, error, info, span, warn, Level};
-use std::{env, fs::{File, read_to_string}};
+use std::{env, fs::{File, read_to_string}, path::Path, process, sync::atomic::{AtomicBool, Ordering}};
+use utility::{image::{ImageBuffer, OutputFormat}, tone_mapping::DisplayTransform};
+use render_state::RenderState;
+use scene::AdaptiveSampling;
 
 mod config;
 mod utility;
 mod objects;
 mod camera;
 mod scene;
+mod render_state;
+mod aovs;
 mod ray_tracer;
 mod sampler;
 mod light;
+mod spectral;
 mod integrators;
+mod environments;
+mod filters;
+mod denoisers;
+mod samplers;
 mod scene_parsing;
 
+/// Set by the first Ctrl-C, asking the render to stop early.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// What the command line asks for: 
+/// `mirth <scene file> [output image] [--state <file>] [--resume] [--samples <n>]`.
+struct CommandLine {
+    scene_filename: String,
+    output_filename: Option<String>,
+    /// Where the render state is saved at every checkpoint and at the end, so that 
+    /// the render can be resumed.
+    state_filename: Option<String>,
+    /// Continue from the render state rather than starting over.
+    resume: bool,
+    /// Overrides the scene's number of samples per pixel.
+    num_samples: Option<u32>,
+}
+
+impl CommandLine {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut state_filename = None;
+        let mut resume = false;
+        let mut num_samples = None;
+
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--state" => {
+                    let filename = args.next().ok_or("--state expects a filename")?;
+                    state_filename = Some(filename.clone());
+                },
+                "--resume" => resume = true,
+                "--samples" => {
+                    let n = args.next()
+                        .and_then(|n| n.parse::<u32>().ok())
+                        .filter(|n| *n > 0)
+                        .ok_or("--samples expects a positive integer")?;
+                    num_samples = Some(n);
+                },
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        if resume && state_filename.is_none() {
+            return Err("--resume needs the render state given with --state".to_string());
+        }
+        if positional.len() > 2 {
+            return Err(format!("unexpected argument '{}'", positional[2]));
+        }
+
+        let mut positional = positional.into_iter();
+        let scene_filename = positional.next()
+            .ok_or("no filename specified (as the 1st argument)")?;
+
+        Ok(Self {
+            scene_filename,
+            output_filename: positional.next(),
+            state_filename,
+            resume,
+            num_samples,
+        })
+    }
+}
+
 struct InternalState {
     tracing_subscriber: Box<dyn tracing::Subscriber>,
 }
@@ -23,31 +98,36 @@ fn main() {
     config::validate_config();
 
     let args: Vec<String> = env::args().collect();
-
-    let scene_file = {
-        let filename = args.get(1);
-        if filename.is_none() {
-            error!("no filename specified (as the 1st argument)");
-            panic!();
+    let command_line = match CommandLine::parse(&args) {
+        Ok(command_line) => command_line,
+        Err(e) => {
+            error!("{}", e);
+            process::exit(1);
         }
-        
-        let file_string = read_to_string(filename.unwrap());
+    };
+
+    let scene_filename = &command_line.scene_filename;
+    let scene_json = {
+        let file_string = read_to_string(scene_filename);
         if file_string.is_err() {
-            error!("could not open file '{}'", filename.unwrap());
+            error!("could not open file '{}'", scene_filename);
             panic!();
         }
 
-        file_string.unwrap()
-    };
-
-    let mut scene = {
-        let json = serde_json::from_str::<serde_json::Value>(&scene_file);
+        let json = serde_json::from_str::<serde_json::Value>(&file_string.unwrap());
         if json.is_err() {
             error!("couldn't parse json: {}", json.unwrap_err());
             panic!();
         }
 
-        let parsed = scene_parsing::parse_json(&json.unwrap());
+        json.unwrap()
+    };
+
+    let scene_directory = Path::new(scene_filename)
+        .parent()
+        .unwrap_or(Path::new(""));
+    let mut scene = {
+        let parsed = scene_parsing::parse_json(&scene_json, scene_directory);
         if parsed.is_err() {
             error!("failed to parse scene: {}", parsed.unwrap_err());
             panic!();
@@ -57,7 +137,130 @@ fn main() {
     };
     info!("finished parsing scene");
 
-    scene.ray_trace();
+    // The command line takes precedence over the scene file.
+    let output_filename = match command_line.output_filename.as_deref().or(scene.output_filename()) {
+        Some(filename) => filename.to_string(),
+        None => {
+            error!("no output file specified (as the 2nd argument or the scene's \"output\" field)");
+            process::exit(1);
+        }
+    };
+    // Check this before rendering rather than finding out afterwards.
+    if let Err(e) = OutputFormat::new_from_filename(&output_filename) {
+        error!("{}", e);
+        process::exit(1);
+    }
+
+    if let Some(num_samples) = command_line.num_samples {
+        scene.set_num_samples(num_samples);
+    }
+
+    let scene_hash = scene_parsing::scene_hash(&scene_json, scene_directory);
+    let image_buffer = if command_line.resume {
+        // `CommandLine::parse` checks that resuming comes with a state file.
+        let state_filename = command_line.state_filename.as_deref().unwrap();
+        let state = match RenderState::load_from_file(state_filename) {
+            Ok(state) => state,
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        };
+        if state.scene_hash != scene_hash || state.seed != scene.seed() {
+            error!("'{}' was saved from a different scene; refusing to resume", state_filename);
+            process::exit(1);
+        }
+        if state.sampler_samples_per_pixel != scene.sampler_samples_per_pixel() {
+            // The points of the new samples would not fit in with those taken so far.
+            error!(
+                "'{}' was saved with the sampler laid out for a different number of samples; refusing to resume \
+                (change the number of samples with --samples instead)",
+                state_filename
+            );
+            process::exit(1);
+        }
+
+        info!("resuming from {:.1} samples per pixel", state.image_buffer.samples_per_pixel());
+        state.image_buffer
+    } else {
+        scene.new_image_buffer()
+    };
+
+    // The denoiser is guided by the AOVs, which do not change as samples are added.
+    let aovs = (scene.denoiser().is_some() || !scene.aov_outputs().is_empty())
+        .then(|| scene.render_aovs());
+    if let Some(aovs) = &aovs {
+        for (kind, filename) in scene.aov_outputs() {
+            match aovs.get(*kind).save_to_file(filename, &DisplayTransform::default()) {
+                Ok(()) => info!("wrote {} to '{}'", kind.name(), filename),
+                Err(e) => error!("{}", e),
+            }
+        }
+    }
+
+    let save = |image_buffer: &ImageBuffer| -> bool {
+        let image_result = match (scene.denoiser(), &aovs) {
+            (Some(denoiser), Some(aovs)) => denoiser
+                .denoise(&image_buffer.average_samples(), aovs)
+                .save_to_file(&output_filename, scene.display()),
+            _ => image_buffer.save_to_file(&output_filename, scene.display()),
+        };
+        if let Err(e) = &image_result {
+            error!("{}", e);
+        }
+
+        let state_result = match &command_line.state_filename {
+            Some(state_filename) => {
+                let state = RenderState {
+                    scene_hash,
+                    seed: scene.seed(),
+                    sampler_samples_per_pixel: scene.sampler_samples_per_pixel(),
+                    image_buffer: image_buffer.clone(),
+                };
+                state.save_to_file(state_filename).map_err(|e| error!("{}", e))
+            },
+            None => Ok(()),
+        };
+
+        let map_result = match scene.adaptive_sampling() {
+            Some(AdaptiveSampling { sample_count_map: Some(filename), max_samples, .. }) => {
+                image_buffer.sample_count_map(*max_samples)
+                    .save_to_file(filename, &DisplayTransform::default())
+                    .map_err(|e| error!("{}", e))
+            },
+            _ => Ok(()),
+        };
+
+        image_result.is_ok() && state_result.is_ok() && map_result.is_ok()
+    };
+
+    install_interrupt_handler();
+    let write_checkpoint = |image_buffer: &ImageBuffer| {
+        if save(image_buffer) {
+            info!("wrote checkpoint to '{}'", output_filename);
+        }
+    };
+    let image_buffer = scene.ray_trace_progressively(image_buffer, &STOP_REQUESTED, write_checkpoint);
+
+    if !save(&image_buffer) {
+        process::exit(1);
+    }
+    info!("wrote image to '{}'", output_filename);
+}
+
+/// The first Ctrl-C stops the render after the current sample, keeping what has 
+/// been rendered; the second quits at once.
+fn install_interrupt_handler() {
+    let result = ctrlc::set_handler(|| {
+        if STOP_REQUESTED.swap(true, Ordering::Relaxed) {
+            process::exit(130);
+        }
+        warn!("interrupted: stopping the render (interrupt again to quit without writing the image)");
+    });
+
+    if let Err(e) = result {
+        warn!("could not install interrupt handler: {}", e);
+    }
 }
 
 fn initialize_internal_state() {
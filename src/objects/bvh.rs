@@ -0,0 +1,392 @@
+//! A bounding volume hierarchy (BVH): a binary tree whose nodes store a box
+//! containing everything below them. A ray that misses a node's box cannot hit
+//! anything in that subtree, so most of the scene is never tested against the ray.
+//!
+//! The hierarchy only knows the bounding boxes of its primitives and refers to them
+//! by their index. This way the same structure serves any collection of things that
+//! can be bounded and intersected.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    config::AccStructureAxisSelectionMethod,
+    utility::{
+        math::{bounding_box::BoundingBox, float::Float, ray::Ray3, vector::Point3},
+        rng::RandomNumberGenerator
+    }
+};
+
+// E==== IMPORTS }}}1
+
+/// Nodes with at most this many primitives are never split.
+const MAX_PRIMITIVES_PER_LEAF: usize = 4;
+/// The surface area heuristic may decide not to split a node, but nodes with more
+/// than this many primitives are split regardless.
+const SAH_MAX_PRIMITIVES_PER_LEAF: usize = 16;
+/// The number of candidate split positions per axis considered by the surface area
+/// heuristic.
+const SAH_NUM_BUCKETS: usize = 12;
+/// The cost of visiting a node, relative to intersecting a primitive.
+const SAH_TRAVERSAL_COST: Float = 0.125;
+
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    /// The primitives, reordered so that each leaf refers to a contiguous range.
+    primitive_indices: Vec<usize>,
+}
+
+struct BvhNode {
+    bounding_box: BoundingBox,
+    kind: BvhNodeKind,
+}
+
+enum BvhNodeKind {
+    Leaf { first: usize, count: usize },
+    /// The left child immediately follows its parent in `Bvh::nodes`. `axis` is the
+    /// axis the primitives were split along.
+    Interior { right_child: usize, axis: usize },
+}
+
+struct BuildPrimitive {
+    index: usize,
+    bounding_box: BoundingBox,
+    centroid: Point3,
+}
+
+struct BvhBuilder {
+    method: AccStructureAxisSelectionMethod,
+    rng: RandomNumberGenerator,
+    nodes: Vec<BvhNode>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy over the primitives with the given (global space)
+    /// bounding boxes. The primitive at `primitive_bounds[i]` is referred to by `i`.
+    pub fn new(primitive_bounds: &[BoundingBox], method: AccStructureAxisSelectionMethod) -> Self {
+        let mut primitives: Vec<BuildPrimitive> = primitive_bounds
+            .iter()
+            .enumerate()
+            .map(|(index, bounding_box)| BuildPrimitive {
+                index,
+                bounding_box: bounding_box.clone(),
+                centroid: bounding_box.centroid(),
+            })
+            .collect();
+
+        let mut builder = BvhBuilder {
+            method,
+            // The hierarchy should not depend on anything but its input.
+            rng: RandomNumberGenerator::from_seed(1),
+            nodes: Vec::new(),
+        };
+        if !primitives.is_empty() {
+            builder.build(&mut primitives, 0, 0);
+        }
+
+        Self {
+            nodes: builder.nodes,
+            primitive_indices: primitives.iter().map(|p| p.index).collect(),
+        }
+    }
+
+    /// The box containing every primitive.
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self.nodes.first() {
+            Some(root) => root.bounding_box.clone(),
+            None => BoundingBox::empty(),
+        }
+    }
+
+    /// Finds the closest intersection of `ray` with the primitives.
+    ///
+    /// `intersect_primitive(index, ray)` should intersect the primitive `index` with
+    /// `ray`, returning the $t$ of the intersection if there is one in the ray's range.
+    /// After each hit, the range of the ray passed to subsequent calls is shortened
+    /// to end at that hit, so the last hit reported is the closest. Subtrees whose
+    /// boxes lie entirely beyond the closest hit so far are skipped.
+    pub fn traverse<F>(&self, ray: &Ray3, mut intersect_primitive: F)
+    where
+        F: FnMut(usize, &Ray3) -> Option<Float>
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut working_ray = ray.clone();
+        let mut to_visit: Vec<usize> = vec![0];
+
+        while let Some(node_index) = to_visit.pop() {
+            let node = &self.nodes[node_index];
+            if node.bounding_box.intersect(&working_ray).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                BvhNodeKind::Leaf { first, count } => {
+                    for &primitive_index in self.primitive_indices[first..first + count].iter() {
+                        if let Some(t) = intersect_primitive(primitive_index, &working_ray) {
+                            working_ray.max_t = t;
+                        }
+                    }
+                },
+                BvhNodeKind::Interior { right_child, axis } => {
+                    // Visit the child nearer to the ray's origin first, since hits
+                    // there let us skip more of the other child.
+                    let left_child = node_index + 1;
+                    if working_ray.direction.component(axis) < 0.0 {
+                        to_visit.push(left_child);
+                        to_visit.push(right_child);
+                    } else {
+                        to_visit.push(right_child);
+                        to_visit.push(left_child);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl BvhBuilder {
+    /// Builds the subtree over `primitives`, which start at position `offset` in the
+    /// final primitive ordering. Returns the index of the subtree's root.
+    fn build(&mut self, primitives: &mut [BuildPrimitive], offset: usize, depth: usize) -> usize {
+        let bounding_box = primitives
+            .iter()
+            .fold(BoundingBox::empty(), |acc, p| acc.union(&p.bounding_box));
+
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounding_box: bounding_box.clone(),
+            kind: BvhNodeKind::Leaf { first: offset, count: primitives.len() },
+        });
+
+        if primitives.len() <= MAX_PRIMITIVES_PER_LEAF {
+            return node_index;
+        }
+
+        let (axis, mid) = match self.method {
+            AccStructureAxisSelectionMethod::SurfaceAreaHeuristic => {
+                match split_by_surface_area(primitives, &bounding_box) {
+                    Some(split) => split,
+                    None => { return node_index; }
+                }
+            },
+            _ => {
+                let axis = self.choose_axis(primitives, depth);
+                (axis, split_at_median(primitives, axis))
+            }
+        };
+
+        let (left, right) = primitives.split_at_mut(mid);
+        self.build(left, offset, depth + 1);
+        let right_child = self.build(right, offset + mid, depth + 1);
+        self.nodes[node_index].kind = BvhNodeKind::Interior { right_child, axis };
+
+        node_index
+    }
+
+    fn choose_axis(&mut self, primitives: &[BuildPrimitive], depth: usize) -> usize {
+        match self.method {
+            AccStructureAxisSelectionMethod::Random => {
+                usize::min((self.rng.next_float() * 3.0) as usize, 2)
+            },
+            AccStructureAxisSelectionMethod::Alternating => depth % 3,
+            AccStructureAxisSelectionMethod::LargestExtent
+            | AccStructureAxisSelectionMethod::SurfaceAreaHeuristic => {
+                centroid_bounds(primitives).largest_extent_axis()
+            },
+        }
+    }
+}
+
+fn centroid_bounds(primitives: &[BuildPrimitive]) -> BoundingBox {
+    primitives
+        .iter()
+        .fold(BoundingBox::empty(), |acc, p| acc.union_point(&p.centroid))
+}
+
+/// Partially sorts `primitives` so that the first half have the smallest centroids
+/// along `axis`, returning the size of that half.
+fn split_at_median(primitives: &mut [BuildPrimitive], axis: usize) -> usize {
+    let mid = primitives.len() / 2;
+    primitives.select_nth_unstable_by(mid, |a, b| {
+        a.centroid.component(axis).total_cmp(&b.centroid.component(axis))
+    });
+
+    mid
+}
+
+/// Buckets the primitives by centroid along each axis, and evaluates the surface
+/// area heuristic at each bucket boundary: the expected cost of a split is the cost of
+/// intersecting each child's primitives, weighted by the probability that a ray
+/// hitting the parent's box also hits the child's box (the ratio of their areas).
+///
+/// Returns the chosen axis and the number of primitives (moved to the front) going to
+/// the left child, or `None` if not splitting is cheaper (and the node is small).
+fn split_by_surface_area(
+    primitives: &mut [BuildPrimitive],
+    bounding_box: &BoundingBox
+) -> Option<(usize, usize)> {
+    let centroid_bounds = centroid_bounds(primitives);
+    let bucket_of = |p: &BuildPrimitive, axis: usize| -> usize {
+        let min = centroid_bounds.min.component(axis);
+        let extent = centroid_bounds.max.component(axis) - min;
+        let relative = (p.centroid.component(axis) - min) / extent;
+        usize::min((relative * SAH_NUM_BUCKETS as Float) as usize, SAH_NUM_BUCKETS - 1)
+    };
+
+    // (cost, axis, last bucket in the left child)
+    let mut best: Option<(Float, usize, usize)> = None;
+
+    for axis in 0..3 {
+        let extent = centroid_bounds.max.component(axis) - centroid_bounds.min.component(axis);
+        if extent <= 0.0 {
+            continue;
+        }
+
+        let mut counts = [0usize; SAH_NUM_BUCKETS];
+        let mut boxes: [BoundingBox; SAH_NUM_BUCKETS] = std::array::from_fn(|_| BoundingBox::empty());
+        for p in primitives.iter() {
+            let bucket = bucket_of(p, axis);
+            counts[bucket] += 1;
+            boxes[bucket] = boxes[bucket].union(&p.bounding_box);
+        }
+
+        // Sweep from the right to get the area and count right of each boundary...
+        let mut right_areas = [0.0 as Float; SAH_NUM_BUCKETS];
+        let mut right_counts = [0usize; SAH_NUM_BUCKETS];
+        let mut right_box = BoundingBox::empty();
+        let mut right_count = 0;
+        for bucket in (1..SAH_NUM_BUCKETS).rev() {
+            right_box = right_box.union(&boxes[bucket]);
+            right_count += counts[bucket];
+            right_areas[bucket] = right_box.surface_area();
+            right_counts[bucket] = right_count;
+        }
+
+        // ...then from the left to finish the cost of each boundary.
+        let mut left_box = BoundingBox::empty();
+        let mut left_count = 0;
+        for bucket in 0..(SAH_NUM_BUCKETS - 1) {
+            left_box = left_box.union(&boxes[bucket]);
+            left_count += counts[bucket];
+            if left_count == 0 || right_counts[bucket + 1] == 0 {
+                continue;
+            }
+
+            let cost = SAH_TRAVERSAL_COST
+                + (left_box.surface_area() * left_count as Float
+                    + right_areas[bucket + 1] * right_counts[bucket + 1] as Float)
+                / bounding_box.surface_area();
+            if best.as_ref().is_none_or(|(best_cost, _, _)| cost < *best_cost) {
+                best = Some((cost, axis, bucket));
+            }
+        }
+    }
+
+    let leaf_cost = primitives.len() as Float;
+    let must_split = primitives.len() > SAH_MAX_PRIMITIVES_PER_LEAF;
+    match best {
+        Some((cost, axis, last_left_bucket)) if cost < leaf_cost || must_split => {
+            // Move the primitives of the left buckets to the front.
+            let mut mid = 0;
+            for i in 0..primitives.len() {
+                if bucket_of(&primitives[i], axis) <= last_left_bucket {
+                    primitives.swap(i, mid);
+                    mid += 1;
+                }
+            }
+            Some((axis, mid))
+        },
+        // Everything is in the same place: any split is as good as any other, but we
+        // still split so that leaves stay small.
+        None => {
+            let axis = centroid_bounds.largest_extent_axis();
+            Some((axis, split_at_median(primitives, axis)))
+        },
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        objects::shapes::{
+            sphere::{Sphere, SphereInfo},
+            transform::Transform,
+            traits::{IntersectableShape, BoundableShape}
+        },
+        utility::math::vector::Vec3
+    };
+
+    fn random_spheres(rng: &mut RandomNumberGenerator, count: usize) -> Vec<Sphere> {
+        (0..count)
+            .map(|_| Sphere::new(SphereInfo {
+                center: Point3::new(
+                    20.0 * rng.next_float() - 10.0,
+                    20.0 * rng.next_float() - 10.0,
+                    20.0 * rng.next_float() - 10.0
+                ),
+                radius: 0.1 + 0.5 * rng.next_float(),
+                transform: Transform::default(),
+            }))
+            .collect()
+    }
+
+    fn closest_hit_linear(spheres: &[Sphere], ray: &Ray3) -> Option<Float> {
+        spheres
+            .iter()
+            .map(|s| s.intersect(ray))
+            .filter(|info| info.did_hit)
+            .map(|info| info.t)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    #[test]
+    fn bvh_matches_linear_scan() {
+        let mut rng = RandomNumberGenerator::from_seed(7);
+        let spheres = random_spheres(&mut rng, 300);
+        let bounds: Vec<BoundingBox> = spheres.iter().map(|s| s.bounding_box()).collect();
+
+        let methods = [
+            AccStructureAxisSelectionMethod::Random,
+            AccStructureAxisSelectionMethod::Alternating,
+            AccStructureAxisSelectionMethod::LargestExtent,
+            AccStructureAxisSelectionMethod::SurfaceAreaHeuristic,
+        ];
+        for method in methods {
+            let bvh = Bvh::new(&bounds, method);
+
+            for _ in 0..500 {
+                let origin = Point3::new(
+                    30.0 * rng.next_float() - 15.0,
+                    30.0 * rng.next_float() - 15.0,
+                    30.0 * rng.next_float() - 15.0
+                );
+                let direction = Vec3::new(
+                    rng.next_float() - 0.5, rng.next_float() - 0.5, rng.next_float() - 0.5
+                );
+                let ray = Ray3::new(origin, direction);
+
+                let mut closest: Option<Float> = None;
+                bvh.traverse(&ray, |index, working_ray| {
+                    let info = spheres[index].intersect(working_ray);
+                    if !info.did_hit { return None; }
+                    closest = Some(info.t);
+                    Some(info.t)
+                });
+
+                assert_eq!(closest, closest_hit_linear(&spheres, &ray), "{:?}", method);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_bvh() {
+        let bvh = Bvh::new(&[], AccStructureAxisSelectionMethod::SurfaceAreaHeuristic);
+        let ray = Ray3::new(Point3::origin(), Vec3::new(0.0, 0.0, 1.0));
+        bvh.traverse(&ray, |_, _| panic!("there is nothing to intersect"));
+        assert!(bvh.bounding_box().is_empty());
+    }
+}
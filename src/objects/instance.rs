@@ -0,0 +1,401 @@
+//! Instancing: a prototype, i.e. a group of objects such as a tree or a bolt, is
+//! built once and placed in the scene any number of times, each time with its own
+//! transform and possibly its own material. The instances share the prototype's
+//! objects and its acceleration structure, so a thousand trees cost little more
+//! memory than one.
+
+// S==== IMPORTS {{{1
+
+use std::sync::{Arc, Mutex};
+use crate::{
+    config::AccelerationStructure,
+    utility::math::{ray::Ray3, vector::{Point3, Vec3, dot}, float::Float, bounding_box::BoundingBox}
+};
+use super::{
+    object::Object,
+    object_group::{ObjectGroup, ObjectGroupMember},
+    materials::traits::MaterialLike,
+    shapes::{
+        traits::{
+            ShapeIntersectionInfo, IntersectableShape, BoundableShape, SampleableShape, ShapeSample,
+            Transformable, ShapeLike, area_to_solid_angle_pdf
+        },
+        transform::Transform
+    },
+    textures::traits::TextureCoordinates
+};
+
+// E==== IMPORTS }}}1
+
+/// A prototype's objects, shared between its instances.
+type SharedObjects = Arc<[Arc<Object>]>;
+
+pub struct Prototype {
+    /// In the prototype's own space (see `Object::to_instanced()`).
+    objects: SharedObjects,
+    /// Over `objects`, in the same order.
+    group: ObjectGroup,
+    /// The objects made of other materials, for instances that override theirs, so
+    /// that instances with the same material share them.
+    objects_by_material: Mutex<Vec<(Arc<dyn MaterialLike>, SharedObjects)>>,
+}
+
+impl Prototype {
+    /// `objects` should not be empty.
+    pub fn new(objects: &[Arc<Object>], acceleration_structure: &AccelerationStructure) -> Self {
+        let objects: SharedObjects = objects
+            .iter()
+            .map(|object| Arc::new(object.to_instanced(None)))
+            .collect();
+        let members = objects
+            .iter()
+            .map(|object| ObjectGroupMember::Object(object.clone()))
+            .collect();
+        let group = ObjectGroup::new(members, acceleration_structure);
+
+        Self {
+            objects,
+            group,
+            objects_by_material: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The prototype's objects, all made of `material`.
+    fn objects_made_of(&self, material: &Arc<dyn MaterialLike>) -> SharedObjects {
+        let mut objects_by_material = self.objects_by_material.lock().unwrap();
+        if let Some((_, objects)) = objects_by_material.iter().find(|(m, _)| Arc::ptr_eq(m, material)) {
+            return objects.clone();
+        }
+
+        let objects: SharedObjects = self.objects
+            .iter()
+            .map(|object| Arc::new(object.to_instanced(Some(material))))
+            .collect();
+        objects_by_material.push((material.clone(), objects.clone()));
+        objects
+    }
+}
+
+pub struct Instance {
+    prototype: Arc<Prototype>,
+    /// The prototype's objects, made of the instance's material if it has one. The 
+    /// lights among them are the instance's own (see `lights()`).
+    objects: SharedObjects,
+    /// From the prototype's space to global space.
+    transform: Transform,
+    bounding_box: BoundingBox,
+    lights: Vec<Arc<Object>>,
+}
+
+pub struct InstanceInfo {
+    pub prototype: Arc<Prototype>,
+    pub transform: Transform,
+    /// Replaces the materials of all of the prototype's objects.
+    pub material: Option<Arc<dyn MaterialLike>>,
+}
+
+impl Instance {
+    pub fn new(info: InstanceInfo) -> Self {
+        let mut objects = match &info.material {
+            Some(material) => info.prototype.objects_made_of(material),
+            None => info.prototype.objects.clone(),
+        };
+        let bounding_box = info.transform.bounding_box_to_global(&info.prototype.group.bounding_box());
+
+        // Lights are sampled in global space, so they cannot be shared with other 
+        // instances.
+        let lights: Vec<(usize, Arc<Object>)> = objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| Some((index, Arc::new(object.to_instance_light(&info.transform)?))))
+            .collect();
+        if !lights.is_empty() {
+            let mut own_objects = objects.to_vec();
+            for (index, light) in &lights {
+                own_objects[*index] = light.clone();
+            }
+            objects = own_objects.into();
+        }
+
+        Self {
+            prototype: info.prototype,
+            objects,
+            transform: info.transform,
+            bounding_box,
+            lights: lights.into_iter().map(|(_, light)| light).collect(),
+        }
+    }
+
+    /// An axis-aligned box, in global space, containing the whole instance.
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bounding_box.clone()
+    }
+
+    /// The instance's objects that are lights (see `Object::is_light()`), whose 
+    /// shapes are where the instance puts them.
+    pub fn lights(&self) -> &[Arc<Object>] {
+        &self.lights
+    }
+
+    /// The object the ray hits first, if any, and where (in global space).
+    pub fn intersect(&self, ray: &Ray3) -> Option<(Arc<Object>, ShapeIntersectionInfo)> {
+        let (local_ray, origin_offset) = ray_to_prototype(&self.transform, ray);
+        let intersection_info = self.prototype.group.intersect(&local_ray);
+        let object = self.objects[intersection_info.object_index?].clone();
+        let local = intersection_info.shape_intersection_info;
+
+        Some((object, intersection_to_global(&self.transform, &local, origin_offset)))
+    }
+}
+
+/// `ray` in the prototype's space, along with how far (in terms of $t$) its origin 
+/// was moved forward. Transforming the origin rounds it, which could put the origin 
+/// of a spawned ray back behind the surface it leaves (see `offset_ray_origin()`), 
+/// so it is moved past its error as in PBRT.
+fn ray_to_prototype(transform: &Transform, ray: &Ray3) -> (Ray3, Float) {
+    let (origin, origin_error) = transform
+        .point_to_local_with_error(&ray.origin, &Vec3::new(0.0, 0.0, 0.0));
+    let direction = transform.vector_to_local(&ray.direction);
+
+    let length_squared = dot(&direction, &direction);
+    let origin_offset = if length_squared > 0.0 {
+        dot(&direction.abs(), &origin_error) / length_squared
+    } else {
+        0.0
+    };
+
+    let mut to_return = ray.clone();
+    to_return.origin = &origin + &(origin_offset * &direction);
+    to_return.direction = direction;
+    to_return.min_t = Float::max(ray.min_t - origin_offset, 0.0);
+    to_return.max_t = ray.max_t - origin_offset;
+
+    (to_return, origin_offset)
+}
+
+/// The intersection `local` of the ray from `ray_to_prototype()`, in global space.
+fn intersection_to_global(transform: &Transform, local: &ShapeIntersectionInfo, origin_offset: Float) -> ShapeIntersectionInfo {
+    let (point, point_error) = transform.point_to_global_with_error(&local.point, &local.point_error);
+    let surface_normal = transform.normal_to_global(&local.surface_normal).normalize();
+    let shading_normal = transform.normal_to_global(&local.shading_normal).normalize();
+    let texture_coordinates = TextureCoordinates::new(
+        local.texture_coordinates.u(),
+        local.texture_coordinates.v(),
+        shading_normal.clone(),
+        point.clone()
+    );
+
+    ShapeIntersectionInfo {
+        did_hit: true,
+        point,
+        point_error,
+        t: local.t + origin_offset,
+        surface_normal,
+        shading_normal,
+        texture_coordinates,
+    }
+}
+
+/// One of a prototype's shapes, where an instance puts it, so that it can be 
+/// sampled as a light (see `Instance::lights()`).
+pub struct InstancedShape {
+    shape: Arc<dyn ShapeLike>,
+    /// From the prototype's space to global space.
+    transform: Transform,
+}
+
+impl InstancedShape {
+    pub fn new(shape: Arc<dyn ShapeLike>, transform: Transform) -> Self {
+        Self { shape, transform }
+    }
+
+    /// Turns the density `local_pdf` (wrt solid angle at `local_reference`) of the 
+    /// point with the given normal, all in the prototype's space, into the density 
+    /// wrt solid angle at `reference`, in global space.
+    fn pdf_to_global(&self, local_pdf: Float, local_reference: &Point3, local_point: &Point3, local_normal: &Vec3, reference: &Point3) -> Float {
+        let local_normal = local_normal.clone().normalize();
+        let solid_angle_per_area = area_to_solid_angle_pdf(1.0, local_reference, local_point, &local_normal);
+        if solid_angle_per_area == 0.0 {
+            return 0.0;
+        }
+        let area_pdf = local_pdf / solid_angle_per_area / self.transform.area_scale_to_global(&local_normal);
+
+        let point = self.transform.point_to_global(local_point);
+        let surface_normal = self.transform.normal_to_global(&local_normal).normalize();
+        area_to_solid_angle_pdf(area_pdf, reference, &point, &surface_normal)
+    }
+}
+
+impl IntersectableShape for InstancedShape {
+    fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
+        let (local_ray, origin_offset) = ray_to_prototype(&self.transform, ray);
+        let local = self.shape.intersect(&local_ray);
+        if !local.did_hit {
+            return ShapeIntersectionInfo::no_intersection();
+        }
+
+        intersection_to_global(&self.transform, &local, origin_offset)
+    }
+}
+
+impl BoundableShape for InstancedShape {
+    fn bounding_box(&self) -> BoundingBox {
+        self.transform.bounding_box_to_global(&self.shape.bounding_box())
+    }
+}
+
+impl SampleableShape for InstancedShape {
+    fn is_sampleable(&self) -> bool {
+        self.shape.is_sampleable()
+    }
+
+    fn sample_from(&self, reference: &Point3, u: (Float, Float)) -> Option<ShapeSample> {
+        let local_reference = self.transform.point_to_local(reference);
+        let local = self.shape.sample_from(&local_reference, u)?;
+
+        Some(ShapeSample {
+            point: self.transform.point_to_global(&local.point),
+            surface_normal: self.transform.normal_to_global(&local.surface_normal).normalize(),
+            pdf: self.pdf_to_global(local.pdf, &local_reference, &local.point, &local.surface_normal, reference),
+        })
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        let local_reference = self.transform.point_to_local(reference);
+        let local_direction = self.transform.vector_to_local(direction);
+        let hit = self.shape.intersect(&Ray3::new(local_reference.clone(), local_direction.clone()));
+        if !hit.did_hit {
+            return 0.0;
+        }
+
+        let local_pdf = self.shape.pdf_from(&local_reference, &local_direction);
+        self.pdf_to_global(local_pdf, &local_reference, &hit.point, &hit.surface_normal, reference)
+    }
+}
+
+impl Transformable for InstancedShape {
+    fn get_transform(&self) -> Transform {
+        self.transform.clone()
+    }
+}
+
+impl ShapeLike for InstancedShape {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{AccStructureKind, AccStructureAxisSelectionMethod},
+        objects::{
+            object::ObjectInfo,
+            materials::{lambertian::Lambertian, diffuse_light::DiffuseLight},
+            textures::constant::ConstantTexture,
+            shapes::{sphere::{Sphere, SphereInfo}, quad::Quad}
+        },
+        light::Spectrum,
+        sampler,
+        utility::{
+            math::{matrix::{Matrix4, Matrix4AxisRotationInfo}, vector::Point3, angle::{Angle, AngleUnits}},
+            rng::RandomNumberGenerator
+        }
+    };
+
+    #[test]
+    fn instances_behave_like_copies() {
+        // A unit sphere, stretched, turned and placed far from the origin, where
+        // rounding errors are large in absolute terms.
+        let unit_sphere = |transform: Transform| Arc::new(Object::new(ObjectInfo {
+            shape: Arc::new(Sphere::new(SphereInfo {
+                center: Point3::new(0.0, 0.0, 0.0),
+                radius: 1.0,
+                transform,
+            })),
+            texture: Arc::new(ConstantTexture::new_from_rgb(Vec3::new(0.5, 0.5, 0.5))),
+            material: Arc::new(Lambertian {}),
+        }));
+        let center = Point3::new(3.0e4, -2.0e4, 5.0e4);
+        let transform = Transform::new_from_matrix(
+            &(Matrix4::new_from_translation(&center)
+                * Matrix4::new_from_axis_rotation(&Matrix4AxisRotationInfo {
+                    axis: Vec3::new(1.0, 2.0, 3.0).normalize(),
+                    angle: Angle { amount: 37.0, units: AngleUnits::Degrees },
+                })
+                * Matrix4::new_from_scale(&Vec3::new(300.0, 120.0, 450.0)))
+        );
+        let copy = unit_sphere(transform.clone());
+
+        let acceleration_structure = AccelerationStructure {
+            kind: AccStructureKind::BBH,
+            axis_selection_method: AccStructureAxisSelectionMethod::SurfaceAreaHeuristic,
+        };
+        let prototype = Arc::new(Prototype::new(&[unit_sphere(Transform::default())], &acceleration_structure));
+        let instance = Instance::new(InstanceInfo { prototype, transform, material: None });
+
+        let mut rng = RandomNumberGenerator::from_seed(3);
+        let mut random_direction = || sampler::uniform_on_2sphere_from_uniform((rng.next_float(), rng.next_float())).point;
+        for _ in 0..1000 {
+            let direction = random_direction();
+            let ray = Ray3::new(&center + 2000.0 * &direction, (-1.0) * &direction);
+            let (object, hit) = instance.intersect(&ray).unwrap();
+            let expected = copy.shape.intersect(&ray);
+            assert!(!object.is_light());
+            assert!((hit.t / expected.t - 1.0).abs() < 1e-4, "t = {}, expected {}", hit.t, expected.t);
+            assert!(dot(&hit.surface_normal, &expected.surface_normal) > 0.999);
+
+            // Leaving the (convex) ellipsoid, a ray cannot hit it again; entering it 
+            // (other than at a grazing angle), the ray must hit the other side.
+            let w = random_direction();
+            let cos_theta = dot(&w, &hit.surface_normal);
+            let (outward, inward) = if cos_theta > 0.0 { (w.clone(), (-1.0) * &w) } else { ((-1.0) * &w, w) };
+            assert!(instance.intersect(&hit.spawn_ray(outward)).is_none());
+            if cos_theta.abs() > 1e-3 {
+                assert!(instance.intersect(&hit.spawn_ray(inward)).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn instanced_lights_are_sampled_like_copies() {
+        // A unit square light, stretched into a 3 by 0.5 rectangle and turned, and
+        // the same rectangle made directly.
+        let lamp = |width: Float, height: Float, transform: Transform| Arc::new(Object::new(ObjectInfo {
+            shape: Arc::new(Quad { width, height, transform }),
+            texture: Arc::new(ConstantTexture::new_from_rgb(Vec3::new(0.5, 0.5, 0.5))),
+            material: Arc::new(DiffuseLight::new(Spectrum::new(4.0, 4.0, 4.0))),
+        }));
+        let placement = Matrix4::new_from_translation(&Vec3::new(1.0, 2.0, -3.0))
+            * Matrix4::new_from_axis_rotation(&Matrix4AxisRotationInfo {
+                axis: Vec3::new(1.0, 2.0, 3.0).normalize(),
+                angle: Angle { amount: 37.0, units: AngleUnits::Degrees },
+            });
+        let copy = lamp(3.0, 0.5, Transform::new_from_matrix(&placement));
+
+        let acceleration_structure = AccelerationStructure {
+            kind: AccStructureKind::BBH,
+            axis_selection_method: AccStructureAxisSelectionMethod::SurfaceAreaHeuristic,
+        };
+        let prototype = Arc::new(Prototype::new(&[lamp(1.0, 1.0, Transform::default())], &acceleration_structure));
+        let transform = Transform::new_from_matrix(&(placement * Matrix4::new_from_scale(&Vec3::new(3.0, 0.5, 2.0))));
+        let instance = Instance::new(InstanceInfo { prototype, transform, material: None });
+        let group = ObjectGroup::new(vec![ObjectGroupMember::Instance(Box::new(instance))], &acceleration_structure);
+        assert_eq!(group.lights().len(), 1);
+        let light = &group.lights()[0];
+
+        let mut rng = RandomNumberGenerator::from_seed(5);
+        for _ in 0..1000 {
+            let reference = Point3::new(4.0 * rng.next_float() - 2.0, 4.0 * rng.next_float(), 4.0 * rng.next_float());
+            let u = (rng.next_float(), rng.next_float());
+            let (Some(sample), Some(expected)) = (light.sample_from(&reference, u), copy.sample_from(&reference, u)) else {
+                continue;
+            };
+            let distance = &sample.point - &expected.point;
+            assert!(dot(&distance, &distance) < 1e-8);
+            assert!((sample.pdf / expected.pdf - 1.0).abs() < 1e-3, "pdf = {}, expected {}", sample.pdf, expected.pdf);
+
+            let direction = &sample.point - &reference;
+            let pdf = light.pdf_from(&reference, &direction);
+            assert!((pdf / expected.pdf - 1.0).abs() < 1e-3, "pdf = {}, expected {}", pdf, expected.pdf);
+            assert!(group.intersect(&Ray3::new(reference.clone(), direction)).object_index.is_some());
+        }
+    }
+}
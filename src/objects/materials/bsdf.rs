@@ -0,0 +1,103 @@
+//! The bidirectional scattering distribution function (BSDF) $f(\omega_o, \omega_i)$ 
+//! of a surface describes how much of the light arriving from direction $\omega_i$ 
+//! leaves in direction $\omega_o$. The light scattered towards $\omega_o$ is then 
+//! $\int f(\omega_o, \omega_i) L(\omega_i) |\cos\theta_i| \, d\omega_i$.
+//!
+//! Both directions point away from the surface. BSDFs are defined in a local 
+//! shading frame, where the surface normal is the $z$-axis, which keeps them simple.
+//! A `Bsdf` places such a local BSDF at a point of a surface.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    light::Spectrum,
+    utility::math::{
+        float::Float, 
+        vector::Vec3, 
+        orthonormal_basis::OrthonormalBasis
+    }
+};
+
+// E==== IMPORTS }}}1
+
+pub struct BsdfSample {
+    /// The sampled incident direction.
+    pub wi: Vec3,
+    /// $f(\omega_o, \omega_i)$.
+    pub value: Spectrum,
+    /// The probability density (wrt solid angle) with which `wi` was sampled.
+    pub pdf: Float,
+    /// Specular scattering (mirrors, glass, ...) picks `wi` from a distribution 
+    /// without a density, e.g. a single direction. Then `eval()` and `pdf()` are 
+    /// zero for every pair of directions, and only the ratio 
+    /// `value * |cos| / pdf` of the sample is meaningful.
+    pub is_specular: bool,
+}
+
+/// A BSDF in the local shading frame: the surface normal is $(0,0,1)$, and all 
+/// directions are unit vectors.
+pub trait BsdfLike {
+    /// $f(\omega_o, \omega_i)$.
+    fn eval(&self, wo: &Vec3, wi: &Vec3) -> Spectrum;
+
+    /// The density (wrt solid angle) with which `sample()` produces `wi` given `wo`.
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> Float;
+
+    /// Chooses an incident direction given `wo`, ideally with a density roughly 
+    /// proportional to $f(\omega_o, \omega_i) |\cos\theta_i|$. `u_component` and `u` 
+    /// are uniformly distributed in $[0,1)$; the former is for choosing between 
+    /// several ways of scattering (e.g. reflection or refraction) and the latter for 
+    /// the direction. Returns `None` if the light is absorbed.
+    fn sample(&self, wo: &Vec3, u_component: Float, u: (Float, Float)) -> Option<BsdfSample>;
+
+    /// Whether every sample is specular (see `BsdfSample::is_specular`), in which 
+    /// case there is no point in calling `eval()`.
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
+/// A `BsdfLike` at a point of a surface, taking directions in global space.
+pub struct Bsdf {
+    frame: OrthonormalBasis,
+    local: Box<dyn BsdfLike>,
+}
+
+impl Bsdf {
+    /// `normal` is the (global space) shading normal (see 
+    /// `ShapeIntersectionInfo::shading_normal`), which becomes the $z$-axis of the 
+    /// local shading frame.
+    pub fn new(normal: &Vec3, local: Box<dyn BsdfLike>) -> Self {
+        Self {
+            frame: OrthonormalBasis::new_from_vector(normal),
+            local,
+        }
+    }
+
+    /// See `BsdfLike::eval()`.
+    pub fn eval(&self, wo: &Vec3, wi: &Vec3) -> Spectrum {
+        self.local.eval(&self.frame.vector_to_local(wo), &self.frame.vector_to_local(wi))
+    }
+
+    /// See `BsdfLike::pdf()`.
+    pub fn pdf(&self, wo: &Vec3, wi: &Vec3) -> Float {
+        self.local.pdf(&self.frame.vector_to_local(wo), &self.frame.vector_to_local(wi))
+    }
+
+    /// See `BsdfLike::is_specular()`.
+    pub fn is_specular(&self) -> bool {
+        self.local.is_specular()
+    }
+
+    /// See `BsdfLike::sample()`. The sampled direction is in global space.
+    pub fn sample(&self, wo: &Vec3, u_component: Float, u: (Float, Float)) -> Option<BsdfSample> {
+        let mut sample = self.local.sample(&self.frame.vector_to_local(wo), u_component, u)?;
+        sample.wi = self.frame.vector_from_local(sample.wi);
+        Some(sample)
+    }
+}
+
+/// Whether the directions (in the local frame) are on the same side of the surface.
+pub fn same_hemisphere(w: &Vec3, w_prime: &Vec3) -> bool {
+    w.z() * w_prime.z() > 0.0
+}
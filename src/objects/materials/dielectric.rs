@@ -0,0 +1,205 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::math::{ray::Ray3, vector::{Vec3, reflect, refract}, float::Float},
+    light::{Spectrum, ColorConstantsQueryable}
+};
+use super::{
+    traits::MaterialLike,
+    bsdf::{Bsdf, BsdfLike, BsdfSample}
+};
+
+// E==== IMPORTS }}}1
+
+/// How the fraction of light reflected (rather than refracted) is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FresnelApproximation {
+    /// Schlick's polynomial approximation.
+    Schlick,
+    /// The Fresnel equations for unpolarized light.
+    Exact,
+}
+
+/// A transparent material like glass or water. Light is either reflected or 
+/// refracted, chosen randomly according to the Fresnel reflectance. The surface 
+/// normal is taken to point out of the material, into a medium with index of 
+/// refraction 1 (e.g. air).
+///
+/// With an Abbe number, the index of refraction depends on the wavelength of the 
+/// light (dispersion), as given by Cauchy's equation $n(\lambda) = A + B/\lambda^2$. 
+/// This only has an effect in spectral mode.
+pub struct Dielectric {
+    /// At the Fraunhofer D line, if the index depends on the wavelength.
+    index_of_refraction: Float,
+    /// $(n_D - 1) / (n_F - n_C)$, which is smaller the more the index varies with 
+    /// the wavelength: around 60 for crown glass and 30 for flint glass.
+    abbe_number: Option<Float>,
+    fresnel: FresnelApproximation,
+}
+
+/// The wavelengths (in nanometers) of the Fraunhofer D, F and C lines, at which 
+/// the index of refraction of glass is usually given.
+const D_LINE: Float = 587.56;
+const F_LINE: Float = 486.13;
+const C_LINE: Float = 656.27;
+
+impl Dielectric {
+    pub fn new(index_of_refraction: Float, abbe_number: Option<Float>, fresnel: FresnelApproximation) -> Self {
+        Self {
+            index_of_refraction,
+            abbe_number,
+            fresnel,
+        }
+    }
+
+    /// The index of refraction at `wavelength`, or at the D line if it is `None`.
+    pub fn index_at(&self, wavelength: Option<Float>) -> Float {
+        let (abbe_number, wavelength) = match (self.abbe_number, wavelength) {
+            (Some(abbe_number), Some(wavelength)) => (abbe_number, wavelength),
+            _ => { return self.index_of_refraction; }
+        };
+
+        // The coefficients of Cauchy's equation giving the index at the D line and 
+        // the Abbe number.
+        let b = (self.index_of_refraction - 1.0) 
+            / (abbe_number * (1.0 / (F_LINE * F_LINE) - 1.0 / (C_LINE * C_LINE)));
+        let a = self.index_of_refraction - b / (D_LINE * D_LINE);
+        a + b / (wavelength * wavelength)
+    }
+}
+
+/// The fraction of light reflected when arriving at angle $\theta$ to the normal, 
+/// with $\cos\theta$ = `cos_incident`. `eta_ratio` is as in `refract()`.
+fn reflectance(fresnel: FresnelApproximation, cos_incident: Float, eta_ratio: Float) -> Float {
+    match fresnel {
+        FresnelApproximation::Schlick => {
+            let r0 = (1.0 - eta_ratio) / (1.0 + eta_ratio);
+            let r0 = r0 * r0;
+            r0 + (1.0 - r0) * Float::powi(1.0 - cos_incident, 5)
+        },
+        FresnelApproximation::Exact => {
+            let sin2_transmitted = eta_ratio * eta_ratio * (1.0 - cos_incident * cos_incident);
+            if sin2_transmitted >= 1.0 {
+                return 1.0;
+            }
+            let cos_transmitted = Float::sqrt(1.0 - sin2_transmitted);
+
+            // With the index of refraction on the transmitted side set to 1.
+            let parallel = (cos_incident - eta_ratio * cos_transmitted) 
+                / (cos_incident + eta_ratio * cos_transmitted);
+            let perpendicular = (eta_ratio * cos_incident - cos_transmitted) 
+                / (eta_ratio * cos_incident + cos_transmitted);
+            0.5 * (parallel * parallel + perpendicular * perpendicular)
+        }
+    }
+}
+
+impl MaterialLike for Dielectric {
+    fn bsdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        albedo: &Spectrum
+    ) -> Option<Bsdf> {
+        let wavelength = incoming_ray.wavelengths.as_ref().map(|wavelengths| wavelengths.hero());
+        let local = DielectricBsdf { 
+            albedo: albedo.clone(), 
+            index_of_refraction: self.index_at(wavelength),
+            fresnel: self.fresnel,
+        };
+        Some(Bsdf::new(&shape_intersection_info.shading_normal, Box::new(local)))
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.abbe_number.is_some()
+    }
+}
+
+struct DielectricBsdf {
+    albedo: Spectrum,
+    index_of_refraction: Float,
+    fresnel: FresnelApproximation,
+}
+
+impl BsdfLike for DielectricBsdf {
+    fn eval(&self, _wo: &Vec3, _wi: &Vec3) -> Spectrum {
+        Spectrum::black()
+    }
+
+    fn pdf(&self, _wo: &Vec3, _wi: &Vec3) -> Float {
+        0.0
+    }
+
+    /// Reflects with probability equal to the reflectance and refracts otherwise, 
+    /// so the Fresnel terms cancel with the pdf.
+    fn sample(&self, wo: &Vec3, u_component: Float, _u: (Float, Float)) -> Option<BsdfSample> {
+        if wo.z() == 0.0 {
+            return None;
+        }
+
+        // The normal points out of the material.
+        let entering = wo.z() > 0.0;
+        let eta_ratio = if entering { 1.0 / self.index_of_refraction } else { self.index_of_refraction };
+        let normal = Vec3::new(0.0, 0.0, if entering { 1.0 } else { -1.0 });
+        let direction = (-1.0) * wo;
+
+        let reflectance = reflectance(self.fresnel, Float::abs(wo.z()), eta_ratio);
+        let wi = match refract(&direction, &normal, eta_ratio) {
+            Some(refracted) if u_component >= reflectance => refracted,
+            // Total internal reflection, or chose to reflect.
+            _ => reflect(&direction, &normal),
+        };
+        if wi.z() == 0.0 {
+            return None;
+        }
+
+        Some(BsdfSample {
+            value: (1.0 / Float::abs(wi.z())) * &self.albedo,
+            wi,
+            pdf: 1.0,
+            is_specular: true,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresnel_reflectance() {
+        for fresnel in [FresnelApproximation::Schlick, FresnelApproximation::Exact] {
+            // Head on, from air into glass: ((1.5-1)/(1.5+1))^2 = 0.04.
+            assert!((reflectance(fresnel, 1.0, 1.0 / 1.5) - 0.04).abs() < 1e-4);
+            // Grazing.
+            assert!((reflectance(fresnel, 0.0, 1.0 / 1.5) - 1.0).abs() < 1e-4);
+        }
+
+        // Total internal reflection beyond the critical angle (about 41.8 degrees).
+        let cos_60_degrees = 0.5;
+        assert_eq!(reflectance(FresnelApproximation::Exact, cos_60_degrees, 1.5), 1.0);
+        let cos_30_degrees = Float::sqrt(3.0) / 2.0;
+        assert!(reflectance(FresnelApproximation::Exact, cos_30_degrees, 1.5) < 1.0);
+    }
+
+    #[test]
+    fn dispersion() {
+        // Crown glass.
+        let glass = Dielectric::new(1.5168, Some(64.17), FresnelApproximation::Exact);
+        assert!((glass.index_at(Some(D_LINE)) - 1.5168).abs() < 1e-5);
+        assert_eq!(glass.index_at(None), 1.5168);
+        let (n_f, n_c) = (glass.index_at(Some(F_LINE)), glass.index_at(Some(C_LINE)));
+        assert!((0.5168 / (n_f - n_c) - 64.17).abs() < 1e-2);
+        // Blue light is bent more than red.
+        assert!(glass.index_at(Some(450.0)) > glass.index_at(Some(650.0)));
+
+        let water = Dielectric::new(1.333, None, FresnelApproximation::Exact);
+        assert_eq!(water.index_at(Some(450.0)), water.index_at(Some(650.0)));
+    }
+}
@@ -0,0 +1,105 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::math::ray::Ray3,
+    light::Spectrum
+};
+use super::{traits::MaterialLike, bsdf::Bsdf};
+
+// E==== IMPORTS }}}1
+
+/// An area light: the surface emits `radiance` uniformly in every direction, from 
+/// both of its sides, and absorbs all light that reaches it. 
+pub struct DiffuseLight {
+    radiance: Spectrum,
+}
+
+impl DiffuseLight {
+    pub fn new(radiance: Spectrum) -> Self {
+        Self {
+            radiance
+        }
+    }
+}
+
+impl MaterialLike for DiffuseLight {
+    fn bsdf(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo,
+        _albedo: &Spectrum
+    ) -> Option<Bsdf> {
+        None
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+
+    fn emitted(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo
+    ) -> Spectrum {
+        self.radiance.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::{
+        objects::{
+            object::{Object, ObjectInfo},
+            materials::lambertian::Lambertian,
+            textures::constant::ConstantTexture,
+            shapes::{
+                sphere::{Sphere, SphereInfo},
+                triangle::{Triangle, TriangleInfo},
+                transform::Transform,
+                traits::ShapeLike
+            }
+        },
+        utility::math::vector::{Point3, Vec3}
+    };
+
+    fn object(shape: Arc<dyn ShapeLike>, material: Arc<dyn MaterialLike>) -> Object {
+        Object::new(ObjectInfo {
+            shape,
+            texture: Arc::new(ConstantTexture::new_from_rgb(Vec3::new(0.5, 0.5, 0.5))),
+            material,
+        })
+    }
+
+    #[test]
+    fn lights_emit_their_radiance() {
+        let sphere = || Arc::new(Sphere::new(SphereInfo {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            transform: Transform::default(),
+        }));
+        let radiance = Spectrum::new(1.0, 2.0, 4.0);
+        let light = object(sphere(), Arc::new(DiffuseLight::new(radiance.clone())));
+        let ball = object(sphere(), Arc::new(Lambertian {}));
+
+        let ray = Ray3::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = light.shape.intersect(&ray);
+        assert!(hit.did_hit);
+        let emitted = light.emitted(&ray, &hit);
+        assert!(emitted.x() == radiance.x() && emitted.y() == radiance.y() && emitted.z() == radiance.z());
+        let emitted = ball.emitted(&ray, &hit);
+        assert!(emitted.x() == 0.0 && emitted.y() == 0.0 && emitted.z() == 0.0);
+
+        // Only emissive objects are lights, and only if their shape can be sampled.
+        assert!(light.is_light());
+        assert!(!ball.is_light());
+        let triangle = Arc::new(Triangle::new(TriangleInfo {
+            vertices: [Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)],
+            transform: Transform::default(),
+        }));
+        assert!(!object(triangle, Arc::new(DiffuseLight::new(radiance))).is_light());
+    }
+}
@@ -5,38 +5,117 @@ use crate::{
     objects::shapes::traits::ShapeIntersectionInfo,
     utility::math::{
         ray::Ray3, 
-        orthonormal_basis::OrthonormalBasis
+        vector::Vec3,
+        float::{Float, FloatConstants}
     }, 
+    light::{Spectrum, ColorConstantsQueryable},
     sampler, 
 };
-use super::traits::{MaterialLike, MaterialScatterResult};
+use super::{
+    traits::MaterialLike, 
+    bsdf::{Bsdf, BsdfLike, BsdfSample, same_hemisphere}
+};
 
 // E==== IMPORTS }}}1
 
+/// A perfectly diffuse (matte) surface, scattering light equally in all directions.
 pub struct Lambertian {
 }
 
 impl MaterialLike for Lambertian {
-    fn scatter(
+    fn bsdf(
         &self,
-        incoming_ray: &Ray3, 
-        shape_intersection_info: &ShapeIntersectionInfo, 
-        rng: &mut crate::utility::rng::RandomNumberGenerator
-    ) -> MaterialScatterResult {
-        let sample_result = sampler::cosine_on_2sphere_hemisphere(rng);
-
-        let scattered_direction = {
-            let onb = OrthonormalBasis::new_from_vector(&shape_intersection_info.surface_normal);
-            onb.vector_from_local(sample_result.point.clone())
-        };
-        let scattered_ray = Ray3::new(shape_intersection_info.point.clone(), scattered_direction);
+        _incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        albedo: &Spectrum
+    ) -> Option<Bsdf> {
+        let local = LambertianBsdf { albedo: albedo.clone() };
+        Some(Bsdf::new(&shape_intersection_info.shading_normal, Box::new(local)))
+    }
+}
 
+/// $f = \rho / \pi$ for the albedo $\rho$, on either side of the surface.
+pub struct LambertianBsdf {
+    pub albedo: Spectrum,
+}
 
-        MaterialScatterResult {
-            did_scatter: true,
-            scattered_ray,
-            pdf: sample_result.pdf,
+impl BsdfLike for LambertianBsdf {
+    fn eval(&self, wo: &Vec3, wi: &Vec3) -> Spectrum {
+        if !same_hemisphere(wo, wi) {
+            return Spectrum::black();
         }
+
+        Float::get_1_pi() * &self.albedo
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> Float {
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+
+        Float::abs(wi.z()) * Float::get_1_pi()
+    }
+
+    /// Cosine weighted, on the side of `wo`.
+    fn sample(&self, wo: &Vec3, _u_component: Float, u: (Float, Float)) -> Option<BsdfSample> {
+        let sample_result = sampler::cosine_on_2sphere_hemisphere_from_uniform(u);
+        if sample_result.pdf <= 0.0 {
+            return None;
+        }
+
+        let mut wi = sample_result.point;
+        if wo.z() < 0.0 {
+            wi.set_z(-wi.z());
+        }
+
+        Some(BsdfSample {
+            value: self.eval(wo, &wi),
+            wi,
+            pdf: sample_result.pdf,
+            is_specular: false,
+        })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utility::rng::RandomNumberGenerator, light::Spectrum};
+
+    #[test]
+    fn integrates_to_albedo() {
+        let bsdf = LambertianBsdf { albedo: Spectrum::new(0.2, 0.5, 0.9) };
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let num_samples = 100_000;
+        let close = |estimate: &Spectrum, expected: &Spectrum, tolerance: Float| {
+            (estimate.x() - expected.x()).abs() < tolerance
+                && (estimate.y() - expected.y()).abs() < tolerance
+                && (estimate.z() - expected.z()).abs() < tolerance
+        };
+
+        for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.6, 0.0, 0.8), Vec3::new(0.0, 0.6, -0.8)] {
+            // Estimate the integral of f |cos| over the sphere with uniformly 
+            // distributed directions, independently of the BSDF's own sampling. The 
+            // integral of the pdf should be 1.
+            let mut uniform_estimate = Spectrum::black();
+            let mut pdf_integral: Float = 0.0;
+            for _ in 0..num_samples {
+                let sample = sampler::uniform_on_2sphere_from_uniform((rng.next_float(), rng.next_float()));
+                let weight = Float::abs(sample.point.z()) / (sample.pdf * num_samples as Float);
+                uniform_estimate = uniform_estimate + weight * bsdf.eval(&wo, &sample.point);
+                pdf_integral += bsdf.pdf(&wo, &sample.point) / (sample.pdf * num_samples as Float);
+            }
+            assert!(close(&uniform_estimate, &bsdf.albedo, 1e-2), "{:?}", uniform_estimate);
+            assert!((pdf_integral - 1.0).abs() < 1e-2);
+
+            // With importance sampling every sample should be exactly the albedo.
+            for _ in 0..100 {
+                let sample = bsdf.sample(&wo, rng.next_float(), (rng.next_float(), rng.next_float())).unwrap();
+                assert!(same_hemisphere(&wo, &sample.wi));
+                assert!((sample.pdf - bsdf.pdf(&wo, &sample.wi)).abs() < 1e-4);
+                let estimate = (Float::abs(sample.wi.z()) / sample.pdf) * sample.value;
+                assert!(close(&estimate, &bsdf.albedo, 1e-4));
+            }
+        }
+    }
+}
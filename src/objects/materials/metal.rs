@@ -0,0 +1,130 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::math::{ray::Ray3, vector::Vec3, float::Float},
+    light::{Spectrum, ColorConstantsQueryable},
+    sampler
+};
+use super::{
+    traits::MaterialLike, 
+    bsdf::{Bsdf, BsdfLike, BsdfSample, same_hemisphere}
+};
+
+// E==== IMPORTS }}}1
+
+/// A mirror-like surface, reflecting the color of its texture. The reflected 
+/// direction is perturbed by a random vector of length at most `fuzz`, so a fuzz 
+/// of 0 is a perfect mirror and larger values look like brushed metal.
+pub struct Metal {
+    fuzz: Float,
+}
+
+impl Metal {
+    /// `fuzz` is clamped to $[0,1]$.
+    pub fn new(fuzz: Float) -> Self {
+        Self {
+            fuzz: fuzz.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl MaterialLike for Metal {
+    fn bsdf(
+        &self,
+        _incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        albedo: &Spectrum
+    ) -> Option<Bsdf> {
+        let local = MetalBsdf { albedo: albedo.clone(), fuzz: self.fuzz };
+        Some(Bsdf::new(&shape_intersection_info.shading_normal, Box::new(local)))
+    }
+}
+
+/// Even with fuzz we do not know the density of the scattered directions, so this 
+/// is treated as specular.
+struct MetalBsdf {
+    albedo: Spectrum,
+    fuzz: Float,
+}
+
+impl BsdfLike for MetalBsdf {
+    fn eval(&self, _wo: &Vec3, _wi: &Vec3) -> Spectrum {
+        Spectrum::black()
+    }
+
+    fn pdf(&self, _wo: &Vec3, _wi: &Vec3) -> Float {
+        0.0
+    }
+
+    fn sample(&self, wo: &Vec3, u_component: Float, u: (Float, Float)) -> Option<BsdfSample> {
+        let reflected = Vec3::new(-wo.x(), -wo.y(), wo.z());
+        let fuzz = self.fuzz * sampler::uniform_in_2sphere_from_uniform(u_component, u).point;
+        let wi = (reflected + fuzz).normalize();
+
+        // Fuzzing may push the direction into the surface, in which case the light 
+        // is absorbed.
+        if !same_hemisphere(wo, &wi) {
+            return None;
+        }
+
+        Some(BsdfSample {
+            value: (1.0 / Float::abs(wi.z())) * &self.albedo,
+            wi,
+            pdf: 1.0,
+            is_specular: true,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::{math::vector::{Point3, reflect, dot}, rng::RandomNumberGenerator};
+
+    #[test]
+    fn smooth_metal_is_a_mirror() {
+        let normal = Vec3::new(1.0, 2.0, 2.0).normalize();
+        let shape_intersection_info = ShapeIntersectionInfo {
+            did_hit: true,
+            surface_normal: normal.clone(),
+            shading_normal: normal.clone(),
+            ..Default::default()
+        };
+        let ray = Ray3::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.3, -0.2, -1.0).normalize());
+        let bsdf = Metal::new(0.0).bsdf(&ray, &shape_intersection_info, &Spectrum::new(0.5, 0.5, 0.5)).unwrap();
+
+        let wo = (-1.0) * &ray.direction;
+        let sample = bsdf.sample(&wo, 0.7, (0.2, 0.9)).unwrap();
+        assert!(Vec3::are_equal(&sample.wi, &reflect(&ray.direction, &normal)));
+        assert!(sample.is_specular && dot(&sample.wi, &normal) > 0.0);
+    }
+
+    #[test]
+    fn fuzzed_directions_into_the_surface_are_absorbed() {
+        let bsdf = MetalBsdf { albedo: Spectrum::new(0.5, 0.5, 0.5), fuzz: 1.0 };
+        // Nearly grazing, so that fuzz often pushes the direction below the surface.
+        let wo = Vec3::new(0.95, 0.0, 0.1).normalize();
+
+        let mut rng = RandomNumberGenerator::from_seed(7);
+        let mut num_absorbed = 0;
+        for _ in 0..1000 {
+            let (u_component, u) = (rng.next_float(), (rng.next_float(), rng.next_float()));
+            let fuzzed = Vec3::new(-wo.x(), -wo.y(), wo.z()) 
+                + sampler::uniform_in_2sphere_from_uniform(u_component, u).point;
+            match bsdf.sample(&wo, u_component, u) {
+                Some(sample) => assert!(fuzzed.z() > 0.0 && sample.wi.z() > 0.0),
+                None => {
+                    assert!(fuzzed.z() <= 0.0);
+                    num_absorbed += 1;
+                }
+            }
+        }
+        assert!(num_absorbed > 100);
+    }
+}
@@ -1,7 +1,14 @@
 //! Each shape should have a material. When a ray intersects a surface, the 
 //! material determines how that ray scatters. That is precisely what a material 
-//! does in Mirth: determines the direction of the scattered ray. 
+//! does in Mirth: it provides the BSDF of the surface (see `bsdf`), which 
+//! describes how much light is scattered from one direction into another and 
+//! samples scattered directions. A material may also give off light of its own, 
+//! which is how area lights are made.
 
 pub mod traits;
+pub mod bsdf;
 pub mod lambertian;
+pub mod diffuse_light;
+pub mod metal;
+pub mod dielectric;
 
@@ -1,22 +1,43 @@
 use crate::{
-    utility::{
-        math::{float::Float, ray::Ray3}, 
-        rng::RandomNumberGenerator
-    }, 
-    objects::shapes::traits::ShapeIntersectionInfo
+    utility::math::ray::Ray3, 
+    objects::shapes::traits::ShapeIntersectionInfo,
+    light::{Spectrum, ColorConstantsQueryable}
 };
+use super::bsdf::Bsdf;
 
-pub struct MaterialScatterResult {
-    pub did_scatter: bool,
-    pub scattered_ray: Ray3,
-    pub pdf: Float,
-}
+/// Materials are shared between the threads rendering a scene, hence `Send + Sync`.
+pub trait MaterialLike: Send + Sync {
+    /// How the surface scatters light at the intersection. `albedo` is the value of 
+    /// the object's texture there, which most materials use as their color. Returns 
+    /// `None` if the surface absorbs all light.
+    fn bsdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        albedo: &Spectrum
+    ) -> Option<Bsdf>;
+
+    /// Whether the surface scatters each wavelength of light differently (e.g. glass 
+    /// with dispersion), so that in spectral mode a ray can only carry a single 
+    /// wavelength past it. The BSDF is then that of the hero wavelength of the 
+    /// incoming ray.
+    fn is_dispersive(&self) -> bool {
+        false
+    }
+
+    /// Whether `emitted()` can be nonzero, so that the object is a light source.
+    fn is_emissive(&self) -> bool {
+        false
+    }
 
-pub trait MaterialLike {
-    fn scatter(
+    /// The light the surface gives off at the intersection, towards the origin of 
+    /// `incoming_ray`. Most materials are not light sources, so by default this is 
+    /// black.
+    fn emitted(
         &self,
-        incoming_ray: &Ray3, 
-        shape_intersection_info: &ShapeIntersectionInfo, 
-        rng: &mut RandomNumberGenerator
-    ) -> MaterialScatterResult;
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo
+    ) -> Spectrum {
+        Spectrum::black()
+    }
 }
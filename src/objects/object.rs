@@ -1,37 +1,35 @@
 
 // S==== IMPORTS {{{1
 
-use std::{rc::Rc, collections::HashMap};
-use crate::utility::{
-    math::ray::Ray3, 
-    rng::RandomNumberGenerator
+use std::{sync::Arc, collections::HashMap};
+use crate::{
+    utility::math::{ray::Ray3, vector::{Point3, Vec3}, float::Float},
+    light::Spectrum,
+    spectral
 };
 use super::{
-    shapes::{traits::{ShapeLike, ShapeIntersectionInfo}, quad::Quad, self}, 
+    shapes::{traits::{ShapeLike, ShapeIntersectionInfo, ShapeSample}, transform::Transform, quad::Quad, self}, 
+    instance::InstancedShape,
     textures::traits::TextureLike, 
-    materials::traits::{MaterialLike, MaterialScatterResult}
+    materials::{traits::MaterialLike, bsdf::Bsdf}
 };
 use tracing::error;
 
 // E==== IMPORTS }}}1
 
 pub struct Object {
-    pub(super) shape: Rc<dyn ShapeLike>,
-    texture: Rc<dyn TextureLike>,
-    material: Rc<dyn MaterialLike>,
+    pub(super) shape: Arc<dyn ShapeLike>,
+    texture: Arc<dyn TextureLike>,
+    material: Arc<dyn MaterialLike>,
+    /// Whether the object belongs to a prototype (see `Prototype`), in which case 
+    /// its shape is in the prototype's space rather than the scene's.
+    is_instanced: bool,
 }
 
 pub struct ObjectInfo {
-    pub shape: Rc<dyn ShapeLike>,
-    pub texture: Rc<dyn TextureLike>,
-    pub material: Rc<dyn MaterialLike>,
-}
-
-/// Parameter to `Object::sample_new_ray()`.
-pub struct SampleNewRayInfo<'a> {
-    pub incoming_ray: &'a Ray3,
-    pub shape_intersection: &'a ShapeIntersectionInfo,
-    pub rng: &'a mut RandomNumberGenerator,
+    pub shape: Arc<dyn ShapeLike>,
+    pub texture: Arc<dyn TextureLike>,
+    pub material: Arc<dyn MaterialLike>,
 }
 
 impl Object {
@@ -39,12 +37,80 @@ impl Object {
         Self {
             shape: info.shape,
             texture: info.texture,
-            material: info.material
+            material: info.material,
+            is_instanced: false,
+        }
+    }
+
+    /// The object as part of a prototype, made of `material` instead of its own 
+    /// material if given.
+    pub(super) fn to_instanced(&self, material: Option<&Arc<dyn MaterialLike>>) -> Self {
+        Self {
+            shape: self.shape.clone(),
+            texture: self.texture.clone(),
+            material: material.unwrap_or(&self.material).clone(),
+            is_instanced: true,
         }
     }
 
-    pub fn sample_new_ray(&self, info: SampleNewRayInfo) -> MaterialScatterResult {
-        self.material.scatter(info.incoming_ray, info.shape_intersection, info.rng)
+    /// The object of a prototype as a light of the instance placing it by 
+    /// `transform`, or `None` if it is not one (see `is_light()`).
+    pub(super) fn to_instance_light(&self, transform: &Transform) -> Option<Self> {
+        if !(self.material.is_emissive() && self.shape.is_sampleable()) {
+            return None;
+        }
+
+        Some(Self {
+            shape: Arc::new(InstancedShape::new(self.shape.clone(), transform.clone())),
+            texture: self.texture.clone(),
+            material: self.material.clone(),
+            is_instanced: false,
+        })
+    }
+
+    /// The BSDF of the surface at the intersection, or `None` if it absorbs all 
+    /// light. See `MaterialLike::bsdf()`.
+    pub fn bsdf(&self, incoming_ray: &Ray3, shape_intersection: &ShapeIntersectionInfo) -> Option<Bsdf> {
+        let albedo = self.albedo(incoming_ray, shape_intersection);
+        self.material.bsdf(incoming_ray, shape_intersection, &albedo)
+    }
+
+    /// See `MaterialLike::emitted()`. In spectral mode, the color of the light is 
+    /// turned into its spectrum at the wavelengths of `incoming_ray`.
+    pub fn emitted(&self, incoming_ray: &Ray3, shape_intersection: &ShapeIntersectionInfo) -> Spectrum {
+        let emitted = self.material.emitted(incoming_ray, shape_intersection);
+        spectral::illuminant(incoming_ray.wavelengths.as_ref(), &emitted)
+    }
+
+    /// See `MaterialLike::is_dispersive()`.
+    pub fn is_dispersive(&self) -> bool {
+        self.material.is_dispersive()
+    }
+
+    /// Whether the object gives off light and can be sampled as a light source 
+    /// (see `SampleableShape`). A prototype's objects are not, as their shapes are 
+    /// not where the instances put them; each instance has its own lights instead 
+    /// (see `Instance::lights()`).
+    pub fn is_light(&self) -> bool {
+        !self.is_instanced && self.material.is_emissive() && self.shape.is_sampleable()
+    }
+
+    /// See `SampleableShape::sample_from()`.
+    pub fn sample_from(&self, reference: &Point3, u: (Float, Float)) -> Option<ShapeSample> {
+        self.shape.sample_from(reference, u)
+    }
+
+    /// See `SampleableShape::pdf_from()`.
+    pub fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        self.shape.pdf_from(reference, direction)
+    }
+
+    /// The color of the object's texture at the intersection, i.e. the fraction of 
+    /// light (per channel) the surface reflects. In spectral mode, it is turned into 
+    /// the reflectance at the wavelengths of `incoming_ray`.
+    pub fn albedo(&self, incoming_ray: &Ray3, shape_intersection: &ShapeIntersectionInfo) -> Spectrum {
+        let value = self.texture.value_at(incoming_ray, &shape_intersection.texture_coordinates);
+        spectral::reflectance(incoming_ray.wavelengths.as_ref(), &value)
     }
 }
 
@@ -1,31 +1,115 @@
 
 // S==== IMPORTS {{{1
 
-use std::rc::Rc;
-use crate::utility::math::ray::Ray3;
+use std::sync::Arc;
+use crate::{
+    config::{self, AccelerationStructure, AccStructureKind},
+    utility::math::{ray::Ray3, bounding_box::BoundingBox}
+};
 use super::{
     object::Object,
+    instance::Instance,
+    bvh::Bvh,
     shapes::traits::ShapeIntersectionInfo
 };
 
 // E==== IMPORTS }}}1
 
 pub struct ObjectGroup {
-    objects: Vec<Rc<Object>>,
+    members: Vec<ObjectGroupMember>,
+    /// `None` if we were asked not to use an acceleration structure.
+    bvh: Option<Bvh>,
+    /// The objects that are lights (see `Object::is_light()`).
+    lights: Vec<Arc<Object>>,
+}
+
+/// What a group is made of: objects, and instances of prototypes, which are groups 
+/// of objects themselves.
+pub enum ObjectGroupMember {
+    Object(Arc<Object>),
+    Instance(Box<Instance>),
 }
 
 pub struct ObjectGroupIntersectionInfo {
-    pub intersected_object: Option<Rc<Object>>,
+    pub intersected_object: Option<Arc<Object>>,
+    /// The position of the intersected object (or the instance it belongs to) in 
+    /// the group, i.e. in the scene file's list of objects.
+    pub object_index: Option<usize>,
     pub shape_intersection_info: ShapeIntersectionInfo,
 }
 
 impl ObjectGroup {
-    pub fn new_from_vector(objects: Vec<Rc<Object>>) -> Self {
-        Self { objects }
+    /// Uses the acceleration structure described by `MIRTH_CONFIG`.
+    pub fn new_from_vector(members: Vec<ObjectGroupMember>) -> Self {
+        Self::new(members, &config::default_acceleration_structure())
+    }
+
+    pub fn new(members: Vec<ObjectGroupMember>, acceleration_structure: &AccelerationStructure) -> Self {
+        let bvh = match acceleration_structure.kind {
+            AccStructureKind::Nothing => None,
+            AccStructureKind::BBH => {
+                let bounds: Vec<BoundingBox> = members
+                    .iter()
+                    .map(|member| member.bounding_box())
+                    .collect();
+                Some(Bvh::new(&bounds, acceleration_structure.axis_selection_method))
+            }
+        };
+
+        let lights = members
+            .iter()
+            .flat_map(|member| match member {
+                ObjectGroupMember::Object(object) if object.is_light() => vec![object.clone()],
+                ObjectGroupMember::Object(_) => Vec::new(),
+                ObjectGroupMember::Instance(instance) => instance.lights().to_vec(),
+            })
+            .collect();
+
+        Self { members, bvh, lights }
+    }
+
+    /// An axis-aligned box, in global space, containing the whole group.
+    pub fn bounding_box(&self) -> BoundingBox {
+        match &self.bvh {
+            Some(bvh) => bvh.bounding_box(),
+            None => self.members
+                .iter()
+                .fold(BoundingBox::empty(), |acc, member| acc.union(&member.bounding_box())),
+        }
+    }
+
+    pub fn lights(&self) -> &[Arc<Object>] {
+        &self.lights
     }
 
     pub fn intersect(&self, ray: &Ray3) -> ObjectGroupIntersectionInfo {
-        self.intersect_unoptimized(ray)
+        match &self.bvh {
+            Some(bvh) => self.intersect_with_bvh(bvh, ray),
+            None => self.intersect_unoptimized(ray),
+        }
+    }
+
+    /// Only check objects whose bounding boxes the ray passes through.
+    fn intersect_with_bvh(&self, bvh: &Bvh, ray: &Ray3) -> ObjectGroupIntersectionInfo {
+        let mut to_return = ObjectGroupIntersectionInfo {
+            intersected_object: None,
+            object_index: None,
+            shape_intersection_info: ShapeIntersectionInfo::default(),
+        };
+
+        bvh.traverse(ray, |index, working_ray| {
+            let (object, shape_intersection_info) = self.members[index].intersect(working_ray)?;
+
+            let t = shape_intersection_info.t;
+            to_return = ObjectGroupIntersectionInfo {
+                intersected_object: Some(object),
+                object_index: Some(index),
+                shape_intersection_info,
+            };
+            Some(t)
+        });
+
+        to_return
     }
 
     /// Go through each object in the scene and check for intersection.
@@ -33,18 +117,21 @@ impl ObjectGroup {
         let mut working_ray = ray.clone();
         let mut to_return = ObjectGroupIntersectionInfo {
             intersected_object: None,
+            object_index: None,
             shape_intersection_info: ShapeIntersectionInfo::default(),
         };
 
-        for object in self.objects.iter() {
-            let shape_intersection_info = object.shape.intersect(&working_ray);
-            
-            if !shape_intersection_info.did_hit { continue; }
+        for (index, member) in self.members.iter().enumerate() {
+            let (object, shape_intersection_info) = match member.intersect(&working_ray) {
+                Some(hit) => hit,
+                None => { continue; }
+            };
             if shape_intersection_info.t > to_return.shape_intersection_info.t { continue; }
 
             working_ray.max_t = shape_intersection_info.t;
             to_return = ObjectGroupIntersectionInfo {
-                intersected_object: Some(object.clone()),
+                intersected_object: Some(object),
+                object_index: Some(index),
                 shape_intersection_info,
             };
         }
@@ -53,3 +140,23 @@ impl ObjectGroup {
     }
 }
 
+impl ObjectGroupMember {
+    fn bounding_box(&self) -> BoundingBox {
+        match self {
+            Self::Object(object) => object.shape.bounding_box(),
+            Self::Instance(instance) => instance.bounding_box(),
+        }
+    }
+
+    /// The object the ray hits first, if any, and where.
+    fn intersect(&self, ray: &Ray3) -> Option<(Arc<Object>, ShapeIntersectionInfo)> {
+        match self {
+            Self::Object(object) => {
+                let shape_intersection_info = object.shape.intersect(ray);
+                if !shape_intersection_info.did_hit { return None; }
+                Some((object.clone(), shape_intersection_info))
+            },
+            Self::Instance(instance) => instance.intersect(ray),
+        }
+    }
+}
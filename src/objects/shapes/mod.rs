@@ -2,5 +2,7 @@
 pub mod traits;
 pub mod sphere;
 pub mod quad;
+pub mod triangle;
+pub mod triangle_mesh;
 pub mod transform;
 
@@ -3,15 +3,19 @@
 
 use crate::{
     utility::math::{
-        float::{Float, SignCheckable},
-        vector::Vec3,
-        ray::Ray3
+        float::{Float, SignCheckable, FLOAT_ERR, gamma},
+        vector::{Vec3, Point3, cross},
+        ray::Ray3,
+        bounding_box::BoundingBox
     }, objects::textures::traits::TextureCoordinates, 
-     
+    sampler
 };
 use super::{
     transform::Transform, 
-    traits::{Transformable, ShapeLike, IntersectableShape, ShapeIntersectionInfo}
+    traits::{
+        Transformable, ShapeLike, IntersectableShape, BoundableShape, ShapeIntersectionInfo, 
+        SampleableShape, ShapeSample, area_to_solid_angle_pdf
+    }
 };
 
 // E==== IMPORTS }}}1
@@ -33,6 +37,15 @@ impl IntersectableShape for Quad {
     /// $z$-component of $o$) by the unit rate of change of the $z$-component of 
     /// $d$ (which is the $z$-component of $d$). It then suffices to check if this
     /// point of intersection $r(t)$ with $z=0$ lies in the the square.
+    ///
+    /// Since the transform is affine, $t$ is the same in local and global space.
+    ///
+    /// A ray leaving the quad starts off the plane $z=0$ (see 
+    /// `ShapeIntersectionInfo::spawn_ray()`), on the side it heads to, so its $t$ is 
+    /// negative and it does not hit the quad again.
+    ///
+    /// The texture coordinates are $(x/w, y/h)$, so the corner $(0,0,0)$ is at 
+    /// $(u,v)=(0,0)$ and the corner $(w,h,0)$ at $(1,1)$.
     fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
         let transformed_ray = self.transform.ray_to_local(ray);
 
@@ -40,7 +53,7 @@ impl IntersectableShape for Quad {
             return ShapeIntersectionInfo::no_intersection();
         }
 
-        let t = Float::abs(transformed_ray.origin.z()) / transformed_ray.direction.z();
+        let t = (-1 as Float) * transformed_ray.origin.z() / transformed_ray.direction.z();
 
         if !ray.is_in_range(t) {
             return ShapeIntersectionInfo::no_intersection();
@@ -53,21 +66,208 @@ impl IntersectableShape for Quad {
             temp
         };
 
+        let x = intersection_with_plane.x();
+        let y = intersection_with_plane.y();
+        if x < 0.0 || x > self.width || y < 0.0 || y > self.height {
+            return ShapeIntersectionInfo::no_intersection();
+        }
+
+        // On the plane exactly, but $x$ and $y$ were rounded in computing $t$ and 
+        // evaluating the ray.
+        let local_error = {
+            let o = &transformed_ray.origin;
+            let d = &transformed_ray.direction;
+            Vec3::new(
+                gamma(3) * (Float::abs(o.x()) + Float::abs(t * d.x())),
+                gamma(3) * (Float::abs(o.y()) + Float::abs(t * d.y())),
+                0.0
+            )
+        };
+
+        let surface_normal = self.transform.normal_to_global(&Vec3::new(0.0,0.0,1.0)).normalize();
+        let (point, point_error) = self.transform.point_to_global_with_error(&intersection_with_plane, &local_error);
+        let texture_coordinates = TextureCoordinates::new(
+            x / self.width, 
+            y / self.height, 
+            surface_normal.clone(),
+            point.clone()
+        );
+
         ShapeIntersectionInfo {
             did_hit: true,
-            surface_normal: self.transform.vector_to_global(&Vec3::new(0.0,0.0,1.0)),
+            shading_normal: surface_normal.clone(),
+            surface_normal,
             t,
-            point: self.transform.point_to_global(&intersection_with_plane),
-            texture_coordinates: TextureCoordinates::default(),
+            point,
+            point_error,
+            texture_coordinates,
         }
     }
 }
 
+impl BoundableShape for Quad {
+    fn bounding_box(&self) -> BoundingBox {
+        let local = BoundingBox::new_from_points(
+            &Vec3::new(0.0, 0.0, 0.0), 
+            &Vec3::new(self.width, self.height, 0.0)
+        );
+
+        // The quad is flat, so give its box some volume.
+        self.transform.bounding_box_to_global(&local.padded(FLOAT_ERR))
+    }
+}
+
 impl Transformable for Quad {
     fn get_transform(&self) -> Transform {
         self.transform.clone()
     }
 }
 
+impl Quad {
+    /// The area in global space, which the transform may have scaled.
+    fn area(&self) -> Float {
+        let width_edge = self.transform.vector_to_global(&Vec3::new(self.width, 0.0, 0.0));
+        let height_edge = self.transform.vector_to_global(&Vec3::new(0.0, self.height, 0.0));
+        cross(&width_edge, &height_edge).length()
+    }
+}
+
+impl SampleableShape for Quad {
+    fn is_sampleable(&self) -> bool {
+        true
+    }
+
+    /// Uniformly by area. An affine transform scales all areas equally, so a uniform 
+    /// sample in local space is uniform in global space.
+    fn sample_from(&self, reference: &Point3, u: (Float, Float)) -> Option<ShapeSample> {
+        let local_point = sampler::uniform_on_rectangle_from_uniform(u, self.width, self.height).point;
+        let point = self.transform.point_to_global(&local_point);
+        let surface_normal = self.transform.normal_to_global(&Vec3::new(0.0, 0.0, 1.0)).normalize();
+        let pdf = area_to_solid_angle_pdf(1.0 / self.area(), reference, &point, &surface_normal);
+        if pdf == 0.0 {
+            return None;
+        }
+
+        Some(ShapeSample { point, surface_normal, pdf })
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        let hit = self.intersect(&Ray3::new(reference.clone(), direction.clone()));
+        if !hit.did_hit {
+            return 0.0;
+        }
+
+        area_to_solid_angle_pdf(1.0 / self.area(), reference, &hit.point, &hit.surface_normal)
+    }
+}
+
 impl ShapeLike for Quad {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_coordinates() {
+        let quad = Quad {
+            width: 2.0,
+            height: 4.0,
+            transform: Transform::default(),
+        };
+        let hit_at = |x: Float, y: Float| {
+            quad.intersect(&Ray3::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0)))
+        };
+
+        let corner = hit_at(0.0, 0.0);
+        assert!(corner.did_hit);
+        assert!(corner.texture_coordinates.u().abs() < FLOAT_ERR);
+        assert!(corner.texture_coordinates.v().abs() < FLOAT_ERR);
+
+        let inside = hit_at(0.5, 3.0);
+        assert!((inside.texture_coordinates.u() - 0.25).abs() < FLOAT_ERR);
+        assert!((inside.texture_coordinates.v() - 0.75).abs() < FLOAT_ERR);
+        assert!(Vec3::are_equal(inside.texture_coordinates.normal(), &Vec3::new(0.0, 0.0, 1.0)));
+
+        let opposite_corner = hit_at(2.0, 4.0);
+        assert!((opposite_corner.texture_coordinates.u() - 1.0).abs() < FLOAT_ERR);
+        assert!((opposite_corner.texture_coordinates.v() - 1.0).abs() < FLOAT_ERR);
+    }
+
+    #[test]
+    fn rays_hit_the_quad_ahead_of_them_within_its_bounds() {
+        let quad = Quad {
+            width: 2.0,
+            height: 4.0,
+            transform: Transform::default(),
+        };
+        let cast = |origin: Point3, direction: Vec3| quad.intersect(&Ray3::new(origin, direction));
+
+        // From either side.
+        let from_below = cast(Point3::new(1.0, 1.0, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(from_below.did_hit && (from_below.t - 2.0).abs() < FLOAT_ERR);
+        let from_above = cast(Point3::new(1.0, 1.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(from_above.did_hit && (from_above.t - 3.0).abs() < FLOAT_ERR);
+
+        // Not behind the ray, nor beside the quad.
+        assert!(!cast(Point3::new(1.0, 1.0, 3.0), Vec3::new(0.0, 0.0, 1.0)).did_hit);
+        assert!(!cast(Point3::new(3.0, 1.0, 1.0), Vec3::new(0.0, 0.0, -1.0)).did_hit);
+        assert!(!cast(Point3::new(1.0, -0.5, 1.0), Vec3::new(0.0, 0.0, -1.0)).did_hit);
+    }
+
+    #[test]
+    fn spawned_rays_do_not_hit_their_own_surface() {
+        use crate::utility::{math::matrix::Matrix4, rng::RandomNumberGenerator};
+
+        // Far from the origin and tilted, so that rounding errors are large.
+        let quad = Quad {
+            width: 1.0,
+            height: 1.0,
+            transform: Transform::new_from_matrix(&Matrix4::new_from_column_vec3s([
+                &Vec3::new(500.0, 0.0, 300.0),
+                &Vec3::new(0.0, 400.0, 100.0),
+                &cross(&Vec3::new(500.0, 0.0, 300.0), &Vec3::new(0.0, 400.0, 100.0)).normalize(),
+                &Vec3::new(2.0e4, 7.0e4, -3.0e4),
+            ])),
+        };
+
+        let mut rng = RandomNumberGenerator::from_seed(2);
+        for _ in 0..1000 {
+            // Away from the edges, which rounding may move.
+            let (x, y) = (0.01 + 0.98 * rng.next_float(), 0.01 + 0.98 * rng.next_float());
+            let target = quad.transform.point_to_global(&Point3::new(x, y, 0.0));
+            // Not grazing the quad.
+            let direction = Vec3::new(rng.next_float() - 0.5, rng.next_float() - 0.5, rng.next_float() - 0.5)
+                + &quad.transform.normal_to_global(&Vec3::new(0.0, 0.0, 1.0)).normalize();
+            let hit = quad.intersect(&Ray3::new(&target + 10.0 * &direction, (-1.0) * &direction));
+            assert!(hit.did_hit);
+
+            for w in [direction.clone(), (-1.0) * &direction] {
+                assert!(!quad.intersect(&hit.spawn_ray(w)).did_hit);
+            }
+        }
+    }
+
+    #[test]
+    fn sampling_accounts_for_transform() {
+        use crate::utility::{math::matrix::Matrix4, rng::RandomNumberGenerator};
+
+        // A unit quad scaled to area 6.
+        let quad = Quad {
+            width: 1.0,
+            height: 1.0,
+            transform: Transform::new_from_matrix(&Matrix4::new_from_scale(&Vec3::new(2.0, 3.0, 1.0))),
+        };
+        let reference = Point3::new(1.0, 1.5, 100.0);
+        let mut rng = RandomNumberGenerator::from_seed(1);
+
+        for _ in 0..100 {
+            let sample = quad.sample_from(&reference, (rng.next_float(), rng.next_float())).unwrap();
+            // From far away, the quad covers a solid angle of about area / distance^2.
+            let expected_pdf = 100.0 * 100.0 / 6.0;
+            assert!((sample.pdf / expected_pdf - 1.0).abs() < 1e-3);
+
+            let pdf_from = quad.pdf_from(&reference, &(&sample.point - &reference));
+            assert!((pdf_from / sample.pdf - 1.0).abs() < 1e-3);
+        }
+    }
+}
@@ -1,13 +1,22 @@
 
 // S==== IMPORTS {{{1
 
-use crate::utility::math::{
-    vector::{Point3, dot, Vec3}, 
-    ray::Ray3, 
-    float::{Float, SignCheckable}
+use crate::{
+    objects::textures::traits::TextureCoordinates,
+    utility::math::{
+        vector::{Point3, dot, Vec3}, 
+        ray::Ray3, 
+        float::{Float, FloatConstants, SignCheckable, gamma},
+        bounding_box::BoundingBox,
+        orthonormal_basis::OrthonormalBasis
+    },
+    sampler
 };
 use super::{
-    traits::{ShapeIntersectionInfo, IntersectableShape, Transformable, ShapeLike}, 
+    traits::{
+        ShapeIntersectionInfo, IntersectableShape, BoundableShape, Transformable, ShapeLike, 
+        SampleableShape, ShapeSample
+    }, 
     transform::{Transform, self}
 };
 
@@ -34,6 +43,17 @@ impl Sphere {
             transform: info.transform,
         }
     }
+
+    /// Spherical coordinates of the point with (local, unit) normal `n`. With $\theta$ 
+    /// the angle from the $-y$ axis and $\phi$ the angle around the $y$ axis, starting 
+    /// at $-x$ and passing through $+z$, these are $u=\phi/2\pi$ and $v=\theta/\pi$. 
+    /// So $v$ runs from the bottom ($v=0$) to the top ($v=1$) of the sphere.
+    fn texture_coordinates_of_unit_normal(n: &Vec3) -> (Float, Float) {
+        let theta = Float::acos(Float::clamp(-n.y(), -1.0, 1.0));
+        let phi = Float::atan2(-n.z(), n.x()) + Float::get_pi();
+
+        (phi / (2.0 * Float::get_pi()), theta * Float::get_1_pi())
+    }
 }
 
 impl IntersectableShape for Sphere { // {{{1
@@ -66,35 +86,56 @@ impl IntersectableShape for Sphere { // {{{1
             else { temp as Float }
         };
 
-        if discriminant.is_negative() {
+        if SignCheckable::is_negative(&discriminant) {
             return ShapeIntersectionInfo::no_intersection();
         }
 
         let t: Float = {
-            // take the smallest value of t (= closest intersection point)
             let t0 = ((-1 as Float) * b - Float::sqrt(discriminant)) / ((2 as Float) * a);
+            let t1 = ((-1 as Float) * b + Float::sqrt(discriminant)) / ((2 as Float) * a);
+
+            // A root near 0 may come out on either side of it, so whether the ray 
+            // leaving the sphere hits it again cannot be told from the roots. The 
+            // signs of C (is the origin inside the sphere?) and B (is the ray heading 
+            // towards the center?) are reliable though, as rays leave the sphere from 
+            // a point offset from its surface (see `ShapeIntersectionInfo::spawn_ray()`).
+            let candidates = if c < 0.0 {
+                // One root is behind the origin, e.g. a ray that refracted into a 
+                // glass ball.
+                [None, Some(t1)]
+            } else if b < 0.0 {
+                // Both roots are ahead; take the closest intersection point.
+                [Some(t0), Some(t1)]
+            } else {
+                [None, None]
+            };
 
-            if !local_ray.is_in_range(t0) {
-                // an example of when the other root is important is if we have a glass ball, 
-                // and are working with a camera ray that scattered inwards 
-                let t1 = ((-1 as Float) * b + Float::sqrt(discriminant)) / ((2 as Float) * a);
-                if !local_ray.is_in_range(t1) { return ShapeIntersectionInfo::no_intersection(); }
-                t1
+            match candidates.into_iter().flatten().find(|t| local_ray.is_in_range(*t)) {
+                Some(t) => t,
+                None => { return ShapeIntersectionInfo::no_intersection(); }
             }
-            else { t0 }
         };
 
         // Collect all calculations into return struct
 
-        to_return.did_hit = true;
-        to_return.point = {
-            // Project hit point to sphere surface to account for floating point errors
+        // Project hit point to sphere surface to account for floating point errors
+        let local_hitpoint: Point3 = {
             let pre_local_hitpoint = local_ray.eval(t);
-            let local_hitpoint: Point3 = 
-                &self.center + (pre_local_hitpoint - &self.center).normalize_to(self.radius);
+            &self.center + (pre_local_hitpoint - &self.center).normalize_to(self.radius)
+        };
+
+        let local_normal = (&local_hitpoint - &self.center) / self.radius;
+
+        // The error of projecting a point onto the sphere.
+        let local_error = gamma(5) * local_hitpoint.abs();
 
-            // Convert from local coordinates 
-            self.transform.point_to_global(&local_hitpoint)
+        to_return.did_hit = true;
+        (to_return.point, to_return.point_error) = self.transform.point_to_global_with_error(&local_hitpoint, &local_error);
+        to_return.surface_normal = self.transform.normal_to_global(&local_normal).normalize();
+        to_return.shading_normal = to_return.surface_normal.clone();
+        to_return.texture_coordinates = {
+            let (u, v) = Self::texture_coordinates_of_unit_normal(&local_normal);
+            TextureCoordinates::new(u, v, to_return.surface_normal.clone(), to_return.point.clone())
         };
         to_return.t = t;
 
@@ -102,14 +143,202 @@ impl IntersectableShape for Sphere { // {{{1
     }
 } // }}}1
 
+impl BoundableShape for Sphere {
+    fn bounding_box(&self) -> BoundingBox {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let local = BoundingBox::new_from_points(&(&self.center - &r), &(&self.center + &r));
+
+        self.transform.bounding_box_to_global(&local)
+    }
+}
+
 impl Transformable for Sphere {
     fn get_transform(&self) -> Transform {
         self.transform.clone()
     }
 }
 
+impl Sphere {
+    /// A sphere in global space containing this one. If the transform only rotates, 
+    /// translates and scales uniformly this is the sphere itself; otherwise the 
+    /// sphere has become an ellipsoid, and we settle for a larger sphere.
+    fn global_bounding_sphere(&self) -> (Point3, Float) {
+        let center = self.transform.point_to_global(&self.center);
+        let axes = [
+            self.transform.vector_to_global(&Vec3::new(1.0, 0.0, 0.0)),
+            self.transform.vector_to_global(&Vec3::new(0.0, 1.0, 0.0)),
+            self.transform.vector_to_global(&Vec3::new(0.0, 0.0, 1.0)),
+        ];
+        let scale = axes[0].length();
+        let is_similarity = axes.iter().all(|axis| (axis.length() - scale).is_zero())
+            && dot(&axes[0], &axes[1]).is_zero()
+            && dot(&axes[1], &axes[2]).is_zero()
+            && dot(&axes[2], &axes[0]).is_zero();
+
+        let scale = if is_similarity {
+            scale
+        } else {
+            // The largest stretch of a linear map is at most its Frobenius norm.
+            Float::sqrt(axes.iter().map(|axis| dot(axis, axis)).sum())
+        };
+
+        (center, self.radius * scale)
+    }
+
+    /// The density (wrt solid angle) of the directions `sample_from()` picks at 
+    /// `reference`, together with the axis and $\cos\theta_{max}$ of the cone of 
+    /// directions in which the bounding sphere is seen. There is no cone if the 
+    /// bounding sphere surrounds `reference`.
+    fn cone_pdf(&self, reference: &Point3) -> (Float, Option<(Vec3, Float)>) {
+        let (center, radius) = self.global_bounding_sphere();
+        let to_center = center - reference;
+        let distance_squared = dot(&to_center, &to_center);
+
+        if distance_squared <= radius * radius {
+            // The sphere surrounds the reference point, so it is seen in every direction.
+            return (0.25 * Float::get_1_pi(), None);
+        }
+
+        let sin2_theta_max = radius * radius / distance_squared;
+        let cos_theta_max = Float::sqrt(Float::max(1.0 - sin2_theta_max, 0.0));
+        let pdf = 1.0 / (2.0 * Float::get_pi() * (1.0 - cos_theta_max));
+        (pdf, Some((to_center, cos_theta_max)))
+    }
+}
+
+impl SampleableShape for Sphere {
+    fn is_sampleable(&self) -> bool {
+        true
+    }
+
+    /// Uniformly among the directions in which `reference` sees the (bounding) 
+    /// sphere, taking the first point where that direction hits the sphere.
+    fn sample_from(&self, reference: &Point3, u: (Float, Float)) -> Option<ShapeSample> {
+        let (pdf, cone) = self.cone_pdf(reference);
+        let direction = match cone {
+            Some((to_center, cos_theta_max)) => {
+                let local = sampler::uniform_in_cone_from_uniform(u, cos_theta_max).point;
+                OrthonormalBasis::new_from_vector(&to_center).vector_from_local(local)
+            },
+            None => sampler::uniform_on_2sphere_from_uniform(u).point,
+        };
+
+        let hit = self.intersect(&Ray3::new(reference.clone(), direction));
+        if !hit.did_hit {
+            return None;
+        }
+
+        Some(ShapeSample {
+            point: hit.point,
+            surface_normal: hit.surface_normal,
+            pdf,
+        })
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        if !self.intersect(&Ray3::new(reference.clone(), direction.clone())).did_hit {
+            return 0.0;
+        }
+
+        self.cone_pdf(reference).0
+    }
+}
+
 impl ShapeLike for Sphere {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::{float::FLOAT_ERR, matrix::Matrix4};
+
+    #[test]
+    fn texture_coordinates() {
+        // Moving the sphere should not change where its texture is.
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 2.0,
+            transform: Transform::new_from_matrix(&Matrix4::new_from_translation(&Vec3::new(5.0, 0.0, 0.0))),
+        });
+        let uv_of_hit_from = |direction: Vec3| {
+            let origin = Point3::new(5.0, 0.0, 0.0) + 10.0 * &direction;
+            let hit = sphere.intersect(&Ray3::new(origin, (-1.0) * direction));
+            assert!(hit.did_hit);
+            (hit.texture_coordinates.u(), hit.texture_coordinates.v())
+        };
+        let assert_uv = |(u, v): (Float, Float), expected_u: Float, expected_v: Float| {
+            assert!((u - expected_u).abs() < FLOAT_ERR, "u = {}, expected {}", u, expected_u);
+            assert!((v - expected_v).abs() < FLOAT_ERR, "v = {}, expected {}", v, expected_v);
+        };
+
+        assert_uv(uv_of_hit_from(Vec3::new(0.0, 0.0, 1.0)), 0.25, 0.5);
+        assert_uv(uv_of_hit_from(Vec3::new(1.0, 0.0, 0.0)), 0.5, 0.5);
+        assert_uv(uv_of_hit_from(Vec3::new(0.0, 0.0, -1.0)), 0.75, 0.5);
+        assert_eq!(uv_of_hit_from(Vec3::new(0.0, 1.0, 0.0)).1, 1.0);
+        assert_eq!(uv_of_hit_from(Vec3::new(0.0, -1.0, 0.0)).1, 0.0);
+
+        let hit = sphere.intersect(&Ray3::new(Point3::new(5.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -1.0)));
+        assert!(Vec3::are_equal(hit.texture_coordinates.normal(), &Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn spawned_rays_do_not_hit_their_own_surface() {
+        use crate::{sampler, utility::rng::RandomNumberGenerator};
+
+        // Far from the origin, where rounding errors are large in absolute terms.
+        let center = Point3::new(3.0e4, -2.0e4, 5.0e4);
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            transform: Transform::new_from_matrix(
+                &(Matrix4::new_from_translation(&center) * Matrix4::new_from_scale(&Vec3::new(300.0, 300.0, 300.0)))
+            ),
+        });
+
+        let mut rng = RandomNumberGenerator::from_seed(2);
+        let mut random_direction = || sampler::uniform_on_2sphere_from_uniform((rng.next_float(), rng.next_float())).point;
+        for _ in 0..1000 {
+            let direction = random_direction();
+            let hit = sphere.intersect(&Ray3::new(&center + 1000.0 * &direction, (-1.0) * &direction));
+            assert!(hit.did_hit);
+
+            // Leaving the (convex) sphere, a ray cannot hit it again; entering it, the 
+            // ray must hit the other side.
+            let w = random_direction();
+            let (outward, inward) = if dot(&w, &hit.surface_normal) > 0.0 { (w.clone(), (-1.0) * &w) } else { ((-1.0) * &w, w) };
+            assert!(!sphere.intersect(&hit.spawn_ray(outward)).did_hit);
+            assert!(sphere.intersect(&hit.spawn_ray(inward)).did_hit);
+        }
+    }
+
+    #[test]
+    fn sampling_estimates_solid_angle() {
+        use crate::utility::rng::RandomNumberGenerator;
+
+        // A sphere of radius 2 seen from distance 6.
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            transform: Transform::new_from_matrix(&Matrix4::new_from_scale(&Vec3::new(2.0, 2.0, 2.0))),
+        });
+        let reference = Point3::new(0.0, 0.0, 6.0);
+        let expected = 2.0 * Float::get_pi() * (1.0 - Float::sqrt(1.0 - 4.0 / 36.0));
+
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let num_samples = 10000;
+        let mut estimate = 0.0;
+        for _ in 0..num_samples {
+            if let Some(sample) = sphere.sample_from(&reference, (rng.next_float(), rng.next_float())) {
+                let pdf_from = sphere.pdf_from(&reference, &(&sample.point - &reference));
+                assert!((pdf_from / sample.pdf - 1.0).abs() < 1e-3);
+                estimate += 1.0 / sample.pdf;
+            }
+        }
+        estimate /= num_samples as Float;
+
+        assert!((estimate / expected - 1.0).abs() < 0.01, "estimate = {}, expected {}", estimate, expected);
+    }
+}
+
 // #[cfg(test)] // {{{1
 // mod tests {
 //     use crate::utility::math::vector::Vec3;
@@ -3,8 +3,8 @@
 use crate::{
     objects::textures::traits::TextureCoordinates, 
     utility::math::{
-        vector::{Point3, Vec3}, 
-        float::Float, ray::Ray3}
+        vector::{Point3, Vec3, dot}, 
+        float::Float, ray::Ray3, bounding_box::BoundingBox}
 };
 use super::transform::Transform;
 
@@ -17,8 +17,17 @@ pub trait Transformable {
 pub struct ShapeIntersectionInfo {
     pub did_hit: bool,
     pub point: Point3,
+    /// A bound on the floating point error of each coordinate of `point`: the exact 
+    /// intersection lies in the box of these half-widths around it.
+    pub point_error: Vec3,
     pub t: Float,
+    /// The true (geometric) normal of the surface, which spawned rays are offset 
+    /// along and the geometric terms of the rendering equation use.
     pub surface_normal: Vec3,
+    /// The normal the surface is shaded with, i.e. the one its BSDF is oriented by. 
+    /// It is `surface_normal` except on meshes with vertex normals, where it is 
+    /// interpolated so that they look smooth.
+    pub shading_normal: Vec3,
     pub texture_coordinates: TextureCoordinates,
 }
 
@@ -27,8 +36,10 @@ impl Default for ShapeIntersectionInfo {
         Self {
             did_hit: false,
             point: Point3::default(),
+            point_error: Vec3::new(0.0, 0.0, 0.0),
             t: Float::INFINITY,
             surface_normal: Vec3::new(0.0,0.0,0.0),
+            shading_normal: Vec3::new(0.0,0.0,0.0),
             texture_coordinates: TextureCoordinates::default(),
         }
     }
@@ -41,11 +52,108 @@ impl ShapeIntersectionInfo {
             ..Default::default()
         }
     }
+
+    /// A ray leaving the surface at the intersection in `direction`. It starts just 
+    /// off the surface (see `offset_ray_origin()`), so that it cannot hit the surface 
+    /// it leaves, and every intersection along it counts.
+    pub fn spawn_ray(&self, direction: Vec3) -> Ray3 {
+        let origin = offset_ray_origin(&self.point, &self.point_error, &self.surface_normal, &direction);
+        let mut to_return = Ray3::new(origin, direction);
+        to_return.min_t = 0.0;
+
+        to_return
+    }
+
+    /// Like `spawn_ray()`, towards `target`, which is at $t=1$ along the ray.
+    pub fn spawn_ray_to(&self, target: &Point3) -> Ray3 {
+        let direction = target - &self.point;
+        let origin = offset_ray_origin(&self.point, &self.point_error, &self.surface_normal, &direction);
+        let mut to_return = Ray3::new(origin.clone(), target - &origin);
+        to_return.min_t = 0.0;
+
+        to_return
+    }
+}
+
+/// Moves `point`, whose coordinates are off by at most `point_error`, along the 
+/// surface `normal` to the side `direction` points to, far enough that the exact 
+/// surface is certainly behind it (as in PBRT). A ray from there in `direction` 
+/// cannot hit the surface again due to rounding.
+pub fn offset_ray_origin(point: &Point3, point_error: &Vec3, normal: &Vec3, direction: &Vec3) -> Point3 {
+    let distance = dot(&normal.abs(), point_error);
+    let offset = if dot(direction, normal) < 0.0 { -distance } else { distance } * normal;
+    let offset_point = point + &offset;
+
+    // Adding the offset rounds too, so round away from the point to be sure.
+    let round_away = |coordinate: Float, offset: Float| {
+        if offset > 0.0 {
+            coordinate.next_up()
+        } else if offset < 0.0 {
+            coordinate.next_down()
+        } else {
+            coordinate
+        }
+    };
+    Point3::new(
+        round_away(offset_point.x(), offset.x()),
+        round_away(offset_point.y(), offset.y()),
+        round_away(offset_point.z(), offset.z())
+    )
 }
 
 pub trait IntersectableShape {
     fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo;
 }
 
-pub trait ShapeLike: IntersectableShape + Transformable {}
+pub trait BoundableShape {
+    /// An axis-aligned box, in global space, containing the whole shape.
+    fn bounding_box(&self) -> BoundingBox;
+}
+
+/// A point on a shape, chosen as seen from some reference point.
+pub struct ShapeSample {
+    pub point: Point3,
+    pub surface_normal: Vec3,
+    /// The probability density, wrt solid angle at the reference point, of the 
+    /// direction towards `point`.
+    pub pdf: Float,
+}
+
+/// Shapes that can be sampled as light sources, so that we can aim rays at them 
+/// instead of waiting for rays to hit them by chance. By default a shape cannot be 
+/// sampled.
+pub trait SampleableShape {
+    fn is_sampleable(&self) -> bool {
+        false
+    }
+
+    /// Chooses a point on the shape visible from `reference`, where `u` is uniformly 
+    /// distributed in $[0,1)^2$. Returns `None` if the sample is wasted, e.g. the 
+    /// chosen direction misses the shape.
+    fn sample_from(&self, _reference: &Point3, _u: (Float, Float)) -> Option<ShapeSample> {
+        None
+    }
+
+    /// The density (wrt solid angle) with which `sample_from()` picks the first point 
+    /// where the ray from `reference` in `direction` hits the shape.
+    fn pdf_from(&self, _reference: &Point3, _direction: &Vec3) -> Float {
+        0.0
+    }
+}
+
+/// Converts a density wrt area at `point` into one wrt solid angle at `reference`:
+/// a small patch of area $A$ covers a solid angle of $A|\cos\theta|/d^2$.
+pub fn area_to_solid_angle_pdf(pdf: Float, reference: &Point3, point: &Point3, surface_normal: &Vec3) -> Float {
+    let to_point = point - reference;
+    let distance_squared = dot(&to_point, &to_point);
+    let cos_theta = Float::abs(dot(surface_normal, &to_point)) / Float::sqrt(distance_squared);
+    if cos_theta == 0.0 {
+        return 0.0;
+    }
+
+    pdf * distance_squared / cos_theta
+}
+
+/// Shapes are shared between the threads rendering a scene, hence `Send + Sync`.
+pub trait ShapeLike: IntersectableShape + BoundableShape + SampleableShape + Transformable + Send + Sync {}
 
@@ -7,7 +7,9 @@ use crate::utility::math::{
     vector::{Point3, Vec3, cross}, 
     ray::Ray3, 
     float::Float, 
-    angle::{Angle, AngleUnits}
+    bounding_box::BoundingBox,
+    angle::{Angle, AngleUnits},
+    orthonormal_basis::OrthonormalBasis
 };
 
 /// Conceptually, this struct is used to move between local and global coordinates.
@@ -39,6 +41,11 @@ impl Transform {
         self.inverse_matrix.transform_vector(vector)
     }
 
+    /// See `Matrix4::transform_point_with_error()`.
+    pub fn point_to_local_with_error(&self, point: &Point3, error: &Vec3) -> (Point3, Vec3) {
+        self.inverse_matrix.transform_point_with_error(point, error)
+    }
+
     pub fn ray_to_local(&self, ray: &Ray3) -> Ray3 {
         let mut to_return: Ray3 = ray.clone();
         to_return.origin = self.point_to_local(&ray.origin);
@@ -51,10 +58,39 @@ impl Transform {
         self.matrix.transform_point(point)
     }
 
+    /// See `Matrix4::transform_point_with_error()`.
+    pub fn point_to_global_with_error(&self, point: &Point3, error: &Vec3) -> (Point3, Vec3) {
+        self.matrix.transform_point_with_error(point, error)
+    }
+
     pub fn vector_to_global(&self, vector: &Vec3) -> Vec3 {
         self.matrix.transform_vector(vector)
     }
 
+    /// Surface normals do not transform like ordinary vectors: to stay orthogonal 
+    /// to the (transformed) surface they are multiplied by the inverse transpose. 
+    /// The returned normal is not normalized.
+    pub fn normal_to_global(&self, normal: &Vec3) -> Vec3 {
+        self.inverse_matrix.transpose().transform_vector(normal)
+    }
+
+    /// How many times larger a small patch of surface with the (local) unit 
+    /// `normal` is in global space.
+    pub fn area_scale_to_global(&self, normal: &Vec3) -> Float {
+        let basis = OrthonormalBasis::new_from_vector(normal);
+        let tangent = self.vector_to_global(&basis.vector_from_local(Vec3::new(1.0, 0.0, 0.0)));
+        let bitangent = self.vector_to_global(&basis.vector_from_local(Vec3::new(0.0, 1.0, 0.0)));
+        cross(&tangent, &bitangent).length()
+    }
+
+    /// The smallest axis-aligned box (in global space) containing the transformed 
+    /// `bounding_box`, which is given in local space.
+    pub fn bounding_box_to_global(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        bounding_box.corners()
+            .iter()
+            .fold(BoundingBox::empty(), |acc, corner| acc.union_point(&self.point_to_global(corner)))
+    }
+
     pub fn ray_to_global(&self, ray: &Ray3) -> Ray3 {
         let mut to_return: Ray3 = ray.clone();
         to_return.origin = self.point_to_global(&ray.origin);
@@ -109,6 +145,32 @@ impl Transform {
 
 // E==== CONSTRUCTORS }}}1
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::vector::dot;
+
+    #[test]
+    fn normals_stay_perpendicular_to_surfaces() {
+        let transform = Transform::new_from_matrix(
+            &(Matrix4::new_from_axis_rotation(&Matrix4AxisRotationInfo {
+                axis: Vec3::new(1.0, 1.0, 0.0).normalize(),
+                angle: Angle { amount: 30.0, units: AngleUnits::Degrees },
+            }) * Matrix4::new_from_scale(&Vec3::new(1.0, 4.0, 0.5)))
+        );
+
+        // The plane through the origin with normal $(1,1,1)$.
+        let normal = Vec3::new(1.0, 1.0, 1.0);
+        let global_normal = transform.normal_to_global(&normal);
+        for tangent in [Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, -1.0)] {
+            let global_tangent = transform.vector_to_global(&tangent);
+            assert!(dot(&global_normal, &global_tangent).abs() < 1e-4);
+        }
+        // Transformed like an ordinary vector, it would not be.
+        assert!(dot(&transform.vector_to_global(&normal), &transform.vector_to_global(&Vec3::new(1.0, -1.0, 0.0))).abs() > 1.0);
+    }
+}
+
 // // S==== TESTS {{{1
 //
 // #[cfg(test)]
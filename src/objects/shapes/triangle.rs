@@ -0,0 +1,188 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::textures::traits::TextureCoordinates,
+    utility::math::{
+        vector::{Point3, Vec3, dot, cross},
+        ray::Ray3,
+        float::{Float, FLOAT_ERR, gamma},
+        bounding_box::BoundingBox
+    }
+};
+use super::{
+    traits::{ShapeIntersectionInfo, IntersectableShape, BoundableShape, SampleableShape, Transformable, ShapeLike},
+    transform::Transform
+};
+
+// E==== IMPORTS }}}1
+
+/// A single triangle. For many triangles sharing vertices, use a `TriangleMesh`.
+pub struct Triangle {
+    /// In global space.
+    vertices: [Point3; 3],
+    transform: Transform,
+}
+
+pub struct TriangleInfo {
+    /// In local space.
+    pub vertices: [Point3; 3],
+    pub transform: Transform,
+}
+
+impl Triangle {
+    pub fn new(info: TriangleInfo) -> Self {
+        // Triangles stay triangles under affine transforms, so we transform the 
+        // vertices once rather than transforming every ray.
+        let vertices = info.vertices.map(|v| info.transform.point_to_global(&v));
+
+        Self {
+            vertices,
+            transform: info.transform,
+        }
+    }
+}
+
+/// Where a ray hit a triangle $p_0 p_1 p_2$.
+pub(super) struct TriangleHit {
+    pub t: Float,
+    /// $(b_0, b_1, b_2)$ such that the point hit is $b_0 p_0 + b_1 p_1 + b_2 p_2$.
+    pub barycentric: [Float; 3],
+}
+
+/// The Möller-Trumbore algorithm: solve $o + td = (1-u-v) p_0 + u p_1 + v p_2$ for
+/// $(t,u,v)$ using Cramer's rule, and check that $(u,v)$ lies in the triangle.
+pub(super) fn intersect_triangle(p0: &Point3, p1: &Point3, p2: &Point3, ray: &Ray3) -> Option<TriangleHit> {
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+
+    let p = cross(&ray.direction, &e2);
+    let determinant = dot(&e1, &p);
+    // The ray is parallel to the triangle's plane.
+    if determinant == 0.0 {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+
+    let s = &ray.origin - p0;
+    let u = dot(&s, &p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = cross(&s, &e1);
+    let v = dot(&ray.direction, &q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = dot(&e2, &q) * inverse_determinant;
+    // Hits closer than the rounding error of $t$ may be the triangle the ray is 
+    // leaving.
+    let t_error = {
+        let (s, e1, e2) = (s.abs(), e1.abs(), e2.abs());
+        let q_bound = Vec3::new(
+            s.y() * e1.z() + s.z() * e1.y(),
+            s.z() * e1.x() + s.x() * e1.z(),
+            s.x() * e1.y() + s.y() * e1.x()
+        );
+        gamma(7) * dot(&e2, &q_bound) * Float::abs(inverse_determinant)
+    };
+    if !ray.is_in_range(t) || t <= t_error {
+        return None;
+    }
+
+    Some(TriangleHit {
+        t,
+        barycentric: [1.0 - u - v, u, v],
+    })
+}
+
+/// A bound on the error of each coordinate of the point $b_0 p_0 + b_1 p_1 + b_2 p_2$ 
+/// hit on the triangle (see PBRT, "Bounding Intersection Point Error").
+pub(super) fn triangle_point_error(barycentric: &[Float; 3], p0: &Point3, p1: &Point3, p2: &Point3) -> Vec3 {
+    let [b0, b1, b2] = *barycentric;
+    gamma(7) * ((b0 * p0).abs() + (b1 * p1).abs() + (b2 * p2).abs())
+}
+
+/// The box containing the triangle, padded in case the triangle is axis-aligned.
+pub(super) fn triangle_bounding_box(p0: &Point3, p1: &Point3, p2: &Point3) -> BoundingBox {
+    BoundingBox::new_from_points(p0, p1)
+        .union_point(p2)
+        .padded(FLOAT_ERR)
+}
+
+impl IntersectableShape for Triangle {
+    fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
+        let [p0, p1, p2] = &self.vertices;
+        let hit = match intersect_triangle(p0, p1, p2, ray) {
+            Some(hit) => hit,
+            None => { return ShapeIntersectionInfo::no_intersection(); }
+        };
+
+        let [b0, b1, b2] = hit.barycentric;
+        let surface_normal = cross(&(p1 - p0), &(p2 - p0)).normalize();
+
+        let point = b0 * p0 + b1 * p1 + b2 * p2;
+
+        ShapeIntersectionInfo {
+            did_hit: true,
+            t: hit.t,
+            texture_coordinates: TextureCoordinates::new(b1 + b2, b2, surface_normal.clone(), point.clone()),
+            point_error: triangle_point_error(&hit.barycentric, p0, p1, p2),
+            point,
+            shading_normal: surface_normal.clone(),
+            surface_normal,
+        }
+    }
+}
+
+impl BoundableShape for Triangle {
+    fn bounding_box(&self) -> BoundingBox {
+        let [p0, p1, p2] = &self.vertices;
+        triangle_bounding_box(p0, p1, p2)
+    }
+}
+
+impl Transformable for Triangle {
+    fn get_transform(&self) -> Transform {
+        self.transform.clone()
+    }
+}
+
+/// Triangles are not sampled as lights yet.
+impl SampleableShape for Triangle {}
+
+impl ShapeLike for Triangle {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::vector::Vec3;
+
+    #[test]
+    fn ray_triangle_intersection() {
+        let triangle = Triangle::new(TriangleInfo {
+            vertices: [
+                Point3::new(0.0, 0.0, 2.0),
+                Point3::new(1.0, 0.0, 2.0),
+                Point3::new(0.0, 1.0, 2.0),
+            ],
+            transform: Transform::default(),
+        });
+
+        let hit = triangle.intersect(&Ray3::new(Point3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(hit.did_hit);
+        assert!((hit.t - 2.0).abs() < FLOAT_ERR);
+        assert!(Point3::are_equal(&hit.point, &Point3::new(0.25, 0.25, 2.0)));
+        assert!((hit.surface_normal.z().abs() - 1.0).abs() < FLOAT_ERR);
+
+        // Outside the triangle, but inside its bounding box.
+        let miss = triangle.intersect(&Ray3::new(Point3::new(0.75, 0.75, 0.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(!miss.did_hit);
+
+        // Parallel to the triangle.
+        let parallel = triangle.intersect(&Ray3::new(Point3::new(0.0, 0.0, 2.0), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(!parallel.did_hit);
+    }
+}
@@ -0,0 +1,219 @@
+//! A mesh of triangles sharing vertices, e.g. loaded from a model file.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    config::AccStructureAxisSelectionMethod,
+    objects::{textures::traits::TextureCoordinates, bvh::Bvh},
+    utility::math::{
+        vector::{Point3, Vec3, cross, dot},
+        ray::Ray3,
+        float::Float,
+        bounding_box::BoundingBox
+    }
+};
+use super::{
+    traits::{ShapeIntersectionInfo, IntersectableShape, BoundableShape, SampleableShape, Transformable, ShapeLike},
+    transform::Transform,
+    triangle::{intersect_triangle, triangle_bounding_box, triangle_point_error}
+};
+
+// E==== IMPORTS }}}1
+
+/// The geometry of a mesh, in local space, as stored in a model file. Vertex `i` is 
+/// at `positions[i]` and, if present, has the normal `normals[i]` and texture 
+/// coordinates `uvs[i]`.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleMeshData {
+    pub positions: Vec<Point3>,
+    /// Either empty or one per position.
+    pub normals: Vec<Vec3>,
+    /// Either empty or one per position.
+    pub uvs: Vec<(Float, Float)>,
+    /// Each triangle, as indices into the vertex arrays.
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMeshData {
+    /// Checks that the indices refer to existing vertices and that the optional 
+    /// vertex attributes are given for every vertex.
+    pub fn validate(&self) -> Result<(), String> {
+        let num_vertices = self.positions.len();
+        if !self.normals.is_empty() && self.normals.len() != num_vertices {
+            return Err(format!("{} normals given for {} vertices", self.normals.len(), num_vertices));
+        }
+        if !self.uvs.is_empty() && self.uvs.len() != num_vertices {
+            return Err(format!("{} texture coordinates given for {} vertices", self.uvs.len(), num_vertices));
+        }
+        if let Some(index) = self.triangles.iter().flatten().find(|&&index| index >= num_vertices) {
+            return Err(format!("vertex index {} is out of range ({} vertices)", index, num_vertices));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct TriangleMesh {
+    /// In global space.
+    positions: Vec<Point3>,
+    /// In global space, normalized.
+    normals: Vec<Vec3>,
+    uvs: Vec<(Float, Float)>,
+    triangles: Vec<[usize; 3]>,
+    /// Over the triangles of the mesh, so a ray only checks the triangles near it.
+    bvh: Bvh,
+    transform: Transform,
+}
+
+pub struct TriangleMeshInfo {
+    /// Must satisfy `TriangleMeshData::validate()`.
+    pub data: TriangleMeshData,
+    pub transform: Transform,
+}
+
+impl TriangleMesh {
+    pub fn new(info: TriangleMeshInfo) -> Self {
+        let data = info.data;
+        let transform = info.transform;
+
+        // As for a single triangle, transform the vertices once up front.
+        let positions: Vec<Point3> = data.positions
+            .iter()
+            .map(|p| transform.point_to_global(p))
+            .collect();
+        let normals: Vec<Vec3> = data.normals
+            .iter()
+            .map(|n| transform.normal_to_global(n).normalize())
+            .collect();
+
+        let triangle_bounds: Vec<BoundingBox> = data.triangles
+            .iter()
+            .map(|[i0, i1, i2]| triangle_bounding_box(&positions[*i0], &positions[*i1], &positions[*i2]))
+            .collect();
+        let bvh = Bvh::new(&triangle_bounds, AccStructureAxisSelectionMethod::SurfaceAreaHeuristic);
+
+        Self {
+            positions,
+            normals,
+            uvs: data.uvs,
+            triangles: data.triangles,
+            bvh,
+            transform,
+        }
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+}
+
+impl IntersectableShape for TriangleMesh {
+    fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
+        let mut closest: Option<(usize, [Float; 3], Float)> = None;
+
+        self.bvh.traverse(ray, |triangle_index, working_ray| {
+            let [i0, i1, i2] = self.triangles[triangle_index];
+            let hit = intersect_triangle(
+                &self.positions[i0], &self.positions[i1], &self.positions[i2], working_ray
+            )?;
+            closest = Some((triangle_index, hit.barycentric, hit.t));
+            Some(hit.t)
+        });
+
+        let (triangle_index, [b0, b1, b2], t) = match closest {
+            Some(hit) => hit,
+            None => { return ShapeIntersectionInfo::no_intersection(); }
+        };
+        let [i0, i1, i2] = self.triangles[triangle_index];
+        let (p0, p1, p2) = (&self.positions[i0], &self.positions[i1], &self.positions[i2]);
+
+        // Shade with the (smooth) interpolated vertex normals if the mesh has them, 
+        // but keep the flat triangle's normal, turned to the same side, for the 
+        // geometry: spawned rays must leave the actual surface.
+        let mut surface_normal = cross(&(p1 - p0), &(p2 - p0)).normalize();
+        let shading_normal = if self.normals.is_empty() {
+            surface_normal.clone()
+        } else {
+            (b0 * &self.normals[i0] + b1 * &self.normals[i1] + b2 * &self.normals[i2]).normalize()
+        };
+        if dot(&surface_normal, &shading_normal) < 0.0 {
+            surface_normal = (-1.0) * &surface_normal;
+        }
+
+        // Without texture coordinates, parametrize each triangle like 
+        // `(0,0), (1,0), (1,1)`.
+        let (u, v) = if self.uvs.is_empty() {
+            (b1 + b2, b2)
+        } else {
+            let (uv0, uv1, uv2) = (self.uvs[i0], self.uvs[i1], self.uvs[i2]);
+            (b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0, b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1)
+        };
+
+        let point = b0 * p0 + b1 * p1 + b2 * p2;
+
+        ShapeIntersectionInfo {
+            did_hit: true,
+            t,
+            texture_coordinates: TextureCoordinates::new(u, v, shading_normal.clone(), point.clone()),
+            point_error: triangle_point_error(&[b0, b1, b2], p0, p1, p2),
+            point,
+            surface_normal,
+            shading_normal,
+        }
+    }
+}
+
+impl BoundableShape for TriangleMesh {
+    fn bounding_box(&self) -> BoundingBox {
+        self.bvh.bounding_box()
+    }
+}
+
+impl Transformable for TriangleMesh {
+    fn get_transform(&self) -> Transform {
+        self.transform.clone()
+    }
+}
+
+/// Meshes are not sampled as lights yet.
+impl SampleableShape for TriangleMesh {}
+
+impl ShapeLike for TriangleMesh {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::{math::matrix::Matrix4, rng::RandomNumberGenerator};
+
+    #[test]
+    fn smooth_meshes_spawn_rays_off_the_actual_surface() {
+        // A triangle in the plane $z=0$, far from the origin so that rounding errors 
+        // are large, whose vertex normals all lean towards $+x$.
+        let leaning = Vec3::new(1.0, 0.0, 1.0).normalize();
+        let mesh = TriangleMesh::new(TriangleMeshInfo {
+            data: TriangleMeshData {
+                positions: vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)],
+                normals: vec![leaning.clone(), leaning.clone(), leaning.clone()],
+                uvs: Vec::new(),
+                triangles: vec![[0, 1, 2]],
+            },
+            transform: Transform::new_from_matrix(&Matrix4::new_from_translation(&Vec3::new(3.0e4, -2.0e4, 1.0e4))),
+        });
+
+        let mut rng = RandomNumberGenerator::from_seed(4);
+        for _ in 0..1000 {
+            let (x, y) = (0.05 + 0.4 * rng.next_float(), 0.05 + 0.4 * rng.next_float());
+            let target = mesh.transform.point_to_global(&Point3::new(x, y, 0.0));
+            let hit = mesh.intersect(&Ray3::new(&target + &Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0)));
+            assert!(hit.did_hit);
+            assert!(Vec3::are_equal(&hit.surface_normal, &Vec3::new(0.0, 0.0, 1.0)));
+            assert!(Vec3::are_equal(&hit.shading_normal, &leaning));
+
+            // Directions on one side of the surface but the other side of the 
+            // shading normal.
+            for direction in [Vec3::new(-1.0, 0.0, 0.3), Vec3::new(1.0, 0.0, -0.3)] {
+                assert!(!mesh.intersect(&hit.spawn_ray(direction)).did_hit);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{light::Spectrum, utility::math::{ray::Ray3, float::Float}};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// What the checks are laid out on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckerDomain {
+    /// Squares on the surface, by texture coordinates.
+    Surface,
+    /// Cubes filling space, by (global space) position. Objects look like they were 
+    /// carved out of a block of checks.
+    Solid,
+}
+
+/// Alternates between two other textures.
+#[derive(Debug)]
+pub struct CheckerTexture {
+    even: Arc<dyn TextureLike>,
+    odd: Arc<dyn TextureLike>,
+    /// The number of checks per unit (of texture coordinates or of space).
+    scale: Float,
+    domain: CheckerDomain,
+}
+
+pub struct CheckerTextureInfo {
+    pub even: Arc<dyn TextureLike>,
+    pub odd: Arc<dyn TextureLike>,
+    pub scale: Float,
+    pub domain: CheckerDomain,
+}
+
+impl CheckerTexture {
+    pub fn new(info: CheckerTextureInfo) -> Self {
+        Self {
+            even: info.even,
+            odd: info.odd,
+            scale: info.scale,
+            domain: info.domain,
+        }
+    }
+
+    /// Whether the check containing `coordinate` is an even one, i.e. the sum of its 
+    /// integer coordinates is even.
+    fn is_even(&self, coordinate: &TextureCoordinates) -> bool {
+        let check = |x: Float| (self.scale * x).floor() as i64;
+
+        let sum = match self.domain {
+            CheckerDomain::Surface => check(coordinate.u()) + check(coordinate.v()),
+            CheckerDomain::Solid => {
+                let p = coordinate.point();
+                check(p.x()) + check(p.y()) + check(p.z())
+            }
+        };
+
+        sum.rem_euclid(2) == 0
+    }
+}
+
+impl TextureLike for CheckerTexture {
+    fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        if self.is_even(coordinate) {
+            self.even.value_at(incoming_ray, coordinate)
+        } else {
+            self.odd.value_at(incoming_ray, coordinate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        objects::textures::constant::ConstantTexture, 
+        utility::math::vector::{Color3, Point3, Vec3}
+    };
+
+    #[test]
+    fn checks_alternate() {
+        let checker = |domain| CheckerTexture::new(CheckerTextureInfo {
+            even: Arc::new(ConstantTexture::new_from_rgb(Color3::new(1.0, 1.0, 1.0))),
+            odd: Arc::new(ConstantTexture::new_from_rgb(Color3::new(0.0, 0.0, 0.0))),
+            scale: 2.0,
+            domain,
+        });
+        let at = |u: Float, v: Float, point: Point3| {
+            TextureCoordinates::new(u, v, Vec3::new(0.0, 0.0, 1.0), point)
+        };
+
+        let surface = checker(CheckerDomain::Surface);
+        assert!(surface.is_even(&at(0.1, 0.1, Point3::new(0.7, 0.0, 0.0))));
+        assert!(!surface.is_even(&at(0.6, 0.1, Point3::origin())));
+        assert!(surface.is_even(&at(0.6, 0.6, Point3::origin())));
+
+        let solid = checker(CheckerDomain::Solid);
+        assert!(solid.is_even(&at(0.6, 0.1, Point3::new(0.1, 0.1, 0.1))));
+        assert!(!solid.is_even(&at(0.1, 0.1, Point3::new(0.1, 0.1, -0.1))));
+        assert!(solid.is_even(&at(0.1, 0.1, Point3::new(-0.1, 0.1, -0.1))));
+    }
+}
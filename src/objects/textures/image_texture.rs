@@ -0,0 +1,279 @@
+//! Textures looked up in an image, e.g. loaded from a PNG file.
+
+// S==== IMPORTS {{{1
+
+use std::{sync::Arc, path::Path, fs::File, io::BufReader};
+use image::codecs::hdr::HdrDecoder;
+use crate::{
+    light::{Spectrum, srgb_to_linear}, 
+    utility::math::{ray::Ray3, float::Float, vector::Color3}
+};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// How the color between the centers of texels is determined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// The color of the closest texel.
+    Nearest,
+    /// Linearly interpolate the four closest texels.
+    Bilinear,
+}
+
+/// What texture coordinates outside of $[0,1]$ refer to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureWrap {
+    /// Tile the image.
+    Repeat,
+    /// Extend the texels on the edges of the image.
+    Clamp,
+    /// Tile the image, flipping every other copy.
+    Mirror,
+}
+
+/// How the (integer) values stored in the image file are to be interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureColorSpace {
+    Srgb,
+    Linear,
+}
+
+#[derive(Debug)]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    /// Linear colors, row by row starting at the top of the image.
+    texels: Vec<Color3>,
+    filter: TextureFilter,
+    wrap: TextureWrap,
+}
+
+pub struct ImageTextureInfo {
+    pub width: usize,
+    pub height: usize,
+    /// `width * height` linear colors, row by row starting at the top of the image.
+    pub texels: Vec<Color3>,
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+}
+
+impl ImageTexture {
+    pub fn new(info: ImageTextureInfo) -> Self {
+        assert!(info.width > 0 && info.height > 0 && info.texels.len() == info.width * info.height);
+
+        Self {
+            width: info.width,
+            height: info.height,
+            texels: info.texels,
+            filter: info.filter,
+            wrap: info.wrap,
+        }
+    }
+
+    /// Loads a PNG, JPEG, HDR, ... file. Floating point images are linear already, so
+    /// `color_space` only applies to images storing integers.
+    pub fn new_from_file(
+        filename: &Path, 
+        filter: TextureFilter, 
+        wrap: TextureWrap, 
+        color_space: TextureColorSpace
+    ) -> Result<Self, String> {
+        let is_radiance_hdr = filename
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+        let (width, height, texels) = if is_radiance_hdr {
+            Self::read_radiance_hdr(filename)?
+        } else {
+            Self::read_image(filename, color_space)?
+        };
+        if width == 0 || height == 0 {
+            return Err("the image is empty".to_string());
+        }
+
+        let info = ImageTextureInfo {
+            width,
+            height,
+            texels,
+            filter,
+            wrap,
+        };
+        Ok(Self::new(info))
+    }
+
+    fn read_image(filename: &Path, color_space: TextureColorSpace) -> Result<(usize, usize, Vec<Color3>), String> {
+        let image = image::open(filename).map_err(|e| e.to_string())?;
+        let is_float = matches!(
+            image, 
+            image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_)
+        );
+        let decode = |component: f32| -> Float {
+            match (is_float, color_space) {
+                (false, TextureColorSpace::Srgb) => srgb_to_linear(component as Float),
+                _ => component as Float,
+            }
+        };
+
+        // Integers are normalized to [0,1] by the conversion.
+        let image = image.into_rgb32f();
+        let texels = image
+            .pixels()
+            .map(|p| Color3::new(decode(p[0]), decode(p[1]), decode(p[2])))
+            .collect();
+
+        Ok((image.width() as usize, image.height() as usize, texels))
+    }
+
+    /// `image::open()` converts Radiance HDR files to 8 bits per channel, losing 
+    /// everything brighter than white, so we decode them ourselves.
+    fn read_radiance_hdr(filename: &Path) -> Result<(usize, usize, Vec<Color3>), String> {
+        let file = File::open(filename).map_err(|e| e.to_string())?;
+        let decoder = HdrDecoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let metadata = decoder.metadata();
+        let texels = decoder
+            .read_image_hdr()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|p| Color3::new(p[0] as Float, p[1] as Float, p[2] as Float))
+            .collect();
+
+        Ok((metadata.width as usize, metadata.height as usize, texels))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Linear colors, row by row starting at the top of the image.
+    pub fn texels(&self) -> &[Color3] {
+        &self.texels
+    }
+
+    /// Maps a (possibly out of bounds) texel index along an axis with `size` texels 
+    /// to a valid one.
+    fn wrap_index(&self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match self.wrap {
+            TextureWrap::Repeat => index.rem_euclid(size),
+            TextureWrap::Clamp => index.clamp(0, size - 1),
+            TextureWrap::Mirror => {
+                let m = index.rem_euclid(2 * size);
+                if m < size { m } else { 2 * size - 1 - m }
+            }
+        };
+
+        wrapped as usize
+    }
+
+    fn texel(&self, x: i64, y: i64) -> &Color3 {
+        let x = self.wrap_index(x, self.width);
+        let y = self.wrap_index(y, self.height);
+        &self.texels[y * self.width + x]
+    }
+
+    /// The color at $(u,v)$, where $(0,0)$ is the bottom left of the image and $(1,1)$ 
+    /// its top right.
+    pub fn color_at(&self, u: Float, v: Float) -> Color3 {
+        // In units of texels, with the origin at the top left corner of the image.
+        let x = u * (self.width as Float);
+        let y = (1.0 - v) * (self.height as Float);
+
+        match self.filter {
+            TextureFilter::Nearest => {
+                self.texel(x.floor() as i64, y.floor() as i64).clone()
+            },
+            TextureFilter::Bilinear => {
+                // Relative to the texel centers.
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (dx, dy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                (1.0 - dx) * (1.0 - dy) * self.texel(x0, y0)
+                    + dx * (1.0 - dy) * self.texel(x0 + 1, y0)
+                    + (1.0 - dx) * dy * self.texel(x0, y0 + 1)
+                    + dx * dy * self.texel(x0 + 1, y0 + 1)
+            }
+        }
+    }
+}
+
+impl TextureLike for ImageTexture {
+    fn value_at(&self, _incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        Arc::new(self.color_at(coordinate.u(), coordinate.v()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::float::FLOAT_ERR;
+
+    /// A 2x1 image: black on the left, white on the right.
+    fn black_and_white(filter: TextureFilter, wrap: TextureWrap) -> ImageTexture {
+        ImageTexture::new(ImageTextureInfo {
+            width: 2,
+            height: 1,
+            texels: vec![Color3::new(0.0, 0.0, 0.0), Color3::new(1.0, 1.0, 1.0)],
+            filter,
+            wrap,
+        })
+    }
+
+    #[test]
+    fn filtering_and_wrapping() {
+        let close = |color: Color3, expected: Float| (color.x() - expected).abs() < FLOAT_ERR;
+
+        let nearest = black_and_white(TextureFilter::Nearest, TextureWrap::Repeat);
+        assert!(close(nearest.color_at(0.2, 0.5), 0.0));
+        assert!(close(nearest.color_at(0.7, 0.5), 1.0));
+        assert!(close(nearest.color_at(1.2, 0.5), 0.0));
+        assert!(close(nearest.color_at(-0.2, 0.5), 1.0));
+
+        let clamped = black_and_white(TextureFilter::Nearest, TextureWrap::Clamp);
+        assert!(close(clamped.color_at(1.7, 0.5), 1.0));
+        assert!(close(clamped.color_at(-3.0, 0.5), 0.0));
+
+        let mirrored = black_and_white(TextureFilter::Nearest, TextureWrap::Mirror);
+        assert!(close(mirrored.color_at(1.2, 0.5), 1.0));
+        assert!(close(mirrored.color_at(1.7, 0.5), 0.0));
+        assert!(close(mirrored.color_at(2.2, 0.5), 0.0));
+
+        // Texel centers are at u = 0.25 and u = 0.75.
+        let bilinear = black_and_white(TextureFilter::Bilinear, TextureWrap::Clamp);
+        assert!(close(bilinear.color_at(0.25, 0.5), 0.0));
+        assert!(close(bilinear.color_at(0.5, 0.5), 0.5));
+        assert!(close(bilinear.color_at(0.625, 0.5), 0.75));
+        assert!(close(bilinear.color_at(0.0, 0.5), 0.0));
+
+        // Between the last and (wrapped around) first texel.
+        let bilinear_repeat = black_and_white(TextureFilter::Bilinear, TextureWrap::Repeat);
+        assert!(close(bilinear_repeat.color_at(1.0, 0.5), 0.5));
+    }
+
+    #[test]
+    fn srgb_images_are_linearized() {
+        let filename = std::env::temp_dir().join("mirth_image_texture_test.png");
+        let image = image::RgbImage::from_pixel(1, 1, image::Rgb([255, 188, 0]));
+        image.save(&filename).unwrap();
+
+        let load = |color_space| ImageTexture::new_from_file(
+            &filename, TextureFilter::Nearest, TextureWrap::Repeat, color_space
+        ).unwrap().color_at(0.5, 0.5);
+
+        let srgb = load(TextureColorSpace::Srgb);
+        assert!((srgb.x() - 1.0).abs() < 1e-3);
+        // 188 is roughly the sRGB encoding of 0.5.
+        assert!((srgb.y() - 0.5).abs() < 1e-2);
+        assert!(srgb.z().abs() < 1e-3);
+
+        let linear = load(TextureColorSpace::Linear);
+        assert!((linear.y() - 188.0 / 255.0).abs() < 1e-3);
+        std::fs::remove_file(&filename).unwrap();
+    }
+}
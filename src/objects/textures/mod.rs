@@ -2,3 +2,7 @@
 pub mod traits;
 pub mod constant;
 
+pub mod image_texture;
+pub mod perlin;
+pub mod checker;
+pub mod noise;
@@ -0,0 +1,86 @@
+//! Textures blending between two other textures according to a pattern made of 
+//! Perlin noise.
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{light::Spectrum, utility::math::{ray::Ray3, float::{Float, FloatConstants}}};
+use super::{traits::{TextureLike, TextureCoordinates}, perlin::Perlin};
+
+// E==== IMPORTS }}}1
+
+/// Each pattern maps a point to a number in $[0,1]$.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoisePattern {
+    /// Plain (smooth) Perlin noise.
+    Noise,
+    /// Turbulence, which looks rougher, like smoke or clouds.
+    Turbulence { octaves: u32 },
+    /// Stripes along the $x$ axis, distorted by turbulence.
+    Marble { octaves: u32, distortion: Float },
+    /// Rings around the $y$ axis, distorted by turbulence.
+    Wood { octaves: u32, distortion: Float },
+}
+
+#[derive(Debug)]
+pub struct NoiseTexture {
+    /// Where the pattern is 0.
+    low: Arc<dyn TextureLike>,
+    /// Where the pattern is 1.
+    high: Arc<dyn TextureLike>,
+    pattern: NoisePattern,
+    /// Points are multiplied by this before evaluating the pattern, so larger values 
+    /// give finer detail.
+    scale: Float,
+    perlin: Perlin,
+}
+
+pub struct NoiseTextureInfo {
+    pub low: Arc<dyn TextureLike>,
+    pub high: Arc<dyn TextureLike>,
+    pub pattern: NoisePattern,
+    pub scale: Float,
+    pub seed: u32,
+}
+
+impl NoiseTexture {
+    pub fn new(info: NoiseTextureInfo) -> Self {
+        Self {
+            low: info.low,
+            high: info.high,
+            pattern: info.pattern,
+            scale: info.scale,
+            perlin: Perlin::new(info.seed),
+        }
+    }
+
+    fn pattern_at(&self, coordinate: &TextureCoordinates) -> Float {
+        let p = self.scale * coordinate.point();
+
+        let value = match self.pattern {
+            NoisePattern::Noise => 0.5 * (1.0 + self.perlin.noise(&p)),
+            NoisePattern::Turbulence { octaves } => self.perlin.turbulence(&p, octaves),
+            NoisePattern::Marble { octaves, distortion } => {
+                let phase = p.x() + distortion * self.perlin.turbulence(&p, octaves);
+                0.5 * (1.0 + Float::sin(Float::get_pi() * phase))
+            },
+            NoisePattern::Wood { octaves, distortion } => {
+                let radius = Float::sqrt(p.x() * p.x() + p.z() * p.z())
+                    + distortion * self.perlin.turbulence(&p, octaves);
+                radius - radius.floor()
+            },
+        };
+
+        value.clamp(0.0, 1.0)
+    }
+}
+
+impl TextureLike for NoiseTexture {
+    fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        let t = self.pattern_at(coordinate);
+        let low = self.low.value_at(incoming_ray, coordinate);
+        let high = self.high.value_at(incoming_ray, coordinate);
+
+        Arc::new((1.0 - t) * low.as_ref() + t * high.as_ref())
+    }
+}
@@ -0,0 +1,127 @@
+//! Perlin's "improved noise": a smooth pseudo random function of space, which is
+//! the basis of most procedural textures.
+
+// S==== IMPORTS {{{1
+
+use crate::utility::{
+    math::{float::Float, vector::Point3},
+    rng::RandomNumberGenerator
+};
+
+// E==== IMPORTS }}}1
+
+/// The lattice repeats after this many cells along each axis.
+const PERMUTATION_SIZE: usize = 256;
+
+#[derive(Debug)]
+pub struct Perlin {
+    /// A random permutation of `0..PERMUTATION_SIZE`, repeated twice so that sums of 
+    /// two entries can be looked up without wrapping.
+    permutation: Vec<usize>,
+}
+
+impl Perlin {
+    /// Different seeds give different (but equally random looking) noise.
+    pub fn new(seed: u32) -> Self {
+        let mut rng = RandomNumberGenerator::from_seed(seed);
+
+        // Fisher-Yates shuffle.
+        let mut permutation: Vec<usize> = (0..PERMUTATION_SIZE).collect();
+        for i in (1..PERMUTATION_SIZE).rev() {
+            let j = usize::min((rng.next_float() * ((i + 1) as Float)) as usize, i);
+            permutation.swap(i, j);
+        }
+        permutation.extend_from_within(..);
+
+        Self { permutation }
+    }
+
+    /// Noise at `point`, in $[-1,1]$. It is zero at the corners of the integer 
+    /// lattice, and varies over roughly one unit.
+    pub fn noise(&self, point: &Point3) -> Float {
+        let floor = [point.x().floor(), point.y().floor(), point.z().floor()];
+        // The cell of the lattice the point is in ...
+        let cell = floor.map(|f| (f as i64).rem_euclid(PERMUTATION_SIZE as i64) as usize);
+        // ... and the position within it.
+        let (x, y, z) = (point.x() - floor[0], point.y() - floor[1], point.z() - floor[2]);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.permutation;
+        let a = p[cell[0]] + cell[1];
+        let aa = p[a] + cell[2];
+        let ab = p[a + 1] + cell[2];
+        let b = p[cell[0] + 1] + cell[1];
+        let ba = p[b] + cell[2];
+        let bb = p[b + 1] + cell[2];
+
+        lerp(w,
+            lerp(v,
+                lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1.0, y, z)),
+                lerp(u, gradient(p[ab], x, y - 1.0, z), gradient(p[bb], x - 1.0, y - 1.0, z))),
+            lerp(v,
+                lerp(u, gradient(p[aa + 1], x, y, z - 1.0), gradient(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, gradient(p[ab + 1], x, y - 1.0, z - 1.0), gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0))))
+    }
+
+    /// A sum of `octaves` layers of noise magnitudes, each at twice the frequency and 
+    /// half the amplitude of the previous one. Roughly in $[0,1]$.
+    pub fn turbulence(&self, point: &Point3, octaves: u32) -> Float {
+        let mut sum: Float = 0.0;
+        let mut frequency: Float = 1.0;
+        let mut amplitude: Float = 1.0;
+
+        for _ in 0..octaves {
+            sum += amplitude * self.noise(&(frequency * point)).abs();
+            frequency *= 2.0;
+            amplitude *= 0.5;
+        }
+
+        sum
+    }
+}
+
+/// $6t^5 - 15t^4 + 10t^3$, which has zero first and second derivatives at 0 and 1.
+fn fade(t: Float) -> Float {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: Float, a: Float, b: Float) -> Float {
+    a + t * (b - a)
+}
+
+/// The dot product of $(x,y,z)$ with one of 12 gradient directions (the midpoints 
+/// of the edges of a cube), chosen by `hash`.
+fn gradient(hash: usize, x: Float, y: Float, z: Float) -> Float {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_bounded_and_continuous() {
+        let perlin = Perlin::new(1);
+        let mut rng = RandomNumberGenerator::from_seed(2);
+
+        for _ in 0..1000 {
+            let point = Point3::new(
+                20.0 * rng.next_float() - 10.0, 
+                20.0 * rng.next_float() - 10.0, 
+                20.0 * rng.next_float() - 10.0
+            );
+            let noise = perlin.noise(&point);
+            assert!((-1.0..=1.0).contains(&noise));
+
+            let nearby = perlin.noise(&(&point + Point3::new(1e-3, 1e-3, 1e-3)));
+            assert!((noise - nearby).abs() < 1e-2);
+        }
+
+        assert_eq!(perlin.noise(&Point3::new(3.0, -2.0, 5.0)), 0.0);
+        assert_ne!(perlin.noise(&Point3::new(0.5, 0.5, 0.5)), Perlin::new(2).noise(&Point3::new(0.5, 0.5, 0.5)));
+    }
+}
@@ -1,27 +1,43 @@
 use std::{sync::Arc, fmt::Debug};
 use crate::{
-    utility::math::{float::Float, ray::Ray3, vector::Vec3}, 
+    utility::math::{float::Float, ray::Ray3, vector::{Vec3, Point3}}, 
     light::Spectrum
 };
 
+/// Where on a surface a texture is looked up. Shapes parametrize their surfaces by
+/// $(u,v)$, usually in $[0,1]^2$.
 #[derive(Debug)]
 pub struct TextureCoordinates {
     u: Float,
     v: Float,
+    /// The (global space, unit) surface normal at the point.
     normal: Vec3,
+    /// The point itself, in global space. Solid textures are looked up by this.
+    point: Point3,
 }
 
 impl TextureCoordinates {
+    pub fn new(u: Float, v: Float, normal: Vec3, point: Point3) -> Self {
+        TextureCoordinates { u, v, normal, point }
+    }
+
+    pub fn u(&self) -> Float { self.u }
+    pub fn v(&self) -> Float { self.v }
+    pub fn normal(&self) -> &Vec3 { &self.normal }
+    pub fn point(&self) -> &Point3 { &self.point }
+
     pub fn default() -> Self {
         TextureCoordinates{
             u: 0.0,
             v: 0.0,
-            normal: Vec3::new(0.0,0.0,0.0)
+            normal: Vec3::new(0.0,0.0,0.0),
+            point: Point3::origin(),
         }
     }
 }
 
-pub trait TextureLike: Debug {
+/// Textures are shared between the threads rendering a scene, hence `Send + Sync`.
+pub trait TextureLike: Debug + Send + Sync {
     fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum>;
 }
 
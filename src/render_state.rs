@@ -0,0 +1,249 @@
+//! Saving a render in progress to disk, so that it can later be resumed and given
+//! more samples.
+//!
+//! # the file format
+//!
+//! All numbers are little-endian.
+//! ```
+//! magic          8 bytes, "MIRTHACC"
+//! version        u32, currently 5
+//! scene hash     u64, see `scene_parsing::scene_hash`
+//! seed           u32
+//! sampler layout u32, the samples per pixel the sampler is laid out for (see 
+//!                `SamplerLike::samples_per_pixel()`), or 0 if it is not
+//! passes         u32, the number of passes over the image so far
+//! width, height  u32 each
+//! pixels         width * height times, starting at the bottom left and going row
+//!                by row:
+//!     sum        3 f64, the filter-weighted sum of the red, green and blue of the
+//!                samples around the pixel
+//!     weight     f64, the sum of the filter weights
+//!     count      u32, the number of samples taken in the pixel
+//!     mean, m2   f64 each, the statistics of the luminance of the samples (see
+//!                `RunningStatistics`)
+//! ```
+//!
+//! Numbers are stored in double precision whatever `Float` is, so that nothing is
+//! lost with the `f64` feature and a render can be resumed by either build.
+//!
+//! The random numbers of a render are derived from the seed, the sampler's layout 
+//! and each pixel's number of samples (see `samplers`), so these are all of the 
+//! random number generator's state: a resumed render is identical to one that was 
+//! never stopped. The scene hash ignores the number of samples in the scene file, 
+//! which the layout may depend on, hence the layout is stored separately.
+
+use std::{fmt::{Debug, Display}, fs::{self, File}, io::{self, BufReader, BufWriter, Read, Write}};
+
+use crate::utility::{image::{ImageBuffer, Resolution}, math::{float::{self, Float}, vector::Color3, statistics::RunningStatistics}};
+
+const MAGIC: &[u8; 8] = b"MIRTHACC";
+const VERSION: u32 = 5;
+/// A sum and a weight (8 bytes each), a count (4 bytes) and two statistics (8 bytes 
+/// each).
+const BYTES_PER_PIXEL: usize = 4 * 8 + 4 + 2 * 8;
+
+/// Everything needed to continue a render where it left off.
+pub struct RenderState {
+    /// Identifies the scene that was being rendered.
+    pub scene_hash: u64,
+    pub seed: u32,
+    /// See `SamplerLike::samples_per_pixel()`.
+    pub sampler_samples_per_pixel: Option<u32>,
+    pub image_buffer: ImageBuffer,
+}
+
+impl RenderState {
+    /// Writes to a temporary file first, so an interrupted write never clobbers the
+    /// previous state.
+    pub fn save_to_file(&self, filename: &str) -> Result<(), RenderStateError> {
+        let to_error = |e: io::Error| RenderStateError::Io {
+            filename: filename.to_string(),
+            msg: e.to_string(),
+        };
+        let temporary_filename = format!("{}.tmp", filename);
+
+        let file = File::create(&temporary_filename).map_err(to_error)?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer).map_err(to_error)?;
+        writer.flush().map_err(to_error)?;
+        drop(writer);
+
+        fs::rename(&temporary_filename, filename).map_err(to_error)
+    }
+
+    pub fn load_from_file(filename: &str) -> Result<Self, RenderStateError> {
+        let file = File::open(filename).map_err(|e| RenderStateError::Io {
+            filename: filename.to_string(),
+            msg: e.to_string(),
+        })?;
+
+        Self::read(&mut BufReader::new(file)).map_err(|msg| RenderStateError::Invalid {
+            filename: filename.to_string(),
+            msg,
+        })
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let resolution = self.image_buffer.resolution();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.scene_hash.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&self.sampler_samples_per_pixel.unwrap_or(0).to_le_bytes())?;
+        writer.write_all(&self.image_buffer.num_passes().to_le_bytes())?;
+        writer.write_all(&resolution.width.to_le_bytes())?;
+        writer.write_all(&resolution.height.to_le_bytes())?;
+
+        let sums = self.image_buffer.sums();
+        let pixels = sums.iter()
+            .zip(self.image_buffer.weights())
+            .zip(self.image_buffer.statistics());
+        for ((sum, weight), statistics) in pixels {
+            for value in [sum.x(), sum.y(), sum.z(), *weight] {
+                writer.write_all(&float::to_f64(value).to_le_bytes())?;
+            }
+            writer.write_all(&statistics.count().to_le_bytes())?;
+            for value in [statistics.mean(), statistics.m2()] {
+                writer.write_all(&float::to_f64(value).to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read(reader: &mut impl Read) -> Result<Self, String> {
+        let mut magic = [0u8; 8];
+        let has_magic = reader.read_exact(&mut magic).is_ok() && &magic == MAGIC;
+        if !has_magic {
+            return Err("not a render state file".to_string());
+        }
+
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(format!("unsupported version {} (expected {})", version, VERSION));
+        }
+
+        let mut scene_hash = [0u8; 8];
+        reader.read_exact(&mut scene_hash).map_err(|_| "the file is truncated".to_string())?;
+        let seed = read_u32(reader)?;
+        let sampler_samples_per_pixel = Some(read_u32(reader)?).filter(|n| *n != 0);
+        let passes = read_u32(reader)?;
+        let resolution = Resolution {
+            width: read_u32(reader)?,
+            height: read_u32(reader)?,
+        };
+
+        let num_pixels = (resolution.width as usize) * (resolution.height as usize);
+        let mut bytes = vec![0u8; num_pixels * BYTES_PER_PIXEL];
+        reader.read_exact(&mut bytes).map_err(|_| "the file is truncated".to_string())?;
+
+        let mut sums = Vec::with_capacity(num_pixels);
+        let mut weights = Vec::with_capacity(num_pixels);
+        let mut statistics = Vec::with_capacity(num_pixels);
+        for pixel in bytes.chunks_exact(BYTES_PER_PIXEL) {
+            // The count comes between the fourth and fifth numbers.
+            let float = |i: usize| -> Float {
+                let start = if i < 4 { 8 * i } else { 8 * i + 4 };
+                f64::from_le_bytes(pixel[start..start + 8].try_into().unwrap()) as Float
+            };
+            let count = u32::from_le_bytes(pixel[32..36].try_into().unwrap());
+
+            sums.push(Color3::new(float(0), float(1), float(2)));
+            weights.push(float(3));
+            statistics.push(RunningStatistics::new_from_parts(count, float(4), float(5)));
+        }
+
+        Ok(Self {
+            scene_hash: u64::from_le_bytes(scene_hash),
+            seed,
+            sampler_samples_per_pixel,
+            image_buffer: ImageBuffer::new_from_parts(resolution, &sums, weights, statistics, passes),
+        })
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, String> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes).map_err(|_| "the file is truncated".to_string())?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub enum RenderStateError {
+    /// Opening, reading or writing the file failed.
+    Io { filename: String, msg: String },
+    /// The file is not a render state this version can read.
+    Invalid { filename: String, msg: String },
+}
+
+impl Display for RenderStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderStateError::Io { filename, msg } => write!(
+                f, "could not access render state '{}': {}", filename, msg
+            ),
+            RenderStateError::Invalid { filename, msg } => write!(
+                f, "invalid render state '{}': {}", filename, msg
+            ),
+        }
+    }
+}
+
+impl Debug for RenderStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utility::image::{Pixel, PixelSample, RenderedTile, Tile}, filters::tent::TentFilter};
+
+    #[test]
+    fn round_trip() {
+        let resolution = Resolution { width: 3, height: 2 };
+        let mut image_buffer = ImageBuffer::new(resolution.clone());
+        for i in 0..2 {
+            let tile = Tile { origin: Pixel { x: 0, y: 0 }, width: 3, height: 2 };
+            // Leave out one pixel in the second pass, as adaptive sampling would.
+            let samples = tile.pixels()
+                .filter(|pixel| i == 0 || pixel.x != 1)
+                .map(|pixel| {
+                    let color = Color3::new(pixel.x as Float, pixel.y as Float, i as Float + 0.25);
+                    PixelSample { pixel, offset: (0.25, 0.75), color }
+                })
+                .collect();
+            image_buffer.add_sample(&[RenderedTile { tile, samples }], &TentFilter::new(1.0));
+        }
+        let state = RenderState { 
+            scene_hash: 0xDEADBEEF12345678, 
+            seed: 7, 
+            sampler_samples_per_pixel: Some(16), 
+            image_buffer 
+        };
+
+        let mut bytes = Vec::new();
+        state.write(&mut bytes).unwrap();
+        let restored = RenderState::read(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.scene_hash, state.scene_hash);
+        assert_eq!(restored.seed, 7);
+        assert_eq!(restored.sampler_samples_per_pixel, Some(16));
+        assert_eq!(restored.image_buffer.num_passes(), 2);
+        assert_eq!(restored.image_buffer.resolution().width, 3);
+        for (a, b) in restored.image_buffer.sums().iter().zip(state.image_buffer.sums().iter()) {
+            assert!(a.x() == b.x() && a.y() == b.y() && a.z() == b.z());
+        }
+        assert_eq!(restored.image_buffer.weights(), state.image_buffer.weights());
+        let statistics = restored.image_buffer.statistics().iter().zip(state.image_buffer.statistics());
+        for (a, b) in statistics {
+            assert!(a.count() == b.count() && a.mean() == b.mean() && a.m2() == b.m2());
+        }
+        assert_eq!(restored.image_buffer.num_pixel_samples(&Pixel { x: 1, y: 0 }), 1);
+
+        // Truncated or foreign files are rejected rather than misread.
+        assert!(RenderState::read(&mut &bytes[..bytes.len() - 1]).is_err());
+        assert!(RenderState::read(&mut &b"not a render state"[..]).is_err());
+    }
+}
@@ -1,4 +1,7 @@
 //! Methods for generating random samples (points) on various geometries.
+//!
+//! The `*_from_uniform()` variants transform given numbers, uniformly distributed in
+//! $[0,1)$, rather than drawing them from a random number generator.
 
 use crate::utility::{
     rng::RandomNumberGenerator, 
@@ -14,16 +17,40 @@ pub struct SampleResult {
 }
 
 pub fn uniform_on_2sphere_hemisphere(rng: &mut RandomNumberGenerator) -> SampleResult {
-    sphere_sampler_helper(rng, SphereSampleKind::UniformHemisphere)
+    let u = (rng.next_float(), rng.next_float());
+    sphere_sampler_helper(u, SphereSampleKind::UniformHemisphere)
 }
 
 pub fn cosine_on_2sphere_hemisphere(rng: &mut RandomNumberGenerator) -> SampleResult {
-    sphere_sampler_helper(rng, SphereSampleKind::CosineHemisphere)
+    let u = (rng.next_float(), rng.next_float());
+    sphere_sampler_helper(u, SphereSampleKind::CosineHemisphere)
+}
+
+pub fn cosine_on_2sphere_hemisphere_from_uniform(u: (Float, Float)) -> SampleResult {
+    sphere_sampler_helper(u, SphereSampleKind::CosineHemisphere)
+}
+
+/// Uniformly on the whole unit sphere.
+pub fn uniform_on_2sphere_from_uniform(u: (Float, Float)) -> SampleResult {
+    let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * u.0);
+    let cos_theta = 1.0 - 2.0 * u.1;
+    let sin_theta = Float::sqrt(Float::max(1.0 - cos_theta * cos_theta, 0.0));
+
+    SampleResult {
+        point: Vec3::new(cos_phi * sin_theta, sin_phi * sin_theta, cos_theta),
+        pdf: 0.25 * Float::get_1_pi(),
+    }
 }
 
 pub fn uniform_in_1sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
-    let r = Float::sqrt(rng.next_float());
-    let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * rng.next_float());
+    uniform_in_1sphere_from_uniform((rng.next_float(), rng.next_float()))
+}
+
+/// Uniformly in the unit disk: `u.0` determines the distance from the center and 
+/// `u.1` the angle.
+pub fn uniform_in_1sphere_from_uniform(u: (Float, Float)) -> SampleResult {
+    let r = Float::sqrt(u.0);
+    let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * u.1);
 
     SampleResult {
         point: Vec3::new(cos_phi * r, sin_phi * r, 0.0),
@@ -31,6 +58,48 @@ pub fn uniform_in_1sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
     }
 }
 
+/// Uniformly in the solid unit ball.
+pub fn uniform_in_2sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
+    uniform_in_2sphere_from_uniform(rng.next_float(), (rng.next_float(), rng.next_float()))
+}
+
+/// Uniformly in the solid unit ball: `u_radius` determines the distance from the 
+/// center and `u_direction` the direction.
+pub fn uniform_in_2sphere_from_uniform(u_radius: Float, u_direction: (Float, Float)) -> SampleResult {
+    let direction = uniform_on_2sphere_from_uniform(u_direction).point;
+    // The distance is distributed like the volume of the ball of that radius.
+    let r = Float::cbrt(u_radius);
+
+    SampleResult {
+        point: r * direction,
+        pdf: 0.75 * Float::get_1_pi(),
+    }
+}
+
+/// Uniformly on the rectangle with corners $(0,0,0)$ and $(w,h,0)$. The density is 
+/// wrt area.
+pub fn uniform_on_rectangle_from_uniform(u: (Float, Float), width: Float, height: Float) -> SampleResult {
+    SampleResult {
+        point: Vec3::new(u.0 * width, u.1 * height, 0.0),
+        pdf: 1.0 / (width * height),
+    }
+}
+
+/// Uniformly (wrt solid angle) among the unit vectors making an angle of at most 
+/// $\theta_{max}$ with the $z$-axis. As seen from outside a sphere, the sphere fills 
+/// such a cone, so this samples the directions towards it.
+pub fn uniform_in_cone_from_uniform(u: (Float, Float), cos_theta_max: Float) -> SampleResult {
+    let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * u.0);
+    // As in the Archimedes hat-box theorem, the cosine is uniformly distributed.
+    let cos_theta = 1.0 - u.1 * (1.0 - cos_theta_max);
+    let sin_theta = Float::sqrt(Float::max(1.0 - cos_theta * cos_theta, 0.0));
+
+    SampleResult {
+        point: Vec3::new(cos_phi * sin_theta, sin_phi * sin_theta, cos_theta),
+        pdf: 1.0 / (2.0 * Float::get_pi() * (1.0 - cos_theta_max)),
+    }
+}
+
 // S==== HELPERS {{{1
 
 enum SphereSampleKind {
@@ -41,24 +110,24 @@ enum SphereSampleKind {
 }
 
 /// Helper encapsulating various ways to sample on the unit sphere.
-fn sphere_sampler_helper(rng: &mut RandomNumberGenerator, kind: SphereSampleKind) -> SampleResult {
+fn sphere_sampler_helper(u: (Float, Float), kind: SphereSampleKind) -> SampleResult {
     let pdf: Float;
 
     // We sample spherical coordinates.
 
-    let phi = (2 as Float) * Float::get_pi() * rng.next_float();
+    let phi = (2 as Float) * Float::get_pi() * u.0;
     let (sin_phi, cos_phi) = Float::sin_cos(phi);
 
     let cos_theta = match kind {
         SphereSampleKind::UniformHemisphere => {
             // By the Archimedes hat-box theorem, it suffices to sample the enscribing
             // cylinder.
-            pdf = 0.5 * Float::get_pi();
-            rng.next_float()
+            pdf = 0.5 * Float::get_1_pi();
+            u.1
         },
         SphereSampleKind::CosineHemisphere => {
-            let to_return = Float::sqrt(rng.next_float());
-            pdf = to_return * Float::get_pi();
+            let to_return = Float::sqrt(u.1);
+            pdf = to_return * Float::get_1_pi();
             to_return
         }
     };
@@ -108,6 +177,29 @@ mod tests {
             write!(&mut file, "{},{},{}\n", point.x(), point.y(), point.z()).unwrap();
         }
     }
+
+    #[test]
+    fn hemisphere_pdfs_are_normalized() {
+        // The mean of 1/pdf estimates the solid angle of the hemisphere, 2 pi.
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let n = 100_000;
+        let (mut uniform_sum, mut cosine_sum) = (0.0, 0.0);
+        for _ in 0..n {
+            let uniform = uniform_on_2sphere_hemisphere(&mut rng);
+            assert!((uniform.pdf - 0.5 * Float::get_1_pi()).abs() < 1e-6);
+            uniform_sum += 1.0 / uniform.pdf;
+
+            let cosine = cosine_on_2sphere_hemisphere(&mut rng);
+            assert!((cosine.pdf - cosine.point.z() * Float::get_1_pi()).abs() < 1e-5);
+            if cosine.pdf > 0.0 {
+                cosine_sum += 1.0 / cosine.pdf;
+            }
+        }
+
+        let two_pi = 2.0 * Float::get_pi();
+        assert!((uniform_sum / n as Float / two_pi - 1.0).abs() < 1e-3);
+        assert!((cosine_sum / n as Float / two_pi - 1.0).abs() < 0.05, "{}", cosine_sum / n as Float);
+    }
 }
 
 // E==== TESTS }}}1
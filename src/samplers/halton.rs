@@ -0,0 +1,67 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::{math::float::Float, image::Pixel};
+use super::{traits::SamplerLike, scrambling};
+
+// E==== IMPORTS }}}1
+
+/// The bases of the dimensions of the Halton sequence. Further dimensions fall back 
+/// to independent random numbers, since high bases are poorly distributed anyway 
+/// (and those dimensions, deep into a path, matter little).
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 
+    59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131, 
+    137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193, 197, 199, 211, 223, 
+    227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307, 311,
+];
+
+/// The Halton sequence, whose $d$th dimension is the radical inverse of the sample 
+/// index in the $d$th prime. Every pixel uses the same points, Owen scrambled 
+/// differently, so neighbouring pixels are not correlated.
+pub struct HaltonSampler {
+    seed: u32,
+    pixel: Pixel,
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            pixel: Pixel { x: 0, y: 0 },
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl SamplerLike for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: &Pixel, sample_index: u32) {
+        self.pixel = pixel.clone();
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> Float {
+        let hash = scrambling::hash(self.seed, &self.pixel, self.dimension, &[]);
+        let x = match PRIMES.get(self.dimension as usize) {
+            Some(base) => scrambling::owen_scrambled_radical_inverse(*base, self.sample_index as u64, hash as u32),
+            None => {
+                let hash = scrambling::hash(self.seed, &self.pixel, self.dimension, &[self.sample_index as u64]);
+                scrambling::hash_to_float(hash)
+            },
+        };
+        self.dimension += 1;
+
+        x
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    fn clone_sampler(&self) -> Box<dyn SamplerLike> {
+        Box::new(Self::new(self.seed))
+    }
+}
@@ -0,0 +1,41 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::{math::float::Float, image::Pixel, rng::RandomNumberGenerator};
+use super::{traits::SamplerLike, scrambling};
+
+// E==== IMPORTS }}}1
+
+/// Independent uniform random numbers: the simplest sampler, and the baseline the 
+/// others improve on.
+pub struct IndependentSampler {
+    seed: u32,
+    rng: RandomNumberGenerator,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            rng: RandomNumberGenerator::from_seed(seed),
+        }
+    }
+}
+
+impl SamplerLike for IndependentSampler {
+    fn start_pixel_sample(&mut self, pixel: &Pixel, sample_index: u32) {
+        let stream = scrambling::hash(self.seed, pixel, 0, &[sample_index as u64]);
+        self.rng = RandomNumberGenerator::new_stream(self.seed, stream);
+    }
+
+    fn get_1d(&mut self) -> Float {
+        self.rng.next_float()
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        (self.rng.next_float(), self.rng.next_float())
+    }
+
+    fn clone_sampler(&self) -> Box<dyn SamplerLike> {
+        Box::new(Self::new(self.seed))
+    }
+}
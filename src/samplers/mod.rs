@@ -0,0 +1,117 @@
+//! Samplers provide the numbers in $[0,1)$ that drive every random decision of a 
+//! render: where in the pixel a sample is, where on the lens, which way light 
+//! scatters at each bounce, and so on. Each such decision is a *dimension* of the 
+//! pixel sample, and the numbers of one dimension over a pixel's samples can be 
+//! spread out more evenly than independent random numbers would be, which makes 
+//! the pixel converge faster.
+//!
+//! The numbers only depend on the seed, the pixel, the index of the sample within 
+//! the pixel and the dimension, so renders are reproducible no matter how the work 
+//! is split between threads.
+
+pub mod traits;
+pub mod independent;
+pub mod stratified;
+pub mod halton;
+pub mod sobol;
+
+mod scrambling;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::{scene_parsing, utility::math::float::Float};
+
+    /// A matte floor under a sky which, from the floor's point of view, brightens 
+    /// linearly with height. The floor's radiance is $\rho/\pi$ times the integral of 
+    /// the sky times the cosine, i.e. $\rho (h + (z - h) \cdot 2/3)$ for the albedo 
+    /// $\rho$, horizon color $h$ and zenith color $z$, since the average cosine 
+    /// under cosine weighting is $2/3$.
+    fn floor_under_sky_json(sampler_kind: &str, num_samples: u32) -> serde_json::Value {
+        let json = format!(r#"
+            {{
+                "camera": {{
+                    "resolution": [16, 16],
+                    "focal distance": 1,
+                    "vertical fov": 60,
+                    "aperture radius": 0,
+                    "transform": {{
+                        "viewer": {{
+                            "look_from": [0, 1, 0],
+                            "look_at": [0, 0, 0],
+                            "up_direction": [0, 0, -1]
+                        }}
+                    }}
+                }},
+                "integrator": {{
+                    "kind": "path tracer",
+                    "number of samples": {},
+                    "ray recursion limit": 2,
+                    "number of threads": 1
+                }},
+                "sampler": {{ "kind": "{}" }},
+                "environment": {{ 
+                    "kind": "sky", 
+                    "zenith color": [0, 0, 0], 
+                    "horizon color": [1, 1, 1] 
+                }},
+                "textures": [
+                    {{ "name": "grey", "kind": "constant", "rgb color": [0.5, 0.5, 0.5] }}
+                ],
+                "materials": [
+                    {{ "name": "lambertian", "kind": "lambertian" }}
+                ],
+                "objects": [
+                    {{
+                        "shape": {{
+                            "kind": "quad",
+                            "width": 100,
+                            "height": 100,
+                            "transform": {{
+                                "simple sequence": [
+                                    {{ "rotation": {{ "axis": [1, 0, 0], "angle": 90 }} }},
+                                    {{ "translation": [-50, 0, -50] }}
+                                ]
+                            }}
+                        }},
+                        "texture": "grey",
+                        "material": "lambertian"
+                    }}
+                ]
+            }}"#, num_samples, sampler_kind);
+
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// The root mean square error of the pixels.
+    fn render_error(sampler_kind: &str, num_samples: u32) -> Float {
+        let expected = 0.5 * (1.0 / 3.0);
+
+        let json = floor_under_sky_json(sampler_kind, num_samples);
+        let mut scene = scene_parsing::parse_json(&json, Path::new("")).unwrap();
+        let image = scene.ray_trace();
+
+        let resolution = scene.new_image_buffer().resolution().clone();
+        let num_pixels = (resolution.width * resolution.height) as Float;
+        let squared_error: Float = resolution
+            .into_iter()
+            .map(|pixel| (image.get_pixel_color(&pixel).y() - expected).powi(2))
+            .sum();
+
+        (squared_error / num_pixels).sqrt()
+    }
+
+    #[test]
+    fn samplers_converge_faster_than_random_numbers() {
+        let independent = render_error("independent", 16);
+        for kind in ["stratified", "halton", "sobol"] {
+            let error = render_error(kind, 16);
+            assert!(error < 0.5 * independent, "{} sampler error {} vs {}", kind, error, independent);
+        }
+
+        // All of them converge to the right answer.
+        for kind in ["independent", "stratified", "halton", "sobol"] {
+            assert!(render_error(kind, 256) < 0.4 * independent);
+        }
+    }
+}
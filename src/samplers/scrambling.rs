@@ -0,0 +1,156 @@
+//! Hashing and randomization shared by the samplers.
+
+use crate::utility::{math::float::Float, image::Pixel};
+
+/// The largest `Float` less than one.
+pub const ONE_MINUS_EPSILON: Float = 1.0 - Float::EPSILON / 2.0;
+
+/// Thoroughly mixes the bits of `v` (the finalizer of MurmurHash3, with the 
+/// constants of "Better Bit Mixing" by David Stafford).
+pub fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+    v
+}
+
+/// A hash of the sample's seed, pixel and dimension, plus any further `values`.
+pub fn hash(seed: u32, pixel: &Pixel, dimension: u32, values: &[u64]) -> u64 {
+    [seed as u64, pixel.x as u64, pixel.y as u64, dimension as u64]
+        .iter()
+        .chain(values.iter())
+        .fold(0, |hash, value| mix_bits(hash ^ value.wrapping_mul(0x9e3779b97f4a7c15)))
+}
+
+/// A number in $[0,1)$ from (the high bits of) a hash.
+pub fn hash_to_float(hash: u64) -> Float {
+    bits_to_float((hash >> 32) as u32)
+}
+
+/// Interprets `bits` as the binary digits after the point.
+pub fn bits_to_float(bits: u32) -> Float {
+    Float::min(((bits as f64) / 4294967296.0) as Float, ONE_MINUS_EPSILON)
+}
+
+/// The `i`th element of a random permutation of $0, \dots, n-1$ chosen by `seed`, 
+/// without storing the permutation (Kensler, "Correlated Multi-Jittered Sampling").
+pub fn permutation_element(mut i: u32, n: u32, seed: u32) -> u32 {
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < n {
+            break;
+        }
+    }
+
+    (i.wrapping_add(seed)) % n
+}
+
+/// Shuffles the indices of the samples, in blocks of `n`: indices below `n` are 
+/// permuted among themselves, and so on. Different `seed`s give (practically) 
+/// unrelated shuffles.
+pub fn shuffle_index(index: u32, n: u32, seed: u64) -> u32 {
+    let block = index / n;
+    let block_seed = mix_bits(seed ^ (block as u64)) as u32;
+    block * n + permutation_element(index % n, n, block_seed)
+}
+
+/// Owen scrambling of the binary digits of `v`: each digit is flipped or not 
+/// depending on the digits before it, which randomizes a low-discrepancy sequence 
+/// while keeping it evenly spread. This is the hash-based approximation of Burley, 
+/// "Practical Hash-based Owen Scrambling".
+pub fn fast_owen_scramble(mut v: u32, seed: u32) -> u32 {
+    v = v.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x05526c56);
+    v ^= v.wrapping_mul(0x53a22864);
+    v.reverse_bits()
+}
+
+/// The radical inverse of `index` in `base` (its digits mirrored about the point), 
+/// with each digit randomly permuted depending on the digits before it, i.e. Owen 
+/// scrambling in an arbitrary base.
+pub fn owen_scrambled_radical_inverse(base: u32, mut index: u64, seed: u32) -> Float {
+    let base_u64 = base as u64;
+    let limit = u64::MAX / base_u64 - base_u64;
+    let inverse_base = 1.0 / (base as Float);
+    let mut inverse_base_power: Float = 1.0;
+    let mut reversed_digits: u64 = 0;
+
+    // Digits beyond the precision of a `Float` make no difference.
+    while 1.0 - inverse_base_power < 1.0 && reversed_digits < limit {
+        let next = index / base_u64;
+        let digit = (index - next * base_u64) as u32;
+        let digit_seed = mix_bits((seed as u64) ^ reversed_digits) as u32;
+        let digit = permutation_element(digit, base, digit_seed);
+
+        reversed_digits = reversed_digits * base_u64 + (digit as u64);
+        inverse_base_power *= inverse_base;
+        index = next;
+    }
+
+    Float::min(inverse_base_power * (reversed_digits as Float), ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutations_are_permutations() {
+        for n in [1, 2, 5, 16, 100] {
+            for seed in [0, 1, 0xdeadbeef] {
+                let mut seen = vec![false; n as usize];
+                for i in 0..n {
+                    seen[permutation_element(i, n, seed) as usize] = true;
+                }
+                assert!(seen.iter().all(|s| *s));
+            }
+        }
+    }
+
+    #[test]
+    fn scrambled_points_stay_stratified() {
+        // The first 2^k points of the van der Corput sequence fall one in each of 
+        // the 2^k intervals, and scrambling must keep it that way.
+        for (base, n) in [(2, 64), (3, 27)] {
+            let mut seen = vec![false; n];
+            for i in 0..n {
+                let x = match base {
+                    2 => bits_to_float(fast_owen_scramble((i as u32).reverse_bits(), 12345)),
+                    _ => owen_scrambled_radical_inverse(base, i as u64, 12345),
+                };
+                seen[(x * (n as Float)) as usize] = true;
+            }
+            assert!(seen.iter().all(|s| *s));
+        }
+    }
+}
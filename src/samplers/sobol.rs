@@ -0,0 +1,113 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::{math::float::Float, image::Pixel};
+use super::{traits::SamplerLike, scrambling};
+
+// E==== IMPORTS }}}1
+
+/// The Sobol sequence, "padded": every 2D sample uses the first two dimensions of 
+/// the sequence, which are especially well distributed over the square, Owen 
+/// scrambled and with the sample indices shuffled differently for each dimension 
+/// so that the dimensions are not correlated. 1D samples use the first dimension 
+/// alone. This works best with a power of two samples per pixel.
+pub struct SobolSampler {
+    seed: u32,
+    samples_per_pixel: u32,
+    pixel: Pixel,
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u32, samples_per_pixel: u32) -> Self {
+        Self {
+            seed,
+            samples_per_pixel: samples_per_pixel.max(1),
+            pixel: Pixel { x: 0, y: 0 },
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// The shuffled sample index and the hash to scramble with, for the current 
+    /// dimension.
+    fn next_dimension(&mut self) -> (u32, u64) {
+        let hash = scrambling::hash(self.seed, &self.pixel, self.dimension, &[]);
+        let index = scrambling::shuffle_index(self.sample_index, self.samples_per_pixel, hash);
+        self.dimension += 1;
+
+        (index, hash)
+    }
+}
+
+/// The second dimension of the Sobol sequence, as binary digits after the point. Its 
+/// direction numbers are generated by the primitive polynomial $x + 1$, so each is 
+/// the previous one XORed with itself shifted by one digit.
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut direction = 1 << 31;
+    let mut to_return = 0;
+    while index != 0 {
+        if index & 1 == 1 {
+            to_return ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    to_return
+}
+
+impl SamplerLike for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: &Pixel, sample_index: u32) {
+        self.pixel = pixel.clone();
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> Float {
+        let (index, hash) = self.next_dimension();
+        // The first dimension is the van der Corput sequence.
+        scrambling::bits_to_float(scrambling::fast_owen_scramble(index.reverse_bits(), hash as u32))
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        let (index, hash) = self.next_dimension();
+        let x = scrambling::fast_owen_scramble(index.reverse_bits(), hash as u32);
+        let y = scrambling::fast_owen_scramble(sobol_second_dimension(index), (hash >> 32) as u32);
+
+        (scrambling::bits_to_float(x), scrambling::bits_to_float(y))
+    }
+
+    fn samples_per_pixel(&self) -> Option<u32> {
+        Some(self.samples_per_pixel)
+    }
+
+    fn clone_sampler(&self) -> Box<dyn SamplerLike> {
+        Box::new(Self::new(self.seed, self.samples_per_pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_points_are_stratified() {
+        // Any 2^k points of the first two Sobol dimensions (starting at a multiple 
+        // of 2^k) put one point in each cell of any 2^a by 2^b grid with a + b = k.
+        let points: Vec<(u32, u32)> = (0..16u32)
+            .map(|i| (i.reverse_bits(), sobol_second_dimension(i)))
+            .collect();
+
+        for columns_log2 in 0..=4 {
+            let rows_log2 = 4 - columns_log2;
+            let mut seen = [false; 16];
+            for (x, y) in points.iter() {
+                let column = if columns_log2 == 0 { 0 } else { x >> (32 - columns_log2) };
+                let row = if rows_log2 == 0 { 0 } else { y >> (32 - rows_log2) };
+                seen[(row * (1 << columns_log2) + column) as usize] = true;
+            }
+            assert!(seen.iter().all(|s| *s));
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// S==== IMPORTS {{{1
+
+use crate::utility::{math::float::Float, image::Pixel};
+use super::{traits::SamplerLike, scrambling};
+
+// E==== IMPORTS }}}1
+
+/// Jittered sampling: each dimension is divided into as many strata (intervals, or 
+/// cells of a grid in 2D) as there are samples per pixel, every stratum gets one 
+/// sample at a random point within it, and the strata are visited in a random 
+/// order that differs between dimensions. Beyond `samples_per_pixel` samples, the 
+/// strata are visited again.
+pub struct StratifiedSampler {
+    seed: u32,
+    samples_per_pixel: u32,
+    /// The grid of 2D strata is `grid.0` by `grid.1`, with as many cells as samples 
+    /// per pixel.
+    grid: (u32, u32),
+    pixel: Pixel,
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u32, samples_per_pixel: u32) -> Self {
+        let samples_per_pixel = samples_per_pixel.max(1);
+
+        // The most square grid with exactly that many cells.
+        let rows = (1..=samples_per_pixel)
+            .take_while(|rows| rows * rows <= samples_per_pixel)
+            .filter(|rows| samples_per_pixel.is_multiple_of(*rows))
+            .last()
+            .unwrap_or(1);
+
+        Self {
+            seed,
+            samples_per_pixel,
+            grid: (samples_per_pixel / rows, rows),
+            pixel: Pixel { x: 0, y: 0 },
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// The stratum of the current sample in the current dimension, and two random 
+    /// numbers to place the sample within it.
+    fn next_stratum(&mut self) -> (u32, (Float, Float)) {
+        let hash = scrambling::hash(self.seed, &self.pixel, self.dimension, &[]);
+        let stratum = scrambling::shuffle_index(self.sample_index, self.samples_per_pixel, hash) 
+            % self.samples_per_pixel;
+
+        let jitter_hash = scrambling::hash(self.seed, &self.pixel, self.dimension, &[self.sample_index as u64]);
+        let jitter = (
+            scrambling::hash_to_float(jitter_hash), 
+            scrambling::hash_to_float(scrambling::mix_bits(jitter_hash))
+        );
+        self.dimension += 1;
+
+        (stratum, jitter)
+    }
+}
+
+impl SamplerLike for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: &Pixel, sample_index: u32) {
+        self.pixel = pixel.clone();
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> Float {
+        let (stratum, (jitter, _)) = self.next_stratum();
+        let x = ((stratum as Float) + jitter) / (self.samples_per_pixel as Float);
+        Float::min(x, scrambling::ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        let (stratum, jitter) = self.next_stratum();
+        let (columns, rows) = self.grid;
+        let x = (((stratum % columns) as Float) + jitter.0) / (columns as Float);
+        let y = (((stratum / columns) as Float) + jitter.1) / (rows as Float);
+        (Float::min(x, scrambling::ONE_MINUS_EPSILON), Float::min(y, scrambling::ONE_MINUS_EPSILON))
+    }
+
+    fn samples_per_pixel(&self) -> Option<u32> {
+        Some(self.samples_per_pixel)
+    }
+
+    fn clone_sampler(&self) -> Box<dyn SamplerLike> {
+        Box::new(Self::new(self.seed, self.samples_per_pixel))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::utility::{math::float::Float, image::Pixel};
+
+/// Each rendering thread uses its own sampler, made with `clone_sampler()`.
+pub trait SamplerLike: Send + Sync {
+    /// Starts the `sample_index`th sample of `pixel`. The numbers that follow are 
+    /// determined by these (and the seed) alone, so consumers must ask for them in 
+    /// the same order for every sample: the position in the pixel first, then the 
+    /// position on the lens, and then whatever the integrator needs.
+    fn start_pixel_sample(&mut self, pixel: &Pixel, sample_index: u32);
+
+    /// The next dimension of the current sample.
+    fn get_1d(&mut self) -> Float;
+
+    /// The next two dimensions of the current sample, which are spread out evenly 
+    /// over the square (rather than just each on its own).
+    fn get_2d(&mut self) -> (Float, Float);
+
+    /// The number of samples per pixel the sampler's points are laid out for, if 
+    /// they depend on it. A render can only be resumed by a sampler with the same 
+    /// layout.
+    fn samples_per_pixel(&self) -> Option<u32> {
+        None
+    }
+
+    /// A new sampler of the same kind and seed.
+    fn clone_sampler(&self) -> Box<dyn SamplerLike>;
+}
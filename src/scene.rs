@@ -1,17 +1,78 @@
 //! This encapsulates all the geometry of the scene. 
 
 
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::atomic::{AtomicBool, AtomicUsize, Ordering}, thread, time::{Duration, Instant}};
+use tracing::info;
 
-use crate::{camera::Camera, objects::{object_group::ObjectGroup, materials, textures, self}, integrators::{traits::IntegratorLike, ambient_occlusion::AmbientOcclusionIntegrator}, utility::{image::{Resolution, Image, ImageBuffer}, rng::RandomNumberGenerator, math::float::Float}};
+use crate::{aovs::{AovKind, Aovs, PixelAovs}, camera::Camera, denoisers::traits::DenoiserLike, objects::object_group::ObjectGroup, environments::traits::EnvironmentLike, filters::traits::FilterLike, integrators::traits::IntegratorLike, samplers::traits::SamplerLike, spectral::wavelengths::SampledWavelengths, utility::{image::{Image, ImageBuffer, Pixel, Tile, RenderedTile, PixelSample}, math::{float::{self, Float}, statistics::RunningStatistics}, tone_mapping::DisplayTransform}};
+
+/// The side length, in pixels, of the square tiles the image is split into when 
+/// rendering.
+const TILE_SIZE: u32 = 32;
+
+/// The progress of a render is logged at most this often.
+const PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// When to hand the image rendered so far to the caller, e.g. to write it to disk 
+/// during a long render. A checkpoint is due once either limit is reached since the 
+/// previous one; with neither, there are no checkpoints.
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointSchedule {
+    pub every_samples: Option<u32>,
+    pub every_seconds: Option<Float>,
+}
+
+impl CheckpointSchedule {
+    fn is_due(&self, samples_since_last: u32, time_since_last: Duration) -> bool {
+        let enough_samples = self.every_samples.is_some_and(|n| samples_since_last >= n);
+        let enough_time = self.every_seconds
+            .is_some_and(|seconds| time_since_last.as_secs_f64() >= float::to_f64(seconds));
+
+        enough_samples || enough_time
+    }
+}
+
+/// Spends the samples where they are needed: a pixel stops being sampled once the 
+/// relative error of its mean luminance falls below `relative_error`, leaving the 
+/// samples to the noisier pixels. The render ends once the pixels have the scene's 
+/// number of samples on average, or none needs more.
+#[derive(Clone, Debug)]
+pub struct AdaptiveSampling {
+    pub relative_error: Float,
+    /// Every pixel gets at least this many samples, so that its variance estimate 
+    /// can be trusted.
+    pub min_samples: u32,
+    /// No pixel gets more than this many samples.
+    pub max_samples: u32,
+    /// Where to write an image of how many samples each pixel got, for debugging.
+    pub sample_count_map: Option<String>,
+}
+
+impl AdaptiveSampling {
+    fn needs_more_samples(&self, statistics: &RunningStatistics) -> bool {
+        let count = statistics.count();
+        count < self.min_samples 
+            || (count < self.max_samples && statistics.relative_error() > self.relative_error)
+    }
+}
 
 pub struct Scene {
     integrator: Box<dyn IntegratorLike>,
     camera: Camera,
     objects: ObjectGroup, 
-    rng: RandomNumberGenerator,
+    environment: Box<dyn EnvironmentLike>,
+    filter: Box<dyn FilterLike>,
+    display: DisplayTransform,
+    sampler: Box<dyn SamplerLike>,
+    seed: u32,
     num_samples: u32,
-    recursive_depth_limit: u32,
+    num_threads: usize,
+    spectral: bool,
+    output_filename: Option<String>,
+    checkpoints: CheckpointSchedule,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    aov_outputs: Vec<(AovKind, String)>,
+    denoiser: Option<Box<dyn DenoiserLike>>,
 }
 
 impl Debug for Scene {
@@ -24,9 +85,30 @@ pub struct SceneInfo {
     pub integrator: Box<dyn IntegratorLike>,
     pub camera: Camera,
     pub objects: ObjectGroup, 
-    pub rng: RandomNumberGenerator,
+    /// What rays that escape the objects see.
+    pub environment: Box<dyn EnvironmentLike>,
+    /// How the samples are combined into pixels.
+    pub filter: Box<dyn FilterLike>,
+    /// How the image is shown in low dynamic range formats.
+    pub display: DisplayTransform,
+    /// Where the random numbers of the render come from.
+    pub sampler: Box<dyn SamplerLike>,
+    /// All randomness in the render is derived from this.
+    pub seed: u32,
     pub num_samples: u32,
-    pub recursive_depth_limit: u32,
+    pub num_threads: usize,
+    /// Whether light is traced at sampled wavelengths rather than in RGB (see 
+    /// `spectral`).
+    pub spectral: bool,
+    /// Where the render should be written, if the scene file specifies it.
+    pub output_filename: Option<String>,
+    pub checkpoints: CheckpointSchedule,
+    /// Without it, every pixel gets the same number of samples.
+    pub adaptive_sampling: Option<AdaptiveSampling>,
+    /// The AOVs to write and where to write them.
+    pub aov_outputs: Vec<(AovKind, String)>,
+    /// Applied to the image before it is written, if given.
+    pub denoiser: Option<Box<dyn DenoiserLike>>,
 }
 
 impl Scene {
@@ -35,44 +117,301 @@ impl Scene {
             integrator: info.integrator,
             camera: info.camera,
             objects: info.objects,
-            rng: info.rng,
+            environment: info.environment,
+            filter: info.filter,
+            display: info.display,
+            sampler: info.sampler,
+            seed: info.seed,
             num_samples: info.num_samples,
-            recursive_depth_limit: info.recursive_depth_limit,
+            num_threads: usize::max(info.num_threads, 1),
+            spectral: info.spectral,
+            output_filename: info.output_filename,
+            checkpoints: info.checkpoints,
+            adaptive_sampling: info.adaptive_sampling,
+            aov_outputs: info.aov_outputs,
+            denoiser: info.denoiser,
         }
     }
 
+    pub fn output_filename(&self) -> Option<&str> {
+        self.output_filename.as_deref()
+    }
+
+    pub fn adaptive_sampling(&self) -> Option<&AdaptiveSampling> {
+        self.adaptive_sampling.as_ref()
+    }
+
+    pub fn aov_outputs(&self) -> &[(AovKind, String)] {
+        &self.aov_outputs
+    }
+
+    pub fn denoiser(&self) -> Option<&dyn DenoiserLike> {
+        self.denoiser.as_deref()
+    }
+
+    pub fn display(&self) -> &DisplayTransform {
+        &self.display
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// See `SamplerLike::samples_per_pixel()`.
+    pub fn sampler_samples_per_pixel(&self) -> Option<u32> {
+        self.sampler.samples_per_pixel()
+    }
+
+    /// Overrides the number of samples per pixel given in the scene file.
+    pub fn set_num_samples(&mut self, num_samples: u32) {
+        self.num_samples = num_samples;
+    }
+
+    /// An empty buffer of the scene's resolution, to start rendering into.
+    pub fn new_image_buffer(&self) -> ImageBuffer {
+        ImageBuffer::new(self.camera.get_resolution())
+    }
+
     pub fn ray_trace(&mut self) -> Image {
-        let mut image_buffer = ImageBuffer::new(self.camera.get_resolution());
+        self.ray_trace_progressively(self.new_image_buffer(), &AtomicBool::new(false), |_| {})
+            .average_samples()
+    }
+
+    /// Computes the AOVs of the image, splitting its rows between `num_threads` 
+    /// threads. Like the render, the result does not depend on the number of threads.
+    pub fn render_aovs(&self) -> Aovs {
+        let resolution = self.camera.get_resolution();
+        let rows: Vec<u32> = (0..resolution.height).collect();
+        let rows_per_thread = usize::max(rows.len().div_ceil(self.num_threads), 1);
+
+        let pixels: Vec<(Pixel, PixelAovs)> = thread::scope(|scope| {
+            let workers: Vec<_> = rows
+                .chunks(rows_per_thread)
+                .map(|rows| scope.spawn(move || {
+                    let mut sampler = self.sampler.clone_sampler();
+                    rows.iter()
+                        .flat_map(|y| (0..resolution.width).map(move |x| Pixel { x, y: *y }))
+                        .map(|pixel| {
+                            let values = PixelAovs::new(&self.camera, &self.objects, &pixel, sampler.as_mut());
+                            (pixel, values)
+                        })
+                        .collect::<Vec<_>>()
+                }))
+                .collect();
 
-        while image_buffer.num_samples() < self.num_samples {
-            image_buffer.add_sample(self.ray_trace_single_sample());
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("an AOV thread panicked"))
+                .collect()
+        });
+
+        let mut aovs = Aovs::new(resolution);
+        for (pixel, values) in pixels {
+            aovs.set_pixel(&pixel, values);
         }
-        
-        image_buffer.average_samples()
+        aovs
     }
 
-    fn ray_trace_single_sample(&mut self) -> Image {
-        let mut to_return = Image::new(self.camera.get_resolution());
+    /// Adds pass after pass of samples to `image_buffer`, logging the progress, until 
+    /// the pixels have `num_samples` samples (on average, with adaptive sampling). 
+    /// Whenever a checkpoint is due, `on_checkpoint` is given the samples so far.
+    ///
+    /// The buffer may already hold samples, e.g. from a render that was saved and is 
+    /// now resumed; the render continues exactly as if it had never stopped.
+    ///
+    /// Setting `stop` (e.g. from a signal handler) ends the render early: the pass 
+    /// being computed is abandoned and the buffer of completed passes is returned.
+    pub fn ray_trace_progressively(
+        &self, 
+        mut image_buffer: ImageBuffer, 
+        stop: &AtomicBool, 
+        mut on_checkpoint: impl FnMut(&ImageBuffer)
+    ) -> ImageBuffer {
+        assert!(
+            image_buffer.resolution().width == self.camera.get_resolution().width
+                && image_buffer.resolution().height == self.camera.get_resolution().height,
+            "the image buffer must have the scene's resolution"
+        );
+
+        let start = Instant::now();
+        let mut last_log = start;
+        let first_samples_per_pixel = image_buffer.samples_per_pixel();
+        let mut last_checkpoint = (image_buffer.num_passes(), start);
+        let mut pixels_to_sample = self.pixels_to_sample(&image_buffer);
 
-        for pixel in self.camera.get_resolution().into_iter() {
-            let camera_ray = {
-                let px = (pixel.x as Float) + 0.5;
-                let py = (pixel.y as Float) + 0.5;
-                self.camera.generate_ray(px, py, &mut self.rng)
+        while !self.is_finished(&image_buffer, pixels_to_sample.as_deref()) {
+            let tiles = match self.ray_trace_single_sample(&image_buffer, pixels_to_sample.as_deref(), stop) {
+                Some(tiles) => tiles,
+                None => {
+                    info!(
+                        "stopped early after {:.1} of {} samples per pixel", 
+                        image_buffer.samples_per_pixel(), 
+                        self.num_samples
+                    );
+                    break;
+                }
             };
-            
-            let pixel_color = self.integrator.spectrum_from_ray(&self.objects, &camera_ray, &mut self.rng);
-            to_return.set_pixel_color(&pixel, pixel_color);
+            image_buffer.add_sample(&tiles, self.filter.as_ref());
+            pixels_to_sample = self.pixels_to_sample(&image_buffer);
+
+            let now = Instant::now();
+            let is_done = self.is_finished(&image_buffer, pixels_to_sample.as_deref());
+            if now - last_log >= PROGRESS_LOG_INTERVAL || is_done {
+                let samples_done = image_buffer.samples_per_pixel();
+                self.log_progress(samples_done, samples_done - first_samples_per_pixel, now - start);
+                last_log = now;
+            }
+
+            let (checkpoint_pass, checkpoint_time) = last_checkpoint;
+            let is_checkpoint_due = self.checkpoints.is_due(
+                image_buffer.num_passes() - checkpoint_pass, 
+                now - checkpoint_time
+            );
+            if is_checkpoint_due && !is_done {
+                on_checkpoint(&image_buffer);
+                last_checkpoint = (image_buffer.num_passes(), Instant::now());
+            }
+        }
+        
+        image_buffer
+    }
+
+    /// Which pixels the next pass samples, indexed by `Resolution::pixel_index`, or 
+    /// `None` if it samples all of them.
+    fn pixels_to_sample(&self, image_buffer: &ImageBuffer) -> Option<Vec<bool>> {
+        self.adaptive_sampling.as_ref().map(|adaptive_sampling| {
+            image_buffer.statistics()
+                .iter()
+                .map(|statistics| adaptive_sampling.needs_more_samples(statistics))
+                .collect()
+        })
+    }
+
+    fn is_finished(&self, image_buffer: &ImageBuffer, pixels_to_sample: Option<&[bool]>) -> bool {
+        match pixels_to_sample {
+            None => image_buffer.num_passes() >= self.num_samples,
+            Some(pixels_to_sample) => {
+                image_buffer.samples_per_pixel() >= (self.num_samples as Float)
+                    || !pixels_to_sample.contains(&true)
+            },
         }
+    }
+
+    /// `samples_this_run` of the `samples_done` (per pixel) were rendered in the last 
+    /// `elapsed`.
+    fn log_progress(&self, samples_done: Float, samples_this_run: Float, elapsed: Duration) {
+        let samples_per_second = float::to_f64(samples_this_run) / elapsed.as_secs_f64();
+        let remaining = Duration::from_secs_f64(
+            f64::max(f64::from(self.num_samples) - float::to_f64(samples_done), 0.0) / samples_per_second
+        );
 
-        to_return
+        info!(
+            "{:.1}/{} samples per pixel, {:.2} samples/s, elapsed {}, remaining {}",
+            samples_done,
+            self.num_samples,
+            samples_per_second,
+            format_duration(elapsed),
+            format_duration(remaining)
+        );
     }
+
+    /// Computes one sample for every pixel (or, if given, every pixel in 
+    /// `pixels_to_sample`), splitting the image into tiles which `num_threads` threads 
+    /// take turns rendering. Returns `None` if `stop` is set before every tile is done.
+    ///
+    /// Each thread has its own copy of the sampler, whose numbers only depend on the 
+    /// pixel and the index of its sample (the number of samples `image_buffer` 
+    /// already has for it). Which thread renders a tile does not matter, so the 
+    /// result is reproducible for any number of threads.
+    fn ray_trace_single_sample(
+        &self, 
+        image_buffer: &ImageBuffer, 
+        pixels_to_sample: Option<&[bool]>, 
+        stop: &AtomicBool
+    ) -> Option<Vec<RenderedTile>> {
+        let tiles = self.camera.get_resolution().tiles(TILE_SIZE);
+        let next_tile = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.num_threads)
+                .map(|_| scope.spawn(|| {
+                    let mut sampler = self.sampler.clone_sampler();
+                    let mut rendered = Vec::new();
+                    loop {
+                        let tile_index = next_tile.fetch_add(1, Ordering::Relaxed);
+                        if tile_index >= tiles.len() || stop.load(Ordering::Relaxed) { break; }
+
+                        rendered.push(self.ray_trace_tile(
+                            &tiles[tile_index], 
+                            image_buffer, 
+                            pixels_to_sample, 
+                            sampler.as_mut()
+                        ));
+                    }
+                    rendered
+                }))
+                .collect();
+
+            let rendered: Vec<RenderedTile> = workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("a render thread panicked"))
+                .collect();
+            (rendered.len() == tiles.len()).then_some(rendered)
+        })
+    }
+
+    fn ray_trace_tile(
+        &self, 
+        tile: &Tile, 
+        image_buffer: &ImageBuffer, 
+        pixels_to_sample: Option<&[bool]>, 
+        sampler: &mut dyn SamplerLike
+    ) -> RenderedTile {
+        let resolution = self.camera.get_resolution();
+        let samples = tile.pixels()
+            .filter(|pixel| pixels_to_sample.is_none_or(|p| p[resolution.pixel_index(pixel)]))
+            .map(|pixel| {
+                sampler.start_pixel_sample(&pixel, image_buffer.num_pixel_samples(&pixel));
+
+                // A random point in the pixel, so that the samples together cover it.
+                let offset = sampler.get_2d();
+                let mut camera_ray = {
+                    let px = (pixel.x as Float) + offset.0;
+                    let py = (pixel.y as Float) + offset.1;
+                    self.camera.generate_ray(px, py, sampler)
+                };
+                if self.spectral {
+                    camera_ray.wavelengths = Some(SampledWavelengths::sample_visible(sampler.get_1d()));
+                }
+
+                let spectrum = self.integrator.spectrum_from_ray(&self.objects, self.environment.as_ref(), &camera_ray, sampler);
+                let color = match &camera_ray.wavelengths {
+                    Some(wavelengths) => wavelengths.to_rgb(&spectrum),
+                    None => spectrum,
+                };
+                PixelSample { pixel, offset, color }
+            })
+            .collect();
+
+        RenderedTile {
+            tile: tile.clone(),
+            samples,
+        }
+    }
+}
+
+/// As hours, minutes and seconds, e.g. "1:02:03".
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{path::Path, sync::atomic::AtomicBool};
 
-    use crate::{camera::{self, Camera, CameraInfo}, objects::shapes::{transform::Transform, quad::Quad}, utility::{math::{vector::Vec3, angle::{AngleUnits, Angle}}, image::Resolution}};
+    use super::{AdaptiveSampling, CheckpointSchedule};
+    use crate::{scene_parsing, camera::{self, Camera, CameraInfo}, objects::shapes::{transform::Transform, quad::Quad}, utility::{math::{vector::Vec3, angle::{AngleUnits, Angle}}, image::{Pixel, Resolution}}};
 
     #[test]
     fn scene_1() {
@@ -88,6 +427,266 @@ mod tests {
         let camera = Camera::new(camera_info);
 
     }
+
+    /// A small scene (a sphere on a floor, lit by a quad light) for tests that need to 
+    /// actually render something.
+    pub(crate) fn small_scene_json(num_threads: usize) -> serde_json::Value {
+        let json = format!(r#"
+            {{
+                "camera": {{
+                    "resolution": [40, 30],
+                    "focal distance": 1,
+                    "vertical fov": 60,
+                    "aperture radius": 0,
+                    "transform": {{
+                        "viewer": {{
+                            "look_from": [0, 0.5, 3],
+                            "look_at": [0, 0, 0],
+                            "up_direction": [0, 1, 0]
+                        }}
+                    }}
+                }},
+                "integrator": {{
+                    "kind": "path tracer",
+                    "number of samples": 3,
+                    "ray recursion limit": 4,
+                    "number of threads": {}
+                }},
+                "textures": [
+                    {{ "name": "grey", "kind": "constant", "rgb color": [0.5, 0.5, 0.5] }}
+                ],
+                "materials": [
+                    {{ "name": "lambertian", "kind": "lambertian" }},
+                    {{ "name": "light", "kind": "diffuse light", "radiance": [4, 4, 4] }}
+                ],
+                "objects": [
+                    {{
+                        "shape": {{ "kind": "sphere", "center": [0, 0, 0], "radius": 1 }},
+                        "texture": "grey",
+                        "material": "lambertian"
+                    }},
+                    {{
+                        "shape": {{ "kind": "sphere", "center": [0, -101, 0], "radius": 100 }},
+                        "texture": "grey",
+                        "material": "lambertian"
+                    }},
+                    {{
+                        "shape": {{
+                            "kind": "quad",
+                            "width": 1,
+                            "height": 1,
+                            "transform": {{
+                                "simple sequence": [
+                                    {{ "rotation": {{ "axis": [1, 0, 0], "angle": 90 }} }},
+                                    {{ "translation": [-0.5, 3, -0.5] }}
+                                ]
+                            }}
+                        }},
+                        "texture": "grey",
+                        "material": "light"
+                    }}
+                ]
+            }}"#, num_threads);
+
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn render_is_independent_of_thread_count() {
+        let render = |num_threads: usize| {
+            let json = small_scene_json(num_threads);
+            let mut scene = scene_parsing::parse_json(&json, Path::new("")).unwrap();
+            let image = scene.ray_trace();
+
+            scene.camera.get_resolution()
+                .into_iter()
+                .map(|pixel| image.get_pixel_color(&pixel))
+                .collect::<Vec<Vec3>>()
+        };
+
+        let single = render(1);
+        let multiple = render(3);
+        for (a, b) in single.iter().zip(multiple.iter()) {
+            assert!(a.x() == b.x() && a.y() == b.y() && a.z() == b.z());
+        }
+    }
+
+    #[test]
+    fn checkpoints_and_stopping_early() {
+        let json = small_scene_json(1);
+        let mut scene = scene_parsing::parse_json(&json, Path::new("")).unwrap();
+        scene.checkpoints = CheckpointSchedule { every_samples: Some(1), every_seconds: None };
+
+        // The final image is returned rather than given as a checkpoint.
+        let mut num_checkpoints = 0;
+        scene.ray_trace_progressively(scene.new_image_buffer(), &AtomicBool::new(false), |_| num_checkpoints += 1);
+        assert_eq!(num_checkpoints, scene.num_samples - 1);
+
+        // Stopping before the first sample leaves a black image.
+        let image = scene
+            .ray_trace_progressively(scene.new_image_buffer(), &AtomicBool::new(true), |_| panic!("no checkpoint expected"))
+            .average_samples();
+        for pixel in scene.camera.get_resolution() {
+            let color = image.get_pixel_color(&pixel);
+            assert!(color.x() == 0.0 && color.y() == 0.0 && color.z() == 0.0);
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_spends_samples_on_noisy_pixels() {
+        let json = small_scene_json(1);
+        let mut scene = scene_parsing::parse_json(&json, Path::new("")).unwrap();
+        scene.set_num_samples(6);
+        scene.adaptive_sampling = Some(AdaptiveSampling {
+            relative_error: 0.01,
+            min_samples: 2,
+            max_samples: 24,
+            sample_count_map: None,
+        });
+
+        let image_buffer = scene.ray_trace_progressively(scene.new_image_buffer(), &AtomicBool::new(false), |_| {});
+        let counts: Vec<u32> = scene.camera.get_resolution()
+            .into_iter()
+            .map(|pixel| image_buffer.num_pixel_samples(&pixel))
+            .collect();
+
+        // The background is the same in every sample, so it is converged as soon as 
+        // possible, which leaves more samples for the rest.
+        let top_left = scene.camera.get_resolution().pixel_index(&Pixel { x: 0, y: 29 });
+        assert_eq!(counts[top_left], 2);
+        assert!(counts.iter().all(|count| (2..=24).contains(count)));
+        assert!(counts.iter().any(|count| *count > 6));
+
+        // The same number of samples in total, give or take the last pass.
+        let samples_per_pixel = image_buffer.samples_per_pixel();
+        assert!((6.0..7.0).contains(&samples_per_pixel));
+    }
+
+    #[test]
+    fn aovs_describe_the_first_hit() {
+        let json = small_scene_json(2);
+        let scene = scene_parsing::parse_json(&json, Path::new("")).unwrap();
+        let aovs = scene.render_aovs();
+
+        // The sphere is in the middle of the image, about 2 units from the camera.
+        let center = Pixel { x: 20, y: 15 };
+        assert_eq!(aovs.object_id.get_pixel_color(&center).x(), 0.0);
+        assert!((aovs.depth.get_pixel_color(&center).x() - 2.04).abs() < 0.05);
+        assert!((aovs.albedo.get_pixel_color(&center).y() - 0.5).abs() < 1e-5);
+        assert!(aovs.normal.get_pixel_color(&center).z() > 0.9);
+        assert!(aovs.position.get_pixel_color(&center).z() > 0.9);
+
+        let floor = Pixel { x: 20, y: 0 };
+        assert_eq!(aovs.object_id.get_pixel_color(&floor).x(), 1.0);
+        assert!(aovs.normal.get_pixel_color(&floor).y() > 0.99);
+
+        let sky = Pixel { x: 0, y: 29 };
+        assert_eq!(aovs.object_id.get_pixel_color(&sky).x(), -1.0);
+        assert!(aovs.depth.get_pixel_color(&sky).x().is_infinite());
+        assert_eq!(aovs.albedo.get_pixel_color(&sky).x(), 0.0);
+
+        // Like the render, the AOVs do not depend on the number of threads.
+        let single_threaded = scene_parsing::parse_json(&small_scene_json(1), Path::new(""))
+            .unwrap()
+            .render_aovs();
+        for pixel in scene.camera.get_resolution() {
+            let (a, b) = (aovs.normal.get_pixel_color(&pixel), single_threaded.normal.get_pixel_color(&pixel));
+            assert!(a.x() == b.x() && a.y() == b.y() && a.z() == b.z());
+        }
+    }
+
+    #[test]
+    fn instances_look_like_copies() {
+        // The sphere, as an instance of a prototype that is moved into place and
+        // made of the sphere's material rather than its own, and the light, as a
+        // stretched instance that is sampled like the quad it stands for.
+        let mut json = small_scene_json(1);
+        json["prototypes"] = serde_json::json!([
+            {
+                "name": "ball",
+                "objects": [{
+                    "shape": { "kind": "sphere", "center": [2, 0, 0], "radius": 1 },
+                    "texture": "grey",
+                    "material": "light"
+                }]
+            },
+            {
+                "name": "lamp",
+                "objects": [{
+                    "shape": { "kind": "quad", "width": 0.5, "height": 2 },
+                    "texture": "grey",
+                    "material": "light"
+                }]
+            }
+        ]);
+        json["objects"][0] = serde_json::json!({
+            "instance": "ball",
+            "transform": { "simple sequence": [{ "translation": [-2, 0, 0] }] },
+            "material": "lambertian"
+        });
+        json["objects"][2] = serde_json::json!({
+            "instance": "lamp",
+            "transform": {
+                "simple sequence": [
+                    { "scale": [2, 0.5, 1] },
+                    { "rotation": { "axis": [1, 0, 0], "angle": 90 } },
+                    { "translation": [-0.5, 3, -0.5] }
+                ]
+            }
+        });
+        assert_eq!(scene_parsing::parse_json(&json, Path::new("")).unwrap().objects.lights().len(), 1);
+
+        let render = |json: &serde_json::Value| {
+            let mut scene = scene_parsing::parse_json(json, Path::new("")).unwrap();
+            (scene.ray_trace(), scene.render_aovs())
+        };
+        let (copy, copy_aovs) = render(&small_scene_json(1));
+        let (instanced, instanced_aovs) = render(&json);
+
+        let resolution = scene_parsing::parse_json(&json, Path::new("")).unwrap().camera.get_resolution();
+        let (mut copy_sum, mut instanced_sum) = (0.0, 0.0);
+        for pixel in resolution {
+            assert_eq!(copy_aovs.object_id.get_pixel_color(&pixel).x(), instanced_aovs.object_id.get_pixel_color(&pixel).x());
+            let (a, b) = (copy_aovs.depth.get_pixel_color(&pixel).x(), instanced_aovs.depth.get_pixel_color(&pixel).x());
+            assert!(a == b || (a - b).abs() < 1e-3);
+            copy_sum += copy.get_pixel_color(&pixel).y();
+            instanced_sum += instanced.get_pixel_color(&pixel).y();
+        }
+        assert!((instanced_sum / copy_sum - 1.0).abs() < 0.01, "{} vs {}", instanced_sum, copy_sum);
+    }
+
+    #[test]
+    fn resuming_matches_an_uninterrupted_render() {
+        for sampler in [serde_json::Value::Null, serde_json::json!({ "kind": "sobol" })] {
+            let mut json = small_scene_json(2);
+            json["sampler"] = sampler;
+            let parse = |json: &serde_json::Value| scene_parsing::parse_json(json, Path::new("")).unwrap();
+            let never_stopped = AtomicBool::new(false);
+
+            let mut scene = parse(&json);
+            let uninterrupted = scene.ray_trace_progressively(scene.new_image_buffer(), &never_stopped, |_| {});
+
+            // Stop after a sample, then resume with a newly parsed scene.
+            scene.set_num_samples(1);
+            let partial = scene.ray_trace_progressively(scene.new_image_buffer(), &never_stopped, |_| {});
+            let mut resumed_scene = parse(&json);
+            assert_eq!(resumed_scene.sampler_samples_per_pixel(), scene.sampler_samples_per_pixel());
+            resumed_scene.set_num_samples(uninterrupted.num_passes());
+            let resumed = resumed_scene.ray_trace_progressively(partial, &never_stopped, |_| {});
+
+            assert_eq!(resumed.num_passes(), uninterrupted.num_passes());
+            for (a, b) in resumed.sums().iter().zip(uninterrupted.sums().iter()) {
+                assert!(a.x() == b.x() && a.y() == b.y() && a.z() == b.z());
+            }
+
+            // Changing the number of samples in the scene file changes the layout of 
+            // the Sobol sampler, which is why the layout is saved with the render.
+            let mut more_samples = json.clone();
+            more_samples["integrator"]["number of samples"] = serde_json::json!(8);
+            let is_sobol = !json["sampler"].is_null();
+            assert_eq!(parse(&more_samples).sampler_samples_per_pixel() != scene.sampler_samples_per_pixel(), is_sobol);
+        }
+    }
 }
 
 // #[cfg(test)]
@@ -0,0 +1,87 @@
+
+// S==== IMPORTS {{{1
+
+use crate::config::{
+    self, 
+    AccelerationStructure, 
+    AccStructureKind, 
+    AccStructureAxisSelectionMethod
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const BVH_KIND: &str = "bvh";
+const NOTHING_KIND: &str = "none";
+
+const AXIS_SELECTION_FIELD_NAME: &str = "axis selection";
+const RANDOM_AXIS_SELECTION: &str = "random";
+const ALTERNATING_AXIS_SELECTION: &str = "alternating";
+const LARGEST_EXTENT_AXIS_SELECTION: &str = "largest extent";
+const SURFACE_AREA_HEURISTIC_AXIS_SELECTION: &str = "surface area heuristic";
+
+/// Parses the value of the "acceleration structure" field. Missing fields take their
+/// values from the default configuration.
+pub fn new_from_json(json: &serde_json::Value) -> Result<AccelerationStructure, ParseError> {
+    let mut acceleration_structure = config::default_acceleration_structure();
+
+    match json {
+        serde_json::Value::Null => { return Ok(acceleration_structure); },
+        serde_json::Value::Object(_) => {},
+        _ => {
+            let pe = ParseError {
+                msg: "acceleration structure must be a json map".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    }
+
+    if let Some(kind) = get_string_field(json, KIND_FIELD_NAME)? {
+        acceleration_structure.kind = match kind {
+            BVH_KIND => AccStructureKind::BBH,
+            NOTHING_KIND => AccStructureKind::Nothing,
+            other => {
+                let pe = ParseError {
+                    msg: format!("invalid acceleration structure kind '{}'", other),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        };
+    }
+
+    if let Some(method) = get_string_field(json, AXIS_SELECTION_FIELD_NAME)? {
+        acceleration_structure.axis_selection_method = match method {
+            RANDOM_AXIS_SELECTION => AccStructureAxisSelectionMethod::Random,
+            ALTERNATING_AXIS_SELECTION => AccStructureAxisSelectionMethod::Alternating,
+            LARGEST_EXTENT_AXIS_SELECTION => AccStructureAxisSelectionMethod::LargestExtent,
+            SURFACE_AREA_HEURISTIC_AXIS_SELECTION => AccStructureAxisSelectionMethod::SurfaceAreaHeuristic,
+            other => {
+                let pe = ParseError {
+                    msg: format!("invalid axis selection method '{}'", other),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        };
+    }
+
+    config::validate_acceleration_structure(&acceleration_structure);
+    Ok(acceleration_structure)
+}
+
+fn get_string_field<'a>(json: &'a serde_json::Value, field_name: &str) -> Result<Option<&'a str>, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) => Ok(Some(s.as_str())),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' must be a string", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::math::float::Float,
+    denoisers::{
+        traits::DenoiserLike,
+        guides::FeatureSigmas,
+        cross_bilateral::CrossBilateralDenoiser,
+        non_local_means::NonLocalMeansDenoiser
+    }
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const CROSS_BILATERAL_KIND: &str = "cross bilateral";
+const NON_LOCAL_MEANS_KIND: &str = "non-local means";
+
+const RADIUS_FIELD_NAME: &str = "radius";
+const DEFAULT_RADIUS: u32 = 5;
+const SIGMA_SPATIAL_FIELD_NAME: &str = "sigma spatial";
+/// The default is this fraction of the radius.
+const DEFAULT_SIGMA_SPATIAL_FACTOR: Float = 0.5;
+
+const SEARCH_RADIUS_FIELD_NAME: &str = "search radius";
+const DEFAULT_SEARCH_RADIUS: u32 = 7;
+const PATCH_RADIUS_FIELD_NAME: &str = "patch radius";
+const DEFAULT_PATCH_RADIUS: u32 = 1;
+const STRENGTH_FIELD_NAME: &str = "strength";
+const DEFAULT_STRENGTH: Float = 0.5;
+
+const SIGMA_ALBEDO_FIELD_NAME: &str = "sigma albedo";
+const DEFAULT_SIGMA_ALBEDO: Float = 0.1;
+const SIGMA_NORMAL_FIELD_NAME: &str = "sigma normal";
+const DEFAULT_SIGMA_NORMAL: Float = 0.2;
+const SIGMA_DEPTH_FIELD_NAME: &str = "sigma depth";
+const DEFAULT_SIGMA_DEPTH: Float = 0.1;
+
+/// Parses the value of the "denoiser" field; without one, the render is not
+/// denoised.
+pub fn new_from_json(json: &serde_json::Value) -> Result<Option<Box<dyn DenoiserLike>>, ParseError> {
+    if json.is_null() {
+        return Ok(None);
+    }
+
+    let kind = match json[KIND_FIELD_NAME].as_str() {
+        Some(kind) => kind,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of denoiser", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let sigmas = FeatureSigmas {
+        albedo: get_positive_number(json, SIGMA_ALBEDO_FIELD_NAME, DEFAULT_SIGMA_ALBEDO)?,
+        normal: get_positive_number(json, SIGMA_NORMAL_FIELD_NAME, DEFAULT_SIGMA_NORMAL)?,
+        depth: get_positive_number(json, SIGMA_DEPTH_FIELD_NAME, DEFAULT_SIGMA_DEPTH)?,
+    };
+
+    match kind {
+        CROSS_BILATERAL_KIND => {
+            let radius = get_radius(json, RADIUS_FIELD_NAME, DEFAULT_RADIUS)?;
+            let sigma_spatial = get_positive_number(
+                json,
+                SIGMA_SPATIAL_FIELD_NAME,
+                DEFAULT_SIGMA_SPATIAL_FACTOR * Float::max(radius as Float, 1.0)
+            )?;
+            Ok(Some(Box::new(CrossBilateralDenoiser::new(radius, sigma_spatial, sigmas))))
+        },
+        NON_LOCAL_MEANS_KIND => {
+            let search_radius = get_radius(json, SEARCH_RADIUS_FIELD_NAME, DEFAULT_SEARCH_RADIUS)?;
+            let patch_radius = get_radius(json, PATCH_RADIUS_FIELD_NAME, DEFAULT_PATCH_RADIUS)?;
+            let strength = get_positive_number(json, STRENGTH_FIELD_NAME, DEFAULT_STRENGTH)?;
+            Ok(Some(Box::new(NonLocalMeansDenoiser::new(search_radius, patch_radius, strength, sigmas))))
+        },
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown denoiser kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_radius(json: &serde_json::Value, field_name: &str, default: u32) -> Result<u32, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        value => match serde_json::from_value::<u32>(value.clone()) {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                let pe = ParseError {
+                    msg: format!("field '{}' of denoiser must be a non-negative integer", field_name),
+                    json: json.clone(),
+                };
+                Err(pe)
+            }
+        },
+    }
+}
+
+fn get_positive_number(json: &serde_json::Value, field_name: &str, default: Float) -> Result<Float, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        value => match value.as_f64() {
+            Some(n) if n > 0.0 => Ok(n as Float),
+            _ => {
+                let pe = ParseError {
+                    msg: format!("field '{}' of denoiser must be a positive number", field_name),
+                    json: json.clone(),
+                };
+                Err(pe)
+            }
+        },
+    }
+}
@@ -0,0 +1,151 @@
+// S==== IMPORTS {{{1
+
+use std::path::Path;
+use crate::{
+    utility::math::{vector::Color3, float::Float},
+    environments::{
+        traits::EnvironmentLike,
+        constant::ConstantEnvironment,
+        sky::{SkyEnvironment, SkyEnvironmentInfo},
+        environment_map::EnvironmentMap
+    },
+    objects::textures::image_texture::{ImageTexture, TextureFilter, TextureWrap, TextureColorSpace},
+    light::ColorConstantsQueryable
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const CONSTANT_KIND: &str = "constant";
+const SKY_KIND: &str = "sky";
+const MAP_KIND: &str = "map";
+
+const RGB_FIELD_NAME: &str = "rgb color";
+
+const ZENITH_FIELD_NAME: &str = "zenith color";
+const HORIZON_FIELD_NAME: &str = "horizon color";
+const GROUND_FIELD_NAME: &str = "ground color";
+
+const FILE_FIELD_NAME: &str = "file";
+const STRENGTH_FIELD_NAME: &str = "strength";
+const DEFAULT_STRENGTH: Float = 1.0;
+
+/// Parses the value of the "environment" field, or failing that the "background 
+/// color" field (`background_color_json`). Without either, the environment is white.
+/// Image files are looked for relative to `scene_directory`.
+pub fn new_from_json(
+    json: &serde_json::Value, 
+    background_color_json: &serde_json::Value, 
+    scene_directory: &Path
+) -> Result<Box<dyn EnvironmentLike>, ParseError> {
+    if json.is_null() {
+        let color = match background_color_json {
+            serde_json::Value::Null => Color3::white(),
+            _ => get_color(background_color_json)?,
+        };
+        return Ok(Box::new(ConstantEnvironment::new(color)));
+    }
+
+    let kind = match json[KIND_FIELD_NAME].as_str() {
+        Some(kind) => kind,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of environment", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind {
+        CONSTANT_KIND => Ok(Box::new(ConstantEnvironment::new(get_color(&json[RGB_FIELD_NAME])?))),
+        SKY_KIND => Ok(Box::new(parse_sky(json)?)),
+        MAP_KIND => Ok(Box::new(parse_map(json, scene_directory)?)),
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown environment kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_sky(json: &serde_json::Value) -> Result<SkyEnvironment, ParseError> {
+    let zenith = match json.get(ZENITH_FIELD_NAME) {
+        Some(color) => get_color(color)?,
+        None => Color3::new(0.5, 0.7, 1.0),
+    };
+    let horizon = match json.get(HORIZON_FIELD_NAME) {
+        Some(color) => get_color(color)?,
+        None => Color3::white(),
+    };
+    let ground = match json.get(GROUND_FIELD_NAME) {
+        Some(color) => get_color(color)?,
+        None => horizon.clone(),
+    };
+
+    let info = SkyEnvironmentInfo {
+        zenith,
+        horizon,
+        ground,
+    };
+    Ok(SkyEnvironment::new(info))
+}
+
+fn parse_map(json: &serde_json::Value, scene_directory: &Path) -> Result<EnvironmentMap, ParseError> {
+    let filename = match json[FILE_FIELD_NAME].as_str() {
+        Some(f) => f,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of environment map", FILE_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let strength = match &json[STRENGTH_FIELD_NAME] {
+        serde_json::Value::Null => DEFAULT_STRENGTH,
+        serde_json::Value::Number(n) if n.as_f64().is_some_and(|n| n >= 0.0) => n.as_f64().unwrap() as Float,
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' of environment map must be a nonnegative number", STRENGTH_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    // Environment maps are usually HDR files, which are linear anyway.
+    let image = ImageTexture::new_from_file(
+        &scene_directory.join(filename), 
+        TextureFilter::Bilinear, 
+        TextureWrap::Repeat, 
+        TextureColorSpace::Srgb
+    );
+    match image {
+        Ok(image) => Ok(EnvironmentMap::new(image, strength)),
+        Err(msg) => {
+            let pe = ParseError {
+                msg: format!("could not load image '{}': {}", filename, msg),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_color(json: &serde_json::Value) -> Result<Color3, ParseError> {
+    match serde_json::from_value::<Color3>(json.clone()) {
+        Ok(c) => Ok(c),
+        Err(_) => {
+            let pe = ParseError {
+                msg: "could not parse environment color, which should look like [r, g, b]".to_string(),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
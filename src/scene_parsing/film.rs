@@ -0,0 +1,207 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{math::float::Float, tone_mapping::{DisplayTransform, ToneMapping}},
+    filters::{
+        traits::FilterLike,
+        box_filter::BoxFilter,
+        tent::TentFilter,
+        gaussian::GaussianFilter,
+        mitchell::MitchellFilter,
+        lanczos::LanczosFilter
+    }
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const FILTER_FIELD_NAME: &str = "filter";
+const EXPOSURE_FIELD_NAME: &str = "exposure";
+const TONE_MAPPING_FIELD_NAME: &str = "tone mapping";
+
+const KIND_FIELD_NAME: &str = "kind";
+const BOX_KIND: &str = "box";
+const TENT_KIND: &str = "tent";
+const GAUSSIAN_KIND: &str = "gaussian";
+const MITCHELL_KIND: &str = "mitchell";
+const LANCZOS_KIND: &str = "lanczos";
+
+const RADIUS_FIELD_NAME: &str = "radius";
+const DEFAULT_BOX_RADIUS: Float = 0.5;
+const DEFAULT_TENT_RADIUS: Float = 1.0;
+const DEFAULT_GAUSSIAN_RADIUS: Float = 1.5;
+const DEFAULT_MITCHELL_RADIUS: Float = 2.0;
+const DEFAULT_LANCZOS_RADIUS: Float = 2.0;
+
+const SIGMA_FIELD_NAME: &str = "sigma";
+const DEFAULT_SIGMA: Float = 0.5;
+
+const B_FIELD_NAME: &str = "b";
+const C_FIELD_NAME: &str = "c";
+const DEFAULT_B: Float = 1.0 / 3.0;
+const DEFAULT_C: Float = 1.0 / 3.0;
+
+const CLAMP_KIND: &str = "clamp";
+const REINHARD_KIND: &str = "reinhard";
+const EXTENDED_REINHARD_KIND: &str = "extended reinhard";
+const ACES_KIND: &str = "aces";
+const AGX_KIND: &str = "agx";
+
+const WHITE_POINT_FIELD_NAME: &str = "white point";
+const DEFAULT_WHITE_POINT: Float = 4.0;
+
+pub struct FilmParseOutput {
+    pub filter: Box<dyn FilterLike>,
+    pub display: DisplayTransform,
+}
+
+/// Removes the fields which only affect how the rendered image is displayed, rather 
+/// than the image itself.
+pub fn remove_display_fields(json: &mut serde_json::Value) {
+    if let Some(map) = json.as_object_mut() {
+        map.remove(EXPOSURE_FIELD_NAME);
+        map.remove(TONE_MAPPING_FIELD_NAME);
+    }
+}
+
+/// Parses the value of the "film" field, which may be absent.
+pub fn new_from_json(json: &serde_json::Value) -> Result<FilmParseOutput, ParseError> {
+    match json {
+        serde_json::Value::Null | serde_json::Value::Object(_) => {},
+        _ => {
+            let pe = ParseError {
+                msg: "'film' must be a json map".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    }
+
+    let filter = get_filter(&json[FILTER_FIELD_NAME])?;
+    let display = DisplayTransform {
+        exposure: get_number(json, EXPOSURE_FIELD_NAME, 0.0, "film")?,
+        tone_mapping: get_tone_mapping(&json[TONE_MAPPING_FIELD_NAME])?,
+    };
+
+    Ok(FilmParseOutput {
+        filter,
+        display,
+    })
+}
+
+fn get_filter(json: &serde_json::Value) -> Result<Box<dyn FilterLike>, ParseError> {
+    if json.is_null() {
+        return Ok(Box::new(BoxFilter::new(DEFAULT_BOX_RADIUS)));
+    }
+
+    let kind = match json[KIND_FIELD_NAME].as_str() {
+        Some(kind) => kind,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of filter", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind {
+        BOX_KIND => {
+            let radius = get_positive_number(json, RADIUS_FIELD_NAME, DEFAULT_BOX_RADIUS, "filter")?;
+            Ok(Box::new(BoxFilter::new(radius)))
+        },
+        TENT_KIND => {
+            let radius = get_positive_number(json, RADIUS_FIELD_NAME, DEFAULT_TENT_RADIUS, "filter")?;
+            Ok(Box::new(TentFilter::new(radius)))
+        },
+        GAUSSIAN_KIND => {
+            let radius = get_positive_number(json, RADIUS_FIELD_NAME, DEFAULT_GAUSSIAN_RADIUS, "filter")?;
+            let sigma = get_positive_number(json, SIGMA_FIELD_NAME, DEFAULT_SIGMA, "filter")?;
+            Ok(Box::new(GaussianFilter::new(radius, sigma)))
+        },
+        MITCHELL_KIND => {
+            let radius = get_positive_number(json, RADIUS_FIELD_NAME, DEFAULT_MITCHELL_RADIUS, "filter")?;
+            let b = get_number(json, B_FIELD_NAME, DEFAULT_B, "filter")?;
+            let c = get_number(json, C_FIELD_NAME, DEFAULT_C, "filter")?;
+            Ok(Box::new(MitchellFilter::new(radius, b, c)))
+        },
+        LANCZOS_KIND => {
+            let radius = get_positive_number(json, RADIUS_FIELD_NAME, DEFAULT_LANCZOS_RADIUS, "filter")?;
+            Ok(Box::new(LanczosFilter::new(radius)))
+        },
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown filter kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_tone_mapping(json: &serde_json::Value) -> Result<ToneMapping, ParseError> {
+    if json.is_null() {
+        return Ok(ToneMapping::Clamp);
+    }
+
+    let kind = match json[KIND_FIELD_NAME].as_str() {
+        Some(kind) => kind,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of tone mapping", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind {
+        CLAMP_KIND => Ok(ToneMapping::Clamp),
+        REINHARD_KIND => Ok(ToneMapping::Reinhard),
+        EXTENDED_REINHARD_KIND => {
+            let white_point = get_positive_number(
+                json, WHITE_POINT_FIELD_NAME, DEFAULT_WHITE_POINT, "tone mapping"
+            )?;
+            Ok(ToneMapping::ExtendedReinhard { white_point })
+        },
+        ACES_KIND => Ok(ToneMapping::AcesFilmic),
+        AGX_KIND => Ok(ToneMapping::Agx),
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown tone mapping kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// `owner` names the map the field belongs to, for error messages.
+fn get_number(json: &serde_json::Value, field_name: &str, default: Float, owner: &str) -> Result<Float, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        value => match value.as_f64() {
+            Some(n) => Ok(n as Float),
+            None => {
+                let pe = ParseError {
+                    msg: format!("field '{}' of {} must be a number", field_name, owner),
+                    json: json.clone(),
+                };
+                Err(pe)
+            }
+        },
+    }
+}
+
+fn get_positive_number(json: &serde_json::Value, field_name: &str, default: Float, owner: &str) -> Result<Float, ParseError> {
+    let n = get_number(json, field_name, default, owner)?;
+    if n <= 0.0 {
+        let pe = ParseError {
+            msg: format!("field '{}' of {} must be positive", field_name, owner),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    Ok(n)
+}
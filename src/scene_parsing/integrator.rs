@@ -1,13 +1,19 @@
 
 // S==== IMPORTS {{{1
 
-use crate::integrators::{traits::IntegratorLike, ambient_occlusion::AmbientOcclusionIntegrator};
+use std::thread;
+use crate::integrators::{
+    traits::IntegratorLike, 
+    ambient_occlusion::AmbientOcclusionIntegrator, 
+    path_tracer::PathTracerIntegrator
+};
 use super::parse_error::ParseError;
 
 // E==== IMPORTS }}}1
 
 const KIND_FIELD_NAME: &str = "kind";
 const AMBIENT_OCCLUSION_KIND: &str = "ambient occlusion";
+const PATH_TRACER_KIND: &str = "path tracer";
 
 const NUM_SAMPLES_FIELD_NAME: &str = "number of samples";
 const DEFAULT_NUM_SAMPLES: u32 = 64;
@@ -15,25 +21,52 @@ const DEFAULT_NUM_SAMPLES: u32 = 64;
 const RECURSION_LIMIT_FIELD_NAME: &str = "ray recursion limit";
 const DEFAULT_RECURSION_LIMIT: u32 = 64;
 
+const NEXT_EVENT_ESTIMATION_FIELD_NAME: &str = "next event estimation";
+const DEFAULT_NEXT_EVENT_ESTIMATION: bool = false;
+
+const NUM_THREADS_FIELD_NAME: &str = "number of threads";
+
+const SEED_FIELD_NAME: &str = "seed";
+const DEFAULT_SEED: u32 = 1;
+
+const SPECTRAL_FIELD_NAME: &str = "spectral";
+const DEFAULT_SPECTRAL: bool = false;
+
 pub struct IntegratorParseOutput {
     pub integrator: Box<dyn IntegratorLike>,
     pub num_samples: u32,
-    pub recursion_limit: u32,
+    pub num_threads: usize,
+    pub seed: u32,
+    pub spectral: bool,
+}
+
+/// Removes the fields which only affect how long the render takes, rather than the 
+/// image it converges to.
+pub fn remove_sampling_fields(json: &mut serde_json::Value) {
+    if let Some(map) = json.as_object_mut() {
+        map.remove(NUM_SAMPLES_FIELD_NAME);
+        map.remove(NUM_THREADS_FIELD_NAME);
+    }
 }
 
 pub fn new_from_json(json: &serde_json::Value) -> Result<IntegratorParseOutput, ParseError> {
-    let integrator = get_integrator(json)?;
-    let num_samples = get_num_samples(json)?;
     let recursion_limit = get_recursion_limit(json)?;
+    let integrator = get_integrator(json, recursion_limit)?;
+    let num_samples = get_num_samples(json)?;
+    let num_threads = get_num_threads(json)?;
+    let seed = get_seed(json)?;
+    let spectral = get_spectral(json)?;
 
     Ok(IntegratorParseOutput {
         integrator,
         num_samples,
-        recursion_limit
+        num_threads,
+        seed,
+        spectral,
     })
 }
 
-fn get_integrator(json: &serde_json::Value) -> Result<Box<dyn IntegratorLike>, ParseError> {
+fn get_integrator(json: &serde_json::Value, recursion_limit: u32) -> Result<Box<dyn IntegratorLike>, ParseError> {
     let integrator_name = match serde_json::from_value::<String>(json[KIND_FIELD_NAME].clone()) {
         Ok(s) => s,
         Err(_) => {
@@ -47,6 +80,10 @@ fn get_integrator(json: &serde_json::Value) -> Result<Box<dyn IntegratorLike>, P
 
     match integrator_name.as_str() {
         AMBIENT_OCCLUSION_KIND => Ok(Box::new(AmbientOcclusionIntegrator {})),
+        PATH_TRACER_KIND => {
+            let next_event_estimation = get_next_event_estimation(json)?;
+            Ok(Box::new(PathTracerIntegrator::new(recursion_limit, next_event_estimation)))
+        },
         other => {
             let pe = ParseError {
                 msg: format!("invalid integrator kind '{}'", other),
@@ -97,3 +134,74 @@ fn get_recursion_limit(json: &serde_json::Value) -> Result<u32, ParseError> {
     };
 }
 
+fn get_next_event_estimation(json: &serde_json::Value) -> Result<bool, ParseError> {
+    // Default value if none provided.
+    if json.get(NEXT_EVENT_ESTIMATION_FIELD_NAME).is_none() {
+        return Ok(DEFAULT_NEXT_EVENT_ESTIMATION);
+    }
+
+    match serde_json::from_value::<bool>(json[NEXT_EVENT_ESTIMATION_FIELD_NAME].clone()) {
+        Ok(b) => Ok(b),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", NEXT_EVENT_ESTIMATION_FIELD_NAME),
+                json: json.clone()
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_num_threads(json: &serde_json::Value) -> Result<usize, ParseError> {
+    // Default to however many threads the machine can run at once.
+    if json.get(NUM_THREADS_FIELD_NAME).is_none() {
+        return Ok(thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+    }
+
+    match serde_json::from_value::<usize>(json[NUM_THREADS_FIELD_NAME].clone()) {
+        Ok(n) if n > 0 => Ok(n),
+        _ => {
+            let pe = ParseError {
+                msg: format!("field '{}' must be a positive integer", NUM_THREADS_FIELD_NAME),
+                json: json.clone()
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_seed(json: &serde_json::Value) -> Result<u32, ParseError> {
+    // Default value if none provided.
+    if json.get(SEED_FIELD_NAME).is_none() {
+        return Ok(DEFAULT_SEED);
+    }
+
+    match serde_json::from_value::<u32>(json[SEED_FIELD_NAME].clone()) {
+        Ok(n) => Ok(n),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", SEED_FIELD_NAME),
+                json: json.clone()
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_spectral(json: &serde_json::Value) -> Result<bool, ParseError> {
+    // Default value if none provided.
+    if json.get(SPECTRAL_FIELD_NAME).is_none() {
+        return Ok(DEFAULT_SPECTRAL);
+    }
+
+    match serde_json::from_value::<bool>(json[SPECTRAL_FIELD_NAME].clone()) {
+        Ok(b) => Ok(b),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", SPECTRAL_FIELD_NAME),
+                json: json.clone()
+            };
+            Err(pe)
+        }
+    }
+}
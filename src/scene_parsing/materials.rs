@@ -1,8 +1,17 @@
 
 // S==== IMPORTS {{{1
 
-use std::{rc::Rc, collections::HashMap};
-use crate::objects::materials::{lambertian::Lambertian, traits::MaterialLike};
+use std::{sync::Arc, collections::HashMap};
+use crate::{
+    objects::materials::{
+        lambertian::Lambertian, 
+        diffuse_light::DiffuseLight, 
+        metal::Metal,
+        dielectric::{Dielectric, FresnelApproximation},
+        traits::MaterialLike
+    },
+    utility::math::{vector::Color3, float::Float}
+};
 use super::parse_error::ParseError;
 
 // E==== IMPORTS }}}1
@@ -10,13 +19,28 @@ use super::parse_error::ParseError;
 const NAME_FIELD_NAME: &str = "name";
 const KIND_FIELD_NAME: &str = "kind";
 const LAMBERTIAN_KIND: &str = "lambertian";
+const DIFFUSE_LIGHT_KIND: &str = "diffuse light";
+const METAL_KIND: &str = "metal";
+const DIELECTRIC_KIND: &str = "dielectric";
+
+const RADIANCE_FIELD_NAME: &str = "radiance";
+
+const FUZZ_FIELD_NAME: &str = "fuzz";
+const DEFAULT_FUZZ: Float = 0.0;
+
+const INDEX_OF_REFRACTION_FIELD_NAME: &str = "index of refraction";
+const DEFAULT_INDEX_OF_REFRACTION: Float = 1.5;
+const ABBE_NUMBER_FIELD_NAME: &str = "abbe number";
+const FRESNEL_FIELD_NAME: &str = "fresnel";
+const SCHLICK_FRESNEL: &str = "schlick";
+const EXACT_FRESNEL: &str = "exact";
 
 pub struct MaterialMap {
-    map: HashMap<String, Rc<dyn MaterialLike>>
+    map: HashMap<String, Arc<dyn MaterialLike>>
 }
 
 impl MaterialMap {
-    pub fn get(&self, key: &str) -> Result<Rc<dyn MaterialLike>, ParseError> {
+    pub fn get(&self, key: &str) -> Result<Arc<dyn MaterialLike>, ParseError> {
         match self.map.get(key) {
             Some(val) => Ok(val.clone()),
             None => {
@@ -42,7 +66,7 @@ pub fn parse_json(json: &serde_json::Value) -> Result<MaterialMap, ParseError> {
         }
     };   
 
-    let mut to_return: HashMap<String, Rc<dyn MaterialLike>> = HashMap::new();
+    let mut to_return: HashMap<String, Arc<dyn MaterialLike>> = HashMap::new();
     for material in json_array.iter() {
         let result = parse_single_material(&material)?;
         to_return.insert(result.0, result.1);
@@ -53,15 +77,18 @@ pub fn parse_json(json: &serde_json::Value) -> Result<MaterialMap, ParseError> {
     })
 }
 
-fn parse_single_material(json: &serde_json::Value) -> Result<(String, Rc<dyn MaterialLike>), ParseError> {
+fn parse_single_material(json: &serde_json::Value) -> Result<(String, Arc<dyn MaterialLike>), ParseError> {
     let name = get_name(json)?;
 
     let kind_name = get_kind_name(json)?; 
     match kind_name.as_str() {
         LAMBERTIAN_KIND => {
             let material = Lambertian {}; 
-            return Ok((name, Rc::new(material)));
+            return Ok((name, Arc::new(material)));
         },
+        DIFFUSE_LIGHT_KIND => Ok((name, Arc::new(parse_diffuse_light(json)?))),
+        METAL_KIND => Ok((name, Arc::new(parse_metal(json)?))),
+        DIELECTRIC_KIND => Ok((name, Arc::new(parse_dielectric(json)?))),
         other => {
             let pe = ParseError {
                 msg: format!("unknown material kind {}", other),
@@ -112,6 +139,77 @@ fn get_kind_name(json: &serde_json::Value) -> Result<String, ParseError> {
     };
 }
 
+fn parse_diffuse_light(json: &serde_json::Value) -> Result<DiffuseLight, ParseError> {
+    match serde_json::from_value::<Color3>(json[RADIANCE_FIELD_NAME].clone()) {
+        Ok(radiance) => Ok(DiffuseLight::new(radiance)),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of diffuse light", RADIANCE_FIELD_NAME),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_metal(json: &serde_json::Value) -> Result<Metal, ParseError> {
+    let fuzz = get_optional_number(json, FUZZ_FIELD_NAME)?.unwrap_or(DEFAULT_FUZZ);
+    Ok(Metal::new(fuzz))
+}
+
+fn parse_dielectric(json: &serde_json::Value) -> Result<Dielectric, ParseError> {
+    let index_of_refraction = get_optional_number(json, INDEX_OF_REFRACTION_FIELD_NAME)?
+        .unwrap_or(DEFAULT_INDEX_OF_REFRACTION);
+    if index_of_refraction <= 0.0 {
+        let pe = ParseError {
+            msg: format!("field '{}' must be positive", INDEX_OF_REFRACTION_FIELD_NAME),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    let abbe_number = get_optional_number(json, ABBE_NUMBER_FIELD_NAME)?;
+    if abbe_number.is_some_and(|abbe_number| abbe_number <= 0.0) {
+        let pe = ParseError {
+            msg: format!("field '{}' must be positive", ABBE_NUMBER_FIELD_NAME),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    let fresnel = match &json[FRESNEL_FIELD_NAME] {
+        serde_json::Value::Null => FresnelApproximation::Exact,
+        serde_json::Value::String(s) if s == SCHLICK_FRESNEL => FresnelApproximation::Schlick,
+        serde_json::Value::String(s) if s == EXACT_FRESNEL => FresnelApproximation::Exact,
+        _ => {
+            let pe = ParseError {
+                msg: format!(
+                    "value of field '{}' must be '{}' or '{}'", 
+                    FRESNEL_FIELD_NAME, SCHLICK_FRESNEL, EXACT_FRESNEL
+                ),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    Ok(Dielectric::new(index_of_refraction, abbe_number, fresnel))
+}
+
+fn get_optional_number(json: &serde_json::Value, field_name: &str) -> Result<Option<Float>, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Number(n) => Ok(n.as_f64().map(|n| n as Float)),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' must be a number", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
 // S==== TESTS {{{1
 
 #[cfg(test)]
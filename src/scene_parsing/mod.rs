@@ -7,8 +7,9 @@
 //! {
 //!     "camera": ...,
 //!     "integrator": ...,
-//!     "background color": ...,
-//!
+//!     "materials": ...,
+//!     "textures": ...,
+//!     "objects": ...
 //! }
 //! ```
 //!
@@ -16,9 +17,215 @@
 //! ```
 //! {
 //!     ...,
-//!     "background color": []
+//!     "environment": { "kind": "constant", "rgb color": [1, 1, 1] },
+//!     "background color": [1, 1, 1],
+//!     "output": None,
+//!     "film": { "filter": { "kind": "box", "radius": 0.5 }, "exposure": 0, "tone mapping": { "kind": "clamp" } },
+//!     "sampler": { "kind": "independent" },
+//!     "checkpoint": None,
+//!     "adaptive sampling": None,
+//!     "aovs": None,
+//!     "denoiser": None,
+//!     "acceleration structure": { "kind": "bvh", "axis selection": "largest extent" },
+//!     "prototypes": None
+//! }
+//! ```
+//!
+//! ## output
+//!
+//! The file the rendered image is written to, relative to the directory of the 
+//! scene file. The second command line argument, if given, takes precedence. The 
+//! format is deduced from the extension: ".png", ".jpg"/".jpeg", or one of the 
+//! floating point formats ".exr" (OpenEXR) and ".hdr" (Radiance HDR), which keep 
+//! the unclamped values.
+//! ```
+//! "output": "renders/cornell_box.png"
+//! ```
+//!
+//! ## film
+//!
+//! How the samples become pixels. Each sample is taken at a random point of its 
+//! pixel, and each pixel is the average of the samples within the "radius" (in 
+//! pixels, along either axis) of its center, weighted by the "filter". The default 
+//! box filter with radius 0.5 is the plain average of the samples inside the pixel.
+//! ```
+//! "film": {
+//!     "filter": {
+//!         "kind": "box", "radius": Float (default 0.5)
+//!     }
+//! }
+//! ```
+//! The other filters are
+//! ```
+//! { "kind": "tent", "radius": Float (default 1) }
+//! { "kind": "gaussian", "radius": Float (default 1.5), "sigma": Float (default 0.5) }
+//! { "kind": "mitchell", "radius": Float (default 2), "b": Float, "c": Float (both default 1/3) }
+//! { "kind": "lanczos", "radius": Float (default 2) }
+//! ```
+//! The Mitchell-Netravali and Lanczos filters sharpen the image but may cause 
+//! ringing around bright edges; the tent and Gaussian filters blur slightly.
+//!
+//! The low dynamic range formats (".png", ".jpg") can only store values in $[0,1]$. 
+//! Before they are written, the image is multiplied by $2^{exposure}$ and "tone 
+//! mapping" brings it into that range, after which it is sRGB encoded. The 
+//! floating point formats store the linear values, ignoring both.
+//! ```
+//! "film": {
+//!     "exposure": Float (in stops, default 0),
+//!     "tone mapping": {
+//!         "kind": "clamp" | "reinhard" | "extended reinhard" | "aces" | "agx",
+//!         "white point": Float (default 4, "extended reinhard" only)
+//!     }
+//! }
+//! ```
+//! "clamp" clips every channel at 1. "reinhard" compresses the luminance $L$ to 
+//! $L / (1 + L)$, and "extended reinhard" does so such that "white point" maps to 
+//! white. "aces" is a fit of the ACES filmic curve, and "agx" an approximation of 
+//! Blender's AgX, which turns very bright saturated colors white rather than 
+//! clipping them.
+//!
+//! ## sampler
+//!
+//! Where the random numbers of the render come from.
+//! ```
+//! "sampler": {
+//!     "kind": "independent" | "stratified" | "halton" | "sobol"
 //! }
 //! ```
+//! "independent" uses plain random numbers. The others spread each pixel's samples 
+//! out more evenly, which reduces noise: "stratified" jitters the samples within a 
+//! grid of as many cells as the integrator's "number of samples", while "halton" 
+//! and "sobol" use (Owen scrambled) low-discrepancy sequences. "sobol" works best 
+//! with a power of two samples.
+//!
+//! ## checkpoint
+//!
+//! During long renders, the image rendered so far is written to the output file 
+//! every "every samples" samples per pixel and/or every "every seconds" seconds, 
+//! whichever comes first. Either field may be omitted. Interrupting the render 
+//! (Ctrl-C) also writes the image rendered so far; interrupting it twice quits 
+//! immediately. With the `--state` command line option, the samples rendered so 
+//! far are saved at each checkpoint too, so the render can be resumed (see 
+//! `render_state`).
+//! ```
+//! "checkpoint": {
+//!     "every samples": Unsigned Integer,
+//!     "every seconds": Float
+//! }
+//! ```
+//!
+//! ## adaptive sampling
+//!
+//! Without it, every pixel gets the integrator's "number of samples". With it, the 
+//! render spends the same number of samples in total (i.e. that many per pixel on 
+//! average) but stops sampling a pixel once the standard error of its mean 
+//! luminance, relative to the mean, is below "relative error", so that noisy pixels 
+//! get more samples. Every pixel gets between "min samples" (by default 16, or the 
+//! number of samples if that is less) and "max samples" (by default 8 times the 
+//! number of samples) samples. If "sample count map" is given, a grey image of how 
+//! many samples each pixel got (white being "max samples") is written there, 
+//! relative to the directory of the scene file.
+//! ```
+//! "adaptive sampling": {
+//!     "relative error": Float (default 0.01),
+//!     "min samples": Unsigned Integer,
+//!     "max samples": Unsigned Integer,
+//!     "sample count map": String (default None)
+//! }
+//! ```
+//!
+//! ## aovs
+//!
+//! Images of what the camera sees first, rather than of the light arriving from 
+//! it, for denoising and debugging (see `aovs`). Each is written to the given file, 
+//! relative to the directory of the scene file, before the render starts. Any 
+//! subset may be given.
+//! ```
+//! "aovs": {
+//!     "albedo": String,
+//!     "normal": String,
+//!     "depth": String,
+//!     "position": String,
+//!     "object id": String
+//! }
+//! ```
+//! The "albedo" is the color of the texture. The "normal" and "position" are in 
+//! world space, the "depth" is the distance from the camera, and the "object id" is 
+//! the index of the object in "objects". Where nothing is hit, they are black, 
+//! zero, infinite and -1 respectively. Since the low dynamic range formats clamp 
+//! to $[0,1]$, write all but the albedo to ".exr" or ".hdr" files.
+//!
+//! ## denoiser
+//!
+//! Removes the noise from the rendered image before it is written (at checkpoints 
+//! too), guided by its albedo, normal and depth AOVs (see `denoisers`). 
+//! ```
+//! "denoiser": {
+//!     "kind": "cross bilateral",
+//!     "radius": Unsigned Integer (default 5),
+//!     "sigma spatial": Float (default half the radius)
+//! }
+//! "denoiser": {
+//!     "kind": "non-local means",
+//!     "search radius": Unsigned Integer (default 7),
+//!     "patch radius": Unsigned Integer (default 1),
+//!     "strength": Float (default 0.5)
+//! }
+//! ```
+//! Both average each pixel with the pixels within the (search) radius that see a 
+//! similar surface. "cross bilateral" weights them by their distance only, so it 
+//! removes the most noise but blurs shadows. "non-local means" also compares the 
+//! patches around the pixels, which keeps the edges of shadows; a larger "strength" 
+//! removes more noise and more detail. How different the surfaces may be is set by 
+//! "sigma albedo" (default 0.1), "sigma normal" (default 0.2) and "sigma depth" 
+//! (relative to the depth, default 0.1), which either kind accepts.
+//!
+//! ## environment
+//!
+//! The light arriving from infinitely far away, which rays that escape the scene 
+//! see. "background color": [r, g, b] is short for a "constant" environment, and is 
+//! ignored if "environment" is given.
+//! ```
+//! "environment": {
+//!     "kind": "constant",
+//!     "rgb color": [Float, Float, Float]
+//! }
+//! ```
+//! A sky, brightest at the zenith ($+y$), with a uniform ground below the horizon:
+//! ```
+//! "environment": {
+//!     "kind": "sky",
+//!     "zenith color": [Float, Float, Float] (default [0.5, 0.7, 1]),
+//!     "horizon color": [Float, Float, Float] (default [1, 1, 1]),
+//!     "ground color": [Float, Float, Float] (default: the horizon color)
+//! }
+//! ```
+//! An equirectangular (latitude-longitude) image, usually a ".hdr" or ".exr" file, 
+//! relative to the directory of the scene file. The top of the image is $+y$ and 
+//! its center $+x$. With "next event estimation", bright parts of the image are 
+//! sampled as lights.
+//! ```
+//! "environment": {
+//!     "kind": "map",
+//!     "file": String,
+//!     "strength": Float (default 1)
+//! }
+//! ```
+//!
+//! ## acceleration structure
+//!
+//! How the objects are organized to speed up finding the object a ray hits. Either 
+//! field may be omitted.
+//! ```
+//! "acceleration structure": {
+//!     "kind": "bvh" | "none",
+//!     "axis selection": "random" | "alternating" | "largest extent" 
+//!                       | "surface area heuristic"
+//! }
+//! ```
+//! With "none", every ray is tested against every object. The bounding volume 
+//! hierarchy ("bvh") splits the objects along an axis chosen by "axis selection"; 
+//! "surface area heuristic" builds the best hierarchy but takes longest to build.
 //!
 //! ## integrator
 //!
@@ -27,10 +234,22 @@
 //! {
 //!     ...,
 //!     "number of samples": Unsigned Integer (default 64),
-//!     "ray recursion limit": Unsigned Integer (default 64)
+//!     "ray recursion limit": Unsigned Integer (default 64),
+//!     "number of threads": Unsigned Integer (default: the number of cores),
+//!     "seed": Unsigned Integer (default 1),
+//!     "spectral": Boolean (default false)
 //! }
 //! ```
 //!
+//! Renders are reproducible: the same scene and seed produce the same image, 
+//! regardless of the number of threads.
+//!
+//! When "spectral", light is traced at wavelengths sampled for each camera ray 
+//! rather than as red, green and blue. The colors of textures, lights and the 
+//! environment are converted to spectra, and the result back to colors. This is 
+//! needed for dispersion (see "abbe number" of dielectrics), and adds some color 
+//! noise.
+//!
 //! ### ambient occlusion
 //! 
 //! ```
@@ -40,6 +259,21 @@
 //! }
 //! ```
 //!
+//! ### path tracer
+//!
+//! Follows each path until it escapes the scene or has bounced "ray recursion limit"
+//! times.
+//! ```
+//! {
+//!     "kind": "path tracer",
+//!     ...,
+//!     "next event estimation": Boolean (default false)
+//! }
+//! ```
+//! With "next event estimation", each bounce also samples a point on a light (a 
+//! quad or sphere with a "diffuse light" material) and casts a shadow ray towards 
+//! it. This is much less noisy when the lights are small.
+//!
 //! ## camera
 //!
 //! ```
@@ -64,6 +298,35 @@
 //!     ...
 //! ]
 //! ```
+//!
+//! An entry can also be an instance of a prototype (see below), placed in the 
+//! scene by "transform". If "material" is given, it replaces the materials of all 
+//! of the prototype's objects.
+//! ```
+//! {
+//!     "instance": Name of Prototype,
+//!     "transform": Transform (default identity),
+//!     "material": Name of Material (optional)
+//! }
+//! ```
+//!
+//! ## prototypes
+//!
+//! Groups of objects, e.g. a tree made of a trunk and leaves, to be placed in the 
+//! scene many times by instances. A prototype is built once, with its own 
+//! acceleration structure (as described by "acceleration structure"), which all of 
+//! its instances share. Its objects are listed like the scene's, but cannot be 
+//! instances themselves. Each instance of a prototype with lights adds its own 
+//! copies of them to the lights of the scene.
+//! ```
+//! "prototypes": [
+//!     {
+//!         "name": Name,
+//!         "objects": [ Object, ... ]
+//!     },
+//!     ...
+//! ]
+//! ```
 //! 
 //! ## materials 
 //!
@@ -81,6 +344,58 @@
 //!
 //! #### lambertian
 //!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "lambertian"
+//! }
+//! ```
+//!
+//! #### metal
+//!
+//! Reflects like a mirror, tinted by the object's texture. The reflected direction 
+//! is randomly perturbed by up to "fuzz" (between 0 and 1), which makes the metal 
+//! look brushed.
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "metal",
+//!     "fuzz": Float (default 0)
+//! }
+//! ```
+//!
+//! #### dielectric
+//!
+//! A transparent material such as glass (index of refraction about 1.5) or water 
+//! (1.33), which reflects and refracts light. The amount reflected is given by the 
+//! Fresnel equations, either "exact"ly or by Schlick's approximation.
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "dielectric",
+//!     "index of refraction": Float (default 1.5),
+//!     "abbe number": Float (optional),
+//!     "fresnel": "exact" (default) | "schlick"
+//! }
+//! ```
+//! With an "abbe number" (about 60 for crown glass, 30 for flint glass; lower is 
+//! more dispersive) the index of refraction depends on the wavelength, and is the 
+//! given one for yellow light. This splits white light into its colors, but only in 
+//! "spectral" renders.
+//!
+//! #### diffuse light
+//!
+//! Turns the object into an area light, emitting `radiance` uniformly from both 
+//! sides of its surface. Any shape (e.g. a quad or sphere) may use it. The light 
+//! does not reflect anything, so the object's texture has no effect.
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "diffuse light",
+//!     "radiance": [r, g, b]
+//! }
+//! ```
+//!
 //! ## textures
 //!
 //! The basic setup is an array as follows:
@@ -107,6 +422,63 @@
 //!     "rgb_color": [r,g,b]
 //! }
 //! ```
+//!
+//! ### Image texture
+//!
+//! Looks up the color in an image file (PNG, JPEG, HDR, EXR, ...), relative to the 
+//! directory of the scene file. The texture coordinates $(0,0)$ and $(1,1)$ are the 
+//! bottom left and top right of the image.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "image",
+//!     "file": "textures/earth.jpg",
+//!     "filter": "nearest" | "bilinear" (default),
+//!     "wrap": "repeat" (default) | "clamp" | "mirror",
+//!     "color space": "srgb" (default) | "linear"
+//! }
+//! ```
+//! The "color space" says how the integers of e.g. a PNG are to be interpreted; 
+//! floating point images (HDR, EXR) are always taken to be linear.
+//!
+//! ### Checker texture
+//!
+//! Alternates between the textures named "even" and "odd", which must be listed 
+//! before the checker texture. With the "surface" domain, the checks are squares 
+//! laid out by texture coordinates; with "solid" they are cubes filling (global) 
+//! space. There are "scale" checks per unit.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "checker",
+//!     "even": Name of Texture,
+//!     "odd": Name of Texture,
+//!     "domain": "surface" (default) | "solid",
+//!     "scale": Float (default 1)
+//! }
+//! ```
+//!
+//! ### Noise textures
+//!
+//! Blend between the textures named "low" and "high" (by default black and white)
+//! according to a pattern made of Perlin noise, evaluated at the (global) position 
+//! multiplied by "scale":
+//! - "noise": smooth noise.
+//! - "turbulence": several "octaves" of noise at increasing frequencies.
+//! - "marble": stripes along the $x$ axis, distorted by turbulence.
+//! - "wood": rings around the $y$ axis, distorted by turbulence.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "noise" | "turbulence" | "marble" | "wood",
+//!     "low": Name of Texture,
+//!     "high": Name of Texture,
+//!     "scale": Float (default 1),
+//!     "octaves": Unsigned Integer (default 7),
+//!     "distortion": Float (default 2 for marble, 0.2 for wood),
+//!     "seed": Unsigned Integer (default 1)
+//! }
+//! ```
 //! 
 //! ## shapes 
 //!
@@ -132,6 +504,29 @@
 //! }
 //! ```
 //!
+//! ### triangle
+//!
+//! ```
+//! {
+//!     "kind": "triangle",
+//!     "vertices": [Vec3, Vec3, Vec3],
+//!     "transform": Transform
+//! }
+//! ```
+//!
+//! ### mesh
+//!
+//! A triangle mesh loaded from a Wavefront OBJ or PLY (ASCII or binary) file, 
+//! relative to the directory of the scene file. Vertex normals and texture 
+//! coordinates in the file are used if present.
+//! ```
+//! {
+//!     "kind": "mesh",
+//!     "file": "meshes/bunny.ply",
+//!     "transform": Transform
+//! }
+//! ```
+//!
 //! ## transform
 //!
 //! For parsing from the scene file, the value of the field "transform". There are 
@@ -154,8 +549,8 @@
 //!
 //! ### simple sequence type
 //! 
-//! This is specified as a map of simple types, which are described below. It is 
-//! specified like
+//! This is specified as a list of simple types, which are described below and are 
+//! applied in the order given. It is specified like
 //! ```
 //! {
 //!     "simple sequence": [
@@ -165,6 +560,8 @@
 //!     ]
 //! }
 //! ```
+//! A single map holding the simple types, as older scene files have it, is also 
+//! accepted; its rotation is applied first, then its scale, then its translation.
 //! 
 //! The following are the simple types:
 //!
@@ -189,49 +586,396 @@
 //! #### scale
 //! ```
 //! {
-//!     "scale"
+//!     "scale": Vec3
 //! }
 //! ```
 
 use tracing::instrument;
 
-use crate::{scene::{Scene, SceneInfo}, utility::rng::RandomNumberGenerator};
-use self::{parse_error::ParseError, objects::ObjectParseInfo};
+use std::path::Path;
+
+use crate::{scene::{Scene, SceneInfo, CheckpointSchedule, AdaptiveSampling}, objects::object_group::ObjectGroup, aovs::AovKind, utility::math::float::Float};
+use self::{parse_error::ParseError, objects::ObjectParseInfo, prototypes::PrototypeParseInfo};
 
 mod camera;
 mod transform;
 mod objects;
+mod prototypes;
 mod parse_error;
 mod shape;
 mod textures;
 mod materials;
 mod integrator;
+mod acceleration_structure;
+mod environment;
+mod film;
+mod sampler;
+mod denoiser;
+mod obj_file;
+mod ply_file;
 
-pub fn parse_json(json: &serde_json::Value) -> Result<Scene, ParseError> {
+const OUTPUT_FIELD_NAME: &str = "output";
+const FILM_FIELD_NAME: &str = "film";
+const SAMPLER_FIELD_NAME: &str = "sampler";
+const ACCELERATION_STRUCTURE_FIELD_NAME: &str = "acceleration structure";
+const PROTOTYPES_FIELD_NAME: &str = "prototypes";
+const ENVIRONMENT_FIELD_NAME: &str = "environment";
+const BACKGROUND_COLOR_FIELD_NAME: &str = "background color";
+const CHECKPOINT_FIELD_NAME: &str = "checkpoint";
+const EVERY_SAMPLES_FIELD_NAME: &str = "every samples";
+const EVERY_SECONDS_FIELD_NAME: &str = "every seconds";
+const ADAPTIVE_SAMPLING_FIELD_NAME: &str = "adaptive sampling";
+const RELATIVE_ERROR_FIELD_NAME: &str = "relative error";
+const DEFAULT_RELATIVE_ERROR: Float = 0.01;
+const MIN_SAMPLES_FIELD_NAME: &str = "min samples";
+const DEFAULT_MIN_SAMPLES: u32 = 16;
+const MAX_SAMPLES_FIELD_NAME: &str = "max samples";
+/// The default maximum is this multiple of the number of samples.
+const DEFAULT_MAX_SAMPLES_FACTOR: u32 = 8;
+const SAMPLE_COUNT_MAP_FIELD_NAME: &str = "sample count map";
+const AOVS_FIELD_NAME: &str = "aovs";
+const DENOISER_FIELD_NAME: &str = "denoiser";
+/// Names the files meshes, textures and environment maps are loaded from.
+const FILE_FIELD_NAME: &str = "file";
+
+/// Builds the scene described by `json`. Relative paths in the scene file are 
+/// resolved against `scene_directory`, the directory containing the scene file.
+pub fn parse_json(json: &serde_json::Value, scene_directory: &Path) -> Result<Scene, ParseError> {
     let camera = camera::new_from_json(&json["camera"])?;
 
     let parsed_integrator = integrator::new_from_json(&json["integrator"])?;
 
     let objects = {
         let materials = materials::parse_json(&json["materials"])?;
-        let textures = textures::parse_json(&json["textures"])?;
+        let textures = textures::parse_json(&json["textures"], scene_directory)?;
+        let acceleration_structure = acceleration_structure::new_from_json(
+            &json[ACCELERATION_STRUCTURE_FIELD_NAME]
+        )?;
+
+        let prototype_info = PrototypeParseInfo {
+            json: &json[PROTOTYPES_FIELD_NAME],
+            textures: &textures,
+            materials: &materials,
+            scene_directory,
+            acceleration_structure: &acceleration_structure,
+        };
+        let prototypes = prototypes::parse_json(prototype_info)?;
         
         let info = ObjectParseInfo {
             json: &json["objects"],
             textures: &textures,
             materials: &materials,
+            prototypes: &prototypes,
+            scene_directory,
         };
-        objects::parse_json(info)?
+        let members_vector = objects::parse_json(info)?;
+        
+        ObjectGroup::new(members_vector, &acceleration_structure)
     };
 
+    let environment = environment::new_from_json(
+        &json[ENVIRONMENT_FIELD_NAME], 
+        &json[BACKGROUND_COLOR_FIELD_NAME], 
+        scene_directory
+    )?;
+
+    let film = film::new_from_json(&json[FILM_FIELD_NAME])?;
+    let sampler = sampler::new_from_json(
+        &json[SAMPLER_FIELD_NAME], 
+        parsed_integrator.seed, 
+        parsed_integrator.num_samples
+    )?;
+    let output_filename = get_output_filename(json, scene_directory)?;
+    let checkpoints = get_checkpoint_schedule(&json[CHECKPOINT_FIELD_NAME])?;
+    let adaptive_sampling = get_adaptive_sampling(
+        &json[ADAPTIVE_SAMPLING_FIELD_NAME], 
+        parsed_integrator.num_samples, 
+        scene_directory
+    )?;
+    let aov_outputs = get_aov_outputs(&json[AOVS_FIELD_NAME], scene_directory)?;
+    let denoiser = denoiser::new_from_json(&json[DENOISER_FIELD_NAME])?;
+
     let info = SceneInfo {
         camera,
         integrator: parsed_integrator.integrator,
         num_samples: parsed_integrator.num_samples,
-        recursive_depth_limit: parsed_integrator.recursion_limit,
-        rng: RandomNumberGenerator::from_seed(1),
+        seed: parsed_integrator.seed,
+        num_threads: parsed_integrator.num_threads,
+        spectral: parsed_integrator.spectral,
         objects,
+        environment,
+        filter: film.filter,
+        display: film.display,
+        sampler,
+        output_filename,
+        checkpoints,
+        adaptive_sampling,
+        aov_outputs,
+        denoiser,
     };
     Ok(Scene::new(info))
 }
 
+/// Identifies the image a scene file renders, so that a saved render is only resumed 
+/// with the scene it was started with. The number of samples and threads, the output 
+/// file, the checkpoints, the adaptive sampling settings, the AOVs, the denoiser and 
+/// the exposure and tone mapping are ignored, so a render can be resumed with more samples or a different 
+/// look. The contents of the files the scene loads (relative to `scene_directory`), 
+/// such as meshes and images, are hashed along with it.
+pub fn scene_hash(json: &serde_json::Value, scene_directory: &Path) -> u64 {
+    let mut json = json.clone();
+    if let Some(map) = json.as_object_mut() {
+        map.remove(OUTPUT_FIELD_NAME);
+        map.remove(CHECKPOINT_FIELD_NAME);
+        map.remove(ADAPTIVE_SAMPLING_FIELD_NAME);
+        map.remove(AOVS_FIELD_NAME);
+        map.remove(DENOISER_FIELD_NAME);
+        if let Some(integrator_json) = map.get_mut("integrator") {
+            integrator::remove_sampling_fields(integrator_json);
+        }
+        if let Some(film_json) = map.get_mut(FILM_FIELD_NAME) {
+            film::remove_display_fields(film_json);
+        }
+    }
+
+    // Maps are serialized with sorted keys, so the order of the fields in the file 
+    // does not matter. FNV-1a, unlike the standard library's hasher, is guaranteed 
+    // to be the same across builds.
+    let canonical = serde_json::to_string(&json).expect("a json value can be serialized");
+    let mut hash = fnv1a(0xcbf29ce484222325, canonical.as_bytes());
+
+    // A file that cannot be read fails to parse anyway.
+    for filename in loaded_files(&json) {
+        if let Ok(contents) = std::fs::read(scene_directory.join(filename)) {
+            hash = fnv1a(hash, &contents);
+        }
+    }
+
+    hash
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ (*byte as u64)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The names of the files the scene loads, i.e. the values of all "file" fields, in 
+/// the order `json` (with its maps' keys sorted) lists them.
+fn loaded_files(json: &serde_json::Value) -> Vec<&str> {
+    match json {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .flat_map(|(key, value)| match value.as_str() {
+                Some(filename) if key == FILE_FIELD_NAME => vec![filename],
+                _ => loaded_files(value),
+            })
+            .collect(),
+        serde_json::Value::Array(array) => array.iter().flat_map(loaded_files).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn get_output_filename(json: &serde_json::Value, scene_directory: &Path) -> Result<Option<String>, ParseError> {
+    match &json[OUTPUT_FIELD_NAME] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) => {
+            let path = scene_directory.join(s);
+            Ok(Some(path.to_string_lossy().into_owned()))
+        },
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' must be a string", OUTPUT_FIELD_NAME),
+                json: json[OUTPUT_FIELD_NAME].clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_checkpoint_schedule(json: &serde_json::Value) -> Result<CheckpointSchedule, ParseError> {
+    match json {
+        serde_json::Value::Null => { return Ok(CheckpointSchedule::default()); },
+        serde_json::Value::Object(_) => {},
+        _ => {
+            let pe = ParseError {
+                msg: format!("'{}' must be a json map", CHECKPOINT_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    }
+
+    let every_samples = match &json[EVERY_SAMPLES_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        value => match serde_json::from_value::<u32>(value.clone()) {
+            Ok(n) if n > 0 => Some(n),
+            _ => {
+                let pe = ParseError {
+                    msg: format!("field '{}' must be a positive integer", EVERY_SAMPLES_FIELD_NAME),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        },
+    };
+
+    let every_seconds = match &json[EVERY_SECONDS_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        value => match value.as_f64() {
+            Some(seconds) if seconds > 0.0 => Some(seconds as Float),
+            _ => {
+                let pe = ParseError {
+                    msg: format!("field '{}' must be a positive number", EVERY_SECONDS_FIELD_NAME),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        },
+    };
+
+    Ok(CheckpointSchedule { every_samples, every_seconds })
+}
+
+fn get_adaptive_sampling(
+    json: &serde_json::Value, 
+    num_samples: u32, 
+    scene_directory: &Path
+) -> Result<Option<AdaptiveSampling>, ParseError> {
+    match json {
+        serde_json::Value::Null => { return Ok(None); },
+        serde_json::Value::Object(_) => {},
+        _ => {
+            let pe = ParseError {
+                msg: format!("'{}' must be a json map", ADAPTIVE_SAMPLING_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    }
+
+    let relative_error = match &json[RELATIVE_ERROR_FIELD_NAME] {
+        serde_json::Value::Null => DEFAULT_RELATIVE_ERROR,
+        value => match value.as_f64() {
+            Some(relative_error) if relative_error > 0.0 => relative_error as Float,
+            _ => {
+                let pe = ParseError {
+                    msg: format!("field '{}' must be a positive number", RELATIVE_ERROR_FIELD_NAME),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        },
+    };
+
+    let get_samples = |field_name: &str, default: u32| -> Result<u32, ParseError> {
+        match &json[field_name] {
+            serde_json::Value::Null => Ok(default),
+            value => match serde_json::from_value::<u32>(value.clone()) {
+                Ok(n) if n > 0 => Ok(n),
+                _ => {
+                    let pe = ParseError {
+                        msg: format!("field '{}' must be a positive integer", field_name),
+                        json: json.clone(),
+                    };
+                    Err(pe)
+                }
+            },
+        }
+    };
+    let min_samples = get_samples(MIN_SAMPLES_FIELD_NAME, u32::min(DEFAULT_MIN_SAMPLES, num_samples))?;
+    let max_samples = get_samples(
+        MAX_SAMPLES_FIELD_NAME, 
+        num_samples.saturating_mul(DEFAULT_MAX_SAMPLES_FACTOR)
+    )?;
+    if min_samples > max_samples {
+        let pe = ParseError {
+            msg: format!("'{}' must not exceed '{}'", MIN_SAMPLES_FIELD_NAME, MAX_SAMPLES_FIELD_NAME),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    let sample_count_map = match &json[SAMPLE_COUNT_MAP_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(scene_directory.join(s).to_string_lossy().into_owned()),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' must be a string", SAMPLE_COUNT_MAP_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    Ok(Some(AdaptiveSampling { relative_error, min_samples, max_samples, sample_count_map }))
+}
+
+/// The AOVs to write and the files to write them to, in the order of `AovKind::ALL`.
+fn get_aov_outputs(json: &serde_json::Value, scene_directory: &Path) -> Result<Vec<(AovKind, String)>, ParseError> {
+    let map = match json {
+        serde_json::Value::Null => { return Ok(Vec::new()); },
+        serde_json::Value::Object(map) => map,
+        _ => {
+            let pe = ParseError {
+                msg: format!("'{}' must be a json map", AOVS_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    if let Some(name) = map.keys().find(|name| !AovKind::ALL.iter().any(|kind| kind.name() == *name)) {
+        let pe = ParseError {
+            msg: format!("unknown AOV '{}'", name),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    let mut to_return = Vec::new();
+    for kind in AovKind::ALL {
+        match &json[kind.name()] {
+            serde_json::Value::Null => {},
+            serde_json::Value::String(s) => {
+                to_return.push((kind, scene_directory.join(s).to_string_lossy().into_owned()));
+            },
+            _ => {
+                let pe = ParseError {
+                    msg: format!("value of field '{}' must be a string", kind.name()),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        }
+    }
+
+    Ok(to_return)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_hash_covers_loaded_files() {
+        let directory = std::env::temp_dir();
+        let filename = "mirth_scene_hash_test.obj";
+        let json = serde_json::json!({
+            "objects": [{ "shape": { "kind": "mesh", "file": filename } }],
+            "output": "a.png"
+        });
+        let triangle = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        std::fs::write(directory.join(filename), triangle).unwrap();
+        let hash = scene_hash(&json, &directory);
+
+        let mut other_output = json.clone();
+        other_output["output"] = serde_json::json!("b.png");
+        assert_eq!(scene_hash(&other_output, &directory), hash);
+
+        std::fs::write(directory.join(filename), triangle.replace("v 0 1 0", "v 0 2 0")).unwrap();
+        assert_ne!(scene_hash(&json, &directory), hash);
+
+        std::fs::remove_file(directory.join(filename)).unwrap();
+    }
+}
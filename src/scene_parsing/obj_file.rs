@@ -0,0 +1,186 @@
+//! Reads the geometry out of Wavefront OBJ files. Only the vertex data (`v`, `vt`, 
+//! `vn`) and faces (`f`) are used; materials, groups etc. are ignored. Polygons 
+//! are split into triangles as fans, so they should be convex.
+
+// S==== IMPORTS {{{1
+
+use std::collections::HashMap;
+use crate::{
+    objects::shapes::triangle_mesh::TriangleMeshData,
+    utility::math::{float::Float, vector::{Point3, Vec3}}
+};
+
+// E==== IMPORTS }}}1
+
+/// The indices (into the `v`, `vt` and `vn` lists) of one corner of a face. 
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+pub fn parse(contents: &str) -> Result<TriangleMeshData, String> {
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut uvs: Vec<(Float, Float)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut faces: Vec<Vec<FaceVertex>> = Vec::new();
+
+    for (line_index, line) in contents.lines().enumerate() {
+        let line_number = line_index + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => { continue; }
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let xyz = parse_floats(&arguments, 3, line_number)?;
+                positions.push(Point3::new(xyz[0], xyz[1], xyz[2]));
+            },
+            "vt" => {
+                let uv = parse_floats(&arguments, 2, line_number)?;
+                uvs.push((uv[0], uv[1]));
+            },
+            "vn" => {
+                let xyz = parse_floats(&arguments, 3, line_number)?;
+                normals.push(Vec3::new(xyz[0], xyz[1], xyz[2]));
+            },
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(format!("line {}: a face needs at least 3 vertices", line_number));
+                }
+                let face = arguments
+                    .iter()
+                    .map(|argument| parse_face_vertex(
+                        argument, (positions.len(), uvs.len(), normals.len()), line_number
+                    ))
+                    .collect::<Result<Vec<FaceVertex>, String>>()?;
+                faces.push(face);
+            },
+            // Comments and everything we do not support.
+            _ => {},
+        }
+    }
+
+    Ok(build_mesh(&positions, &uvs, &normals, &faces))
+}
+
+fn parse_floats(arguments: &[&str], count: usize, line_number: usize) -> Result<Vec<Float>, String> {
+    if arguments.len() < count {
+        return Err(format!("line {}: expected {} numbers", line_number, count));
+    }
+
+    arguments[..count]
+        .iter()
+        .map(|argument| argument
+            .parse::<Float>()
+            .map_err(|_| format!("line {}: could not parse number '{}'", line_number, argument)))
+        .collect()
+}
+
+/// Parses `v`, `v/vt`, `v//vn` or `v/vt/vn`. Indices start at 1, and negative indices 
+/// count back from the most recent vertex data, whose lengths are `counts`.
+fn parse_face_vertex(
+    argument: &str, 
+    counts: (usize, usize, usize), 
+    line_number: usize
+) -> Result<FaceVertex, String> {
+    let resolve = |index: &str, count: usize| -> Result<usize, String> {
+        let error = || format!("line {}: invalid index '{}'", line_number, index);
+        let parsed = index.parse::<i64>().map_err(|_| error())?;
+        let resolved = if parsed < 0 { count as i64 + parsed } else { parsed - 1 };
+        if resolved < 0 || resolved >= count as i64 {
+            return Err(error());
+        }
+        Ok(resolved as usize)
+    };
+
+    let mut parts = argument.split('/');
+    let position = resolve(parts.next().unwrap_or(""), counts.0)?;
+    let uv = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(resolve(index, counts.1)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(resolve(index, counts.2)?),
+    };
+
+    Ok((position, uv, normal))
+}
+
+/// OBJ indexes positions, texture coordinates and normals separately, whereas a 
+/// `TriangleMeshData` has one index per vertex. So every distinct combination 
+/// becomes a vertex. Texture coordinates and normals are only kept if every face 
+/// vertex has them.
+fn build_mesh(
+    positions: &[Point3], 
+    uvs: &[(Float, Float)], 
+    normals: &[Vec3], 
+    faces: &[Vec<FaceVertex>]
+) -> TriangleMeshData {
+    let use_uvs = faces.iter().flatten().all(|(_, uv, _)| uv.is_some());
+    let use_normals = faces.iter().flatten().all(|(_, _, normal)| normal.is_some());
+
+    let mut mesh = TriangleMeshData::default();
+    let mut vertex_indices: HashMap<FaceVertex, usize> = HashMap::new();
+    let mut vertex_index = |face_vertex: &FaceVertex, mesh: &mut TriangleMeshData| -> usize {
+        let (position, uv, normal) = *face_vertex;
+        let key = (
+            position, 
+            if use_uvs { uv } else { None }, 
+            if use_normals { normal } else { None }
+        );
+
+        *vertex_indices.entry(key).or_insert_with(|| {
+            mesh.positions.push(positions[position].clone());
+            if let (true, Some(uv)) = (use_uvs, uv) {
+                mesh.uvs.push(uvs[uv]);
+            }
+            if let (true, Some(normal)) = (use_normals, normal) {
+                mesh.normals.push(normals[normal].clone());
+            }
+            mesh.positions.len() - 1
+        })
+    };
+
+    for face in faces.iter() {
+        let first = vertex_index(&face[0], &mut mesh);
+        for window in face[1..].windows(2) {
+            let second = vertex_index(&window[0], &mut mesh);
+            let third = vertex_index(&window[1], &mut mesh);
+            mesh.triangles.push([first, second, third]);
+        }
+    }
+
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_quad_with_normals() {
+        let contents = "
+            # a unit square in the xy-plane
+            o square
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vt 1 1
+            vn 0 0 1
+            f 1/1/1 2/1/1 3/2/1 -1/2/-1
+        ";
+
+        let mesh = parse(contents).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.triangles.len(), 2);
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.normals.len(), 4);
+        assert_eq!(mesh.uvs.len(), 4);
+        assert!(Vec3::are_equal(&mesh.positions[mesh.triangles[1][2]], &Point3::new(0.0, 1.0, 0.0)));
+
+        assert!(parse("v 0 0 0\nf 1 2 3").is_err());
+    }
+}
@@ -1,43 +1,96 @@
-use std::rc::Rc;
+use std::{sync::Arc, path::Path};
 
-use crate::objects::{object::{Object, ObjectInfo}, object_group::ObjectGroup};
+use crate::objects::{
+    object::{Object, ObjectInfo},
+    object_group::ObjectGroupMember,
+    instance::{Instance, InstanceInfo}
+};
 
-use super::{shape, parse_error::ParseError, textures::TextureMap, materials::MaterialMap};
+use super::{
+    shape, 
+    transform,
+    parse_error::ParseError, 
+    textures::TextureMap, 
+    materials::MaterialMap, 
+    prototypes::PrototypeMap
+};
+
+const INSTANCE_FIELD_NAME: &str = "instance";
+const TRANSFORM_FIELD_NAME: &str = "transform";
+const MATERIAL_FIELD_NAME: &str = "material";
 
 pub struct ObjectParseInfo<'a> {
     pub json: &'a serde_json::Value,
     pub textures: &'a TextureMap, 
-    pub materials: &'a MaterialMap 
+    pub materials: &'a MaterialMap,
+    pub prototypes: &'a PrototypeMap,
+    /// Files (e.g. meshes) are looked for relative to this.
+    pub scene_directory: &'a Path,
 }
 
-pub fn parse_json(info: ObjectParseInfo) -> Result<ObjectGroup, ParseError> {
-    let mut objects_vector: Vec<Rc<Object>> = Vec::new();
+/// The objects and instances (see `Instance`) of the scene, in order.
+pub fn parse_json(info: ObjectParseInfo) -> Result<Vec<ObjectGroupMember>, ParseError> {
+    let mut members_vector: Vec<ObjectGroupMember> = Vec::new();
 
-    let json_array = match info.json {
-        serde_json::Value::Array(arr) => arr,
-        _ => {
+    for member in get_json_array(info.json)?.iter() {
+        let member_info = ObjectParseInfo {
+            json: member,
+            textures: info.textures,
+            materials: info.materials,
+            prototypes: info.prototypes,
+            scene_directory: info.scene_directory,
+        };
+        if member[INSTANCE_FIELD_NAME].is_null() {
+            members_vector.push(ObjectGroupMember::Object(Arc::new(new_object_from_json(member_info)?)));
+        } else {
+            members_vector.push(ObjectGroupMember::Instance(Box::new(new_instance_from_json(member_info)?)));
+        }
+    }
+
+    Ok(members_vector)
+}
+
+/// The objects of a prototype, which cannot contain instances.
+pub fn parse_prototype_json(info: ObjectParseInfo) -> Result<Vec<Arc<Object>>, ParseError> {
+    let mut objects_vector: Vec<Arc<Object>> = Vec::new();
+
+    for object in get_json_array(info.json)?.iter() {
+        if !object[INSTANCE_FIELD_NAME].is_null() {
             let pe = ParseError {
-                msg: "objects not listed as json array".to_string(),
-                json: info.json.clone(),
+                msg: "prototypes cannot contain instances".to_string(),
+                json: object.clone(),
             };
             return Err(pe);
         }
-    };
 
-    for object in json_array.iter() {
         let object_info = ObjectParseInfo {
-            json: &object,
+            json: object,
             textures: info.textures,
-            materials: info.materials
+            materials: info.materials,
+            prototypes: info.prototypes,
+            scene_directory: info.scene_directory,
         };
-        objects_vector.push(Rc::new(new_object_from_json(object_info)?));
+        objects_vector.push(Arc::new(new_object_from_json(object_info)?));
     }
 
-    Ok(ObjectGroup::new_from_vector(objects_vector))
+    Ok(objects_vector)
+}
+
+fn get_json_array(json: &serde_json::Value) -> Result<&Vec<serde_json::Value>, ParseError> {
+    match json {
+        serde_json::Value::Array(arr) => Ok(arr),
+        _ => {
+            let pe = ParseError {
+                msg: "objects not listed as json array".to_string(),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
 }
 
 fn new_object_from_json(info: ObjectParseInfo) -> Result<Object, ParseError> {
-    let shape = shape::new_from_json(&info.json["shape"])?;
+    let shape = shape::new_from_json(&info.json["shape"], info.scene_directory)?;
 
     let texture = match info.json["texture"].as_str() {
         Some(texture_name) => info.textures.get(texture_name)?,
@@ -50,7 +103,7 @@ fn new_object_from_json(info: ObjectParseInfo) -> Result<Object, ParseError> {
         }
     };
 
-    let material = match info.json["material"].as_str() {
+    let material = match info.json[MATERIAL_FIELD_NAME].as_str() {
         Some(material_name) => info.materials.get(material_name)?,
         None => {
             let pe = ParseError {
@@ -68,3 +121,38 @@ fn new_object_from_json(info: ObjectParseInfo) -> Result<Object, ParseError> {
     };
     Ok(Object::new(object_info))
 }
+
+fn new_instance_from_json(info: ObjectParseInfo) -> Result<Instance, ParseError> {
+    let prototype = match info.json[INSTANCE_FIELD_NAME].as_str() {
+        Some(prototype_name) => info.prototypes.get(prototype_name)?,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", INSTANCE_FIELD_NAME),
+                json: info.json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let transform = transform::new_from_json(&info.json[TRANSFORM_FIELD_NAME])?;
+
+    // Unlike for an object, the material is optional.
+    let material = match &info.json[MATERIAL_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(material_name) => Some(info.materials.get(material_name)?),
+        _ => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", MATERIAL_FIELD_NAME),
+                json: info.json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let instance_info = InstanceInfo {
+        prototype,
+        transform,
+        material,
+    };
+    Ok(Instance::new(instance_info))
+}
@@ -0,0 +1,385 @@
+//! Reads the geometry out of PLY (Stanford polygon) files, in either the ASCII or 
+//! the binary encodings. We use the vertex properties `x`, `y`, `z`, the normals 
+//! `nx`, `ny`, `nz` and the texture coordinates `u`, `v` (also called `s`, `t` or 
+//! `texture_u`, `texture_v`), and the face list `vertex_indices` (or 
+//! `vertex_index`). Polygons are split into triangles as fans. Other elements and 
+//! properties are skipped.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::triangle_mesh::TriangleMeshData,
+    utility::math::{float::Float, vector::{Point3, Vec3}}
+};
+
+// E==== IMPORTS }}}1
+
+const END_OF_HEADER: &str = "end_header";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+enum PropertyType {
+    Scalar(ScalarType),
+    /// A count of type `.0` followed by that many values of type `.1`.
+    List(ScalarType, ScalarType),
+}
+
+struct Property {
+    name: String,
+    kind: PropertyType,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+pub fn parse(contents: &[u8]) -> Result<TriangleMeshData, String> {
+    let (encoding, elements, body_start) = parse_header(contents)?;
+    let mut body = Body {
+        encoding,
+        bytes: &contents[body_start..],
+        position: 0,
+    };
+
+    let mut mesh = TriangleMeshData::default();
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut body, element, &mut mesh)?,
+            "face" => read_faces(&mut body, element, &mut mesh)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        body.read_property(&property.kind)?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(mesh)
+}
+
+// S==== HEADER {{{1
+
+/// Returns the encoding, the elements declared and where the body starts.
+fn parse_header(contents: &[u8]) -> Result<(Encoding, Vec<Element>, usize), String> {
+    if !contents.starts_with(b"ply") {
+        return Err("not a PLY file".to_string());
+    }
+
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut position = 0;
+
+    loop {
+        let line_end = match contents[position..].iter().position(|&byte| byte == b'\n') {
+            Some(offset) => position + offset,
+            None => { return Err(format!("header does not end with '{}'", END_OF_HEADER)); }
+        };
+        let line = std::str::from_utf8(&contents[position..line_end])
+            .map_err(|_| "header is not valid text".to_string())?
+            .trim();
+        position = line_end + 1;
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["ply"] | [] => {},
+            ["comment", ..] | ["obj_info", ..] => {},
+            ["format", format, _version] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::BinaryLittleEndian,
+                    "binary_big_endian" => Encoding::BinaryBigEndian,
+                    other => { return Err(format!("unknown format '{}'", other)); }
+                });
+            },
+            ["element", name, count] => {
+                let count = count
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid element count '{}'", count))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            },
+            ["property", "list", count_type, item_type, name] => {
+                let kind = PropertyType::List(parse_scalar_type(count_type)?, parse_scalar_type(item_type)?);
+                add_property(&mut elements, name, kind)?;
+            },
+            ["property", scalar_type, name] => {
+                let kind = PropertyType::Scalar(parse_scalar_type(scalar_type)?);
+                add_property(&mut elements, name, kind)?;
+            },
+            [END_OF_HEADER] => { break; },
+            _ => { return Err(format!("invalid header line '{}'", line)); }
+        }
+    }
+
+    match encoding {
+        Some(encoding) => Ok((encoding, elements, position)),
+        None => Err("header does not specify the format".to_string()),
+    }
+}
+
+fn add_property(elements: &mut [Element], name: &str, kind: PropertyType) -> Result<(), String> {
+    match elements.last_mut() {
+        Some(element) => {
+            element.properties.push(Property { name: name.to_string(), kind });
+            Ok(())
+        },
+        None => Err(format!("property '{}' does not belong to an element", name)),
+    }
+}
+
+fn parse_scalar_type(name: &str) -> Result<ScalarType, String> {
+    match name {
+        "char" | "int8" => Ok(ScalarType::Int8),
+        "uchar" | "uint8" => Ok(ScalarType::UInt8),
+        "short" | "int16" => Ok(ScalarType::Int16),
+        "ushort" | "uint16" => Ok(ScalarType::UInt16),
+        "int" | "int32" => Ok(ScalarType::Int32),
+        "uint" | "uint32" => Ok(ScalarType::UInt32),
+        "float" | "float32" => Ok(ScalarType::Float32),
+        "double" | "float64" => Ok(ScalarType::Float64),
+        other => Err(format!("unknown property type '{}'", other)),
+    }
+}
+
+// E==== HEADER }}}1
+
+// S==== BODY {{{1
+
+struct Body<'a> {
+    encoding: Encoding,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Body<'a> {
+    fn read_scalar(&mut self, scalar_type: ScalarType) -> Result<f64, String> {
+        match self.encoding {
+            Encoding::Ascii => self.read_ascii_scalar(),
+            Encoding::BinaryLittleEndian | Encoding::BinaryBigEndian => self.read_binary_scalar(scalar_type),
+        }
+    }
+
+    /// Reads a scalar, or a list as a vector of its items.
+    fn read_property(&mut self, kind: &PropertyType) -> Result<Vec<f64>, String> {
+        match kind {
+            PropertyType::Scalar(scalar_type) => Ok(vec![self.read_scalar(*scalar_type)?]),
+            PropertyType::List(count_type, item_type) => {
+                let count = self.read_scalar(*count_type)?;
+                if count < 0.0 {
+                    return Err("negative list length".to_string());
+                }
+                (0..count as usize)
+                    .map(|_| self.read_scalar(*item_type))
+                    .collect()
+            }
+        }
+    }
+
+    fn read_ascii_scalar(&mut self) -> Result<f64, String> {
+        let rest = &self.bytes[self.position..];
+        let start = match rest.iter().position(|byte| !byte.is_ascii_whitespace()) {
+            Some(start) => start,
+            None => { return Err("unexpected end of file".to_string()); }
+        };
+        let length = rest[start..]
+            .iter()
+            .position(|byte| byte.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+        self.position += start + length;
+
+        let token = String::from_utf8_lossy(&rest[start..start + length]);
+        token.parse::<f64>().map_err(|_| format!("could not parse number '{}'", token))
+    }
+
+    fn read_binary_scalar(&mut self, scalar_type: ScalarType) -> Result<f64, String> {
+        let size = match scalar_type {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        };
+        if self.position + size > self.bytes.len() {
+            return Err("unexpected end of file".to_string());
+        }
+
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(&self.bytes[self.position..self.position + size]);
+        self.position += size;
+        // Convert to little endian.
+        if self.encoding == Encoding::BinaryBigEndian {
+            buffer[..size].reverse();
+        }
+
+        let value = match scalar_type {
+            ScalarType::Int8 => i8::from_le_bytes([buffer[0]]) as f64,
+            ScalarType::UInt8 => buffer[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(buffer),
+        };
+        Ok(value)
+    }
+}
+
+fn read_vertices(body: &mut Body, element: &Element, mesh: &mut TriangleMeshData) -> Result<(), String> {
+    let find = |names: &[&str]| element.properties
+        .iter()
+        .position(|property| names.contains(&property.name.as_str()));
+    let position_indices = [find(&["x"]), find(&["y"]), find(&["z"])];
+    let normal_indices = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+    let uv_indices = [find(&["u", "s", "texture_u", "texture_s"]), find(&["v", "t", "texture_v", "texture_t"])];
+
+    let [Some(x), Some(y), Some(z)] = position_indices else {
+        return Err("vertices must have the properties 'x', 'y' and 'z'".to_string());
+    };
+    let normals = match normal_indices {
+        [Some(nx), Some(ny), Some(nz)] => Some((nx, ny, nz)),
+        _ => None,
+    };
+    let uvs = match uv_indices {
+        [Some(u), Some(v)] => Some((u, v)),
+        _ => None,
+    };
+
+    for _ in 0..element.count {
+        // The first value of each property (lists are not meaningful here).
+        let values = element.properties
+            .iter()
+            .map(|property| Ok(body.read_property(&property.kind)?.first().copied().unwrap_or(0.0)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let value = |index: usize| values[index] as Float;
+
+        mesh.positions.push(Point3::new(value(x), value(y), value(z)));
+        if let Some((nx, ny, nz)) = normals {
+            mesh.normals.push(Vec3::new(value(nx), value(ny), value(nz)));
+        }
+        if let Some((u, v)) = uvs {
+            mesh.uvs.push((value(u), value(v)));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_faces(body: &mut Body, element: &Element, mesh: &mut TriangleMeshData) -> Result<(), String> {
+    let indices_property = element.properties
+        .iter()
+        .position(|property| {
+            matches!(property.kind, PropertyType::List(..))
+            && (property.name == "vertex_indices" || property.name == "vertex_index")
+        });
+    let Some(indices_property) = indices_property else {
+        return Err("faces must have the list property 'vertex_indices'".to_string());
+    };
+
+    for _ in 0..element.count {
+        for (property_index, property) in element.properties.iter().enumerate() {
+            let values = body.read_property(&property.kind)?;
+            if property_index != indices_property {
+                continue;
+            }
+
+            if values.len() < 3 {
+                return Err("a face needs at least 3 vertices".to_string());
+            }
+            if values.iter().any(|&index| index < 0.0) {
+                return Err("negative vertex index".to_string());
+            }
+            let indices: Vec<usize> = values.iter().map(|&index| index as usize).collect();
+            for window in indices[1..].windows(2) {
+                mesh.triangles.push([indices[0], window[0], window[1]]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// E==== BODY }}}1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER_AFTER_FORMAT: &str = "\
+comment a unit square in the xy-plane
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+element face 1
+property uchar intensity
+property list uchar int vertex_indices
+end_header
+";
+
+    const POSITIONS: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+
+    fn binary_file(big_endian: bool) -> Vec<u8> {
+        let format = if big_endian { "binary_big_endian" } else { "binary_little_endian" };
+        let mut bytes = format!("ply\nformat {} 1.0\n{}", format, HEADER_AFTER_FORMAT).into_bytes();
+
+        let push_f32 = |bytes: &mut Vec<u8>, x: f32| {
+            bytes.extend(if big_endian { x.to_be_bytes() } else { x.to_le_bytes() });
+        };
+        for position in POSITIONS.iter() {
+            for &x in position.iter() { push_f32(&mut bytes, x); }
+            for x in [0.0, 0.0, 1.0] { push_f32(&mut bytes, x); }
+        }
+        bytes.extend([7u8, 4u8]);
+        for index in 0..4i32 {
+            bytes.extend(if big_endian { index.to_be_bytes() } else { index.to_le_bytes() });
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let ascii = format!(
+            "ply\nformat ascii 1.0\n{}{}\n7 4 0 1 2 3\n",
+            HEADER_AFTER_FORMAT,
+            POSITIONS.iter()
+                .map(|p| format!("{} {} {} 0 0 1", p[0], p[1], p[2]))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+
+        for contents in [ascii.into_bytes(), binary_file(false), binary_file(true)] {
+            let mesh = parse(&contents).unwrap();
+            assert!(mesh.validate().is_ok());
+            assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+            assert_eq!(mesh.normals.len(), 4);
+            assert!(mesh.uvs.is_empty());
+            for (parsed, expected) in mesh.positions.iter().zip(POSITIONS.iter()) {
+                assert!(Vec3::are_equal(parsed, &Point3::new(expected[0] as Float, expected[1] as Float, expected[2] as Float)));
+            }
+        }
+    }
+}
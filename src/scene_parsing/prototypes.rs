@@ -0,0 +1,98 @@
+// S==== IMPORTS {{{1
+
+use std::{sync::Arc, collections::HashMap, path::Path};
+use crate::{config::AccelerationStructure, objects::instance::Prototype};
+use super::{
+    parse_error::ParseError, 
+    objects::{self, ObjectParseInfo}, 
+    textures::TextureMap, 
+    materials::MaterialMap
+};
+
+// E==== IMPORTS }}}1
+
+const NAME_FIELD_NAME: &str = "name";
+const OBJECTS_FIELD_NAME: &str = "objects";
+
+#[derive(Default)]
+pub struct PrototypeMap {
+    map: HashMap<String, Arc<Prototype>>
+}
+
+impl PrototypeMap {
+    pub fn get(&self, key: &str) -> Result<Arc<Prototype>, ParseError> {
+        match self.map.get(key) {
+            Some(val) => Ok(val.clone()),
+            None => {
+                let pe = ParseError {
+                    msg: format!("no prototype named '{}'", key),
+                    json: serde_json::Value::Null,
+                };
+                Err(pe)
+            }
+        }
+    }
+}
+
+pub struct PrototypeParseInfo<'a> {
+    pub json: &'a serde_json::Value,
+    pub textures: &'a TextureMap,
+    pub materials: &'a MaterialMap,
+    /// Files (e.g. meshes) are looked for relative to this.
+    pub scene_directory: &'a Path,
+    /// Built once for each prototype, and shared by its instances.
+    pub acceleration_structure: &'a AccelerationStructure,
+}
+
+/// The field is optional: if it is missing, there are no prototypes.
+pub fn parse_json(info: PrototypeParseInfo) -> Result<PrototypeMap, ParseError> {
+    let json_array = match info.json {
+        serde_json::Value::Null => { return Ok(PrototypeMap::default()); },
+        serde_json::Value::Array(arr) => arr,
+        _ => {
+            let pe = ParseError {
+                msg: "prototypes not listed as json array".to_string(),
+                json: info.json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let mut to_return: HashMap<String, Arc<Prototype>> = HashMap::new();
+    for prototype in json_array.iter() {
+        let name = match prototype[NAME_FIELD_NAME].as_str() {
+            Some(name) => name,
+            None => {
+                let pe = ParseError {
+                    msg: format!("could not parse field '{}' of prototype", NAME_FIELD_NAME),
+                    json: prototype.clone(),
+                };
+                return Err(pe);
+            }
+        };
+
+        let object_info = ObjectParseInfo {
+            json: &prototype[OBJECTS_FIELD_NAME],
+            textures: info.textures,
+            materials: info.materials,
+            // Prototypes cannot contain instances, so there are none to refer to.
+            prototypes: &PrototypeMap::default(),
+            scene_directory: info.scene_directory,
+        };
+        let objects = objects::parse_prototype_json(object_info)?;
+        if objects.is_empty() {
+            let pe = ParseError {
+                msg: format!("prototype '{}' has no objects", name),
+                json: prototype.clone(),
+            };
+            return Err(pe);
+        }
+
+        let prototype = Prototype::new(&objects, info.acceleration_structure);
+        to_return.insert(name.to_string(), Arc::new(prototype));
+    }
+
+    Ok(PrototypeMap {
+        map: to_return
+    })
+}
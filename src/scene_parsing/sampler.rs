@@ -0,0 +1,51 @@
+// S==== IMPORTS {{{1
+
+use crate::samplers::{
+    traits::SamplerLike,
+    independent::IndependentSampler,
+    stratified::StratifiedSampler,
+    halton::HaltonSampler,
+    sobol::SobolSampler
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const INDEPENDENT_KIND: &str = "independent";
+const STRATIFIED_KIND: &str = "stratified";
+const HALTON_KIND: &str = "halton";
+const SOBOL_KIND: &str = "sobol";
+
+/// Parses the value of the "sampler" field; without one, the sampler is 
+/// independent. Some samplers are laid out for `num_samples` samples per pixel.
+pub fn new_from_json(json: &serde_json::Value, seed: u32, num_samples: u32) -> Result<Box<dyn SamplerLike>, ParseError> {
+    if json.is_null() {
+        return Ok(Box::new(IndependentSampler::new(seed)));
+    }
+
+    let kind = match json[KIND_FIELD_NAME].as_str() {
+        Some(kind) => kind,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of sampler", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind {
+        INDEPENDENT_KIND => Ok(Box::new(IndependentSampler::new(seed))),
+        STRATIFIED_KIND => Ok(Box::new(StratifiedSampler::new(seed, num_samples))),
+        HALTON_KIND => Ok(Box::new(HaltonSampler::new(seed))),
+        SOBOL_KIND => Ok(Box::new(SobolSampler::new(seed, num_samples))),
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown sampler kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
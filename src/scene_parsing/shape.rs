@@ -1,13 +1,15 @@
 
 // S==== IMPORTS {{{1
 
-use std::rc::Rc;
+use std::{sync::Arc, path::Path, fs};
 
 use crate::{
     objects::shapes::{
         traits::ShapeLike, 
         quad::Quad, 
         sphere::{Sphere, SphereInfo}, 
+        triangle::{Triangle, TriangleInfo},
+        triangle_mesh::{TriangleMesh, TriangleMeshInfo, TriangleMeshData},
     }, 
     utility::math::{
         float::Float, 
@@ -17,7 +19,9 @@ use crate::{
 
 use super::{
     parse_error::ParseError, 
-    transform
+    transform,
+    obj_file,
+    ply_file
 };
 
 // E==== IMPORTS }}}1
@@ -25,12 +29,17 @@ use super::{
 const KIND_FIELD_NAME: &str = "kind";
 const QUAD_KIND: &str = "quad";
 const SPHERE_KIND: &str = "sphere";
+const TRIANGLE_KIND: &str = "triangle";
+const MESH_KIND: &str = "mesh";
 
-pub fn new_from_json(json: &serde_json::Value) -> Result<Rc<dyn ShapeLike>, ParseError> {
+/// Files referred to by the shape are looked for relative to `scene_directory`.
+pub fn new_from_json(json: &serde_json::Value, scene_directory: &Path) -> Result<Arc<dyn ShapeLike>, ParseError> {
     let kind_name = get_kind_name(json)?;
     match kind_name.as_str() {
-        QUAD_KIND => Ok(Rc::new(new_quad_from_json(json)?)),
-        SPHERE_KIND => Ok(Rc::new(new_sphere_from_json(json)?)),
+        QUAD_KIND => Ok(Arc::new(new_quad_from_json(json)?)),
+        SPHERE_KIND => Ok(Arc::new(new_sphere_from_json(json)?)),
+        TRIANGLE_KIND => Ok(Arc::new(new_triangle_from_json(json)?)),
+        MESH_KIND => Ok(Arc::new(new_mesh_from_json(json, scene_directory)?)),
         other => { 
             let pe = ParseError {
                 msg: format!("invalid shape kind '{}'", other),
@@ -143,3 +152,84 @@ fn new_sphere_from_json(json: &serde_json::Value) -> Result<Sphere, ParseError>
 
 // E==== SPHERE }}}1
 
+// S==== TRIANGLE {{{1
+
+fn new_triangle_from_json(json: &serde_json::Value) -> Result<Triangle, ParseError> {
+    let vertices = match serde_json::from_value::<[Vec3; 3]>(json["vertices"].clone()) {
+        Ok(v) => v,
+        Err(_) => {
+            let pe = ParseError {
+                msg: "could not parse field 'vertices'".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let transform = transform::new_from_json(&json["transform"])?;
+
+    let triangle_info = TriangleInfo {
+        vertices,
+        transform,
+    };
+    Ok(Triangle::new(triangle_info))
+}
+
+// E==== TRIANGLE }}}1
+
+// S==== MESH {{{1
+
+fn new_mesh_from_json(json: &serde_json::Value, scene_directory: &Path) -> Result<TriangleMesh, ParseError> {
+    let filename = match json["file"].as_str() {
+        Some(f) => f,
+        None => {
+            let pe = ParseError {
+                msg: "could not parse field 'file'".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let data = match load_mesh_file(&scene_directory.join(filename)) {
+        Ok(d) => d,
+        Err(msg) => {
+            let pe = ParseError {
+                msg: format!("could not load mesh '{}': {}", filename, msg),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let transform = transform::new_from_json(&json["transform"])?;
+
+    let mesh_info = TriangleMeshInfo {
+        data,
+        transform,
+    };
+    Ok(TriangleMesh::new(mesh_info))
+}
+
+/// Loads an OBJ or PLY file, deciding which by the extension.
+fn load_mesh_file(path: &Path) -> Result<TriangleMeshData, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let contents = fs::read(path).map_err(|e| e.to_string())?;
+
+    let data = match extension.as_deref() {
+        Some("obj") => obj_file::parse(&String::from_utf8_lossy(&contents))?,
+        Some("ply") => ply_file::parse(&contents)?,
+        _ => { return Err("unsupported mesh format (expected '.obj' or '.ply')".to_string()); }
+    };
+
+    data.validate()?;
+    if data.triangles.is_empty() {
+        return Err("the mesh has no faces".to_string());
+    }
+    Ok(data)
+}
+
+// E==== MESH }}}1
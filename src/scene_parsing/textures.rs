@@ -1,9 +1,19 @@
 
 // S==== IMPORTS {{{1
 
-use std::{collections::HashMap, rc::Rc, fmt::format};
+use std::{collections::HashMap, sync::Arc, fmt::format, path::Path};
 use tracing::error;
-use crate::{utility::math::vector::Color3, objects::textures::{traits::TextureLike, constant::ConstantTexture}};
+use crate::{
+    utility::math::{vector::Color3, float::Float}, 
+    objects::textures::{
+        traits::TextureLike, 
+        constant::ConstantTexture,
+        image_texture::{ImageTexture, TextureFilter, TextureWrap, TextureColorSpace},
+        checker::{CheckerTexture, CheckerTextureInfo, CheckerDomain},
+        noise::{NoiseTexture, NoiseTextureInfo, NoisePattern}
+    }, 
+    light::ColorConstantsQueryable
+};
 
 use super::parse_error::ParseError;
 
@@ -13,15 +23,43 @@ const NAME_FIELD_NAME: &str = "name";
 
 const KIND_FIELD_NAME: &str = "kind";
 const CONSTANT_KIND: &str = "constant";
+const IMAGE_KIND: &str = "image";
+const CHECKER_KIND: &str = "checker";
+const NOISE_KIND: &str = "noise";
+const TURBULENCE_KIND: &str = "turbulence";
+const MARBLE_KIND: &str = "marble";
+const WOOD_KIND: &str = "wood";
 
 const RGB_FIELD_NAME: &str = "rgb color";
 
+const FILE_FIELD_NAME: &str = "file";
+const FILTER_FIELD_NAME: &str = "filter";
+const WRAP_FIELD_NAME: &str = "wrap";
+const COLOR_SPACE_FIELD_NAME: &str = "color space";
+
+const EVEN_FIELD_NAME: &str = "even";
+const ODD_FIELD_NAME: &str = "odd";
+const DOMAIN_FIELD_NAME: &str = "domain";
+
+const LOW_FIELD_NAME: &str = "low";
+const HIGH_FIELD_NAME: &str = "high";
+const OCTAVES_FIELD_NAME: &str = "octaves";
+const DEFAULT_OCTAVES: u32 = 7;
+const DISTORTION_FIELD_NAME: &str = "distortion";
+const DEFAULT_MARBLE_DISTORTION: Float = 2.0;
+const DEFAULT_WOOD_DISTORTION: Float = 0.2;
+const SEED_FIELD_NAME: &str = "seed";
+const DEFAULT_SEED: u32 = 1;
+
+const SCALE_FIELD_NAME: &str = "scale";
+const DEFAULT_SCALE: Float = 1.0;
+
 pub struct TextureMap {
-    map: HashMap<String, Rc<dyn TextureLike>>
+    map: HashMap<String, Arc<dyn TextureLike>>
 }
 
 impl TextureMap {
-    pub fn get(&self, key: &str) -> Result<Rc<dyn TextureLike>, ParseError> {
+    pub fn get(&self, key: &str) -> Result<Arc<dyn TextureLike>, ParseError> {
         match self.map.get(key) {
             Some(val) => Ok(val.clone()),
             None => {
@@ -35,7 +73,8 @@ impl TextureMap {
     }
 }
 
-pub fn parse_json(json: &serde_json::Value) -> Result<TextureMap, ParseError> {
+/// Image files are looked for relative to `scene_directory`.
+pub fn parse_json(json: &serde_json::Value, scene_directory: &Path) -> Result<TextureMap, ParseError> {
     let json_array: &Vec<serde_json::Value> = match json {
         serde_json::Value::Array(arr) => arr,
         _ => {
@@ -47,23 +86,34 @@ pub fn parse_json(json: &serde_json::Value) -> Result<TextureMap, ParseError> {
         }
     };   
 
-    let mut to_return: HashMap<String, Rc<dyn TextureLike>> = HashMap::new();
+    // Textures may refer to those listed before them, so we add them to the map as 
+    // we go.
+    let mut to_return = TextureMap {
+        map: HashMap::new()
+    };
     for texture in json_array.iter() {
-        let result = parse_single_texture(&texture)?;
-        to_return.insert(result.0, result.1);
+        let result = parse_single_texture(&texture, scene_directory, &to_return)?;
+        to_return.map.insert(result.0, result.1);
     }
 
-    Ok(TextureMap {
-        map: to_return
-    })
+    Ok(to_return)
 }
 
-fn parse_single_texture(json: &serde_json::Value) -> Result<(String, Rc<dyn TextureLike>), ParseError> {
+fn parse_single_texture(
+    json: &serde_json::Value, 
+    scene_directory: &Path, 
+    previous_textures: &TextureMap
+) -> Result<(String, Arc<dyn TextureLike>), ParseError> {
     let name = get_name(json)?;
 
     let kind_name = get_kind_name(json)?; 
     match kind_name.as_str() {
         CONSTANT_KIND => { return Ok((name.to_owned(), parse_constant_texture(json)?)) },
+        IMAGE_KIND => Ok((name.to_owned(), parse_image_texture(json, scene_directory)?)),
+        CHECKER_KIND => Ok((name.to_owned(), parse_checker_texture(json, previous_textures)?)),
+        NOISE_KIND | TURBULENCE_KIND | MARBLE_KIND | WOOD_KIND => {
+            Ok((name.to_owned(), parse_noise_texture(json, kind_name.as_str(), previous_textures)?))
+        },
         other => {
             let pe = ParseError {
                 msg: format!("unknown texture kind '{}'", other), 
@@ -114,7 +164,7 @@ fn get_kind_name(json: &serde_json::Value) -> Result<String, ParseError> {
     };
 }
 
-fn parse_constant_texture(json: &serde_json::Value) -> Result<Rc<ConstantTexture>, ParseError> {
+fn parse_constant_texture(json: &serde_json::Value) -> Result<Arc<ConstantTexture>, ParseError> {
     let rgb_color = match serde_json::from_value::<Color3>(json[RGB_FIELD_NAME].clone()) {
         Ok(c) => c,
         Err(_) => {
@@ -127,7 +177,188 @@ fn parse_constant_texture(json: &serde_json::Value) -> Result<Rc<ConstantTexture
     };
 
     let texture = ConstantTexture::new_from_rgb(rgb_color);
-    Ok(Rc::new(texture))
+    Ok(Arc::new(texture))
+}
+
+fn parse_image_texture(json: &serde_json::Value, scene_directory: &Path) -> Result<Arc<ImageTexture>, ParseError> {
+    let filename = match json[FILE_FIELD_NAME].as_str() {
+        Some(f) => f,
+        None => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of image texture", FILE_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let filter = match get_optional_string(json, FILTER_FIELD_NAME)? {
+        None | Some("bilinear") => TextureFilter::Bilinear,
+        Some("nearest") => TextureFilter::Nearest,
+        Some(other) => {
+            let pe = ParseError {
+                msg: format!("unknown texture filter '{}'", other),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let wrap = match get_optional_string(json, WRAP_FIELD_NAME)? {
+        None | Some("repeat") => TextureWrap::Repeat,
+        Some("clamp") => TextureWrap::Clamp,
+        Some("mirror") => TextureWrap::Mirror,
+        Some(other) => {
+            let pe = ParseError {
+                msg: format!("unknown texture wrap mode '{}'", other),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let color_space = match get_optional_string(json, COLOR_SPACE_FIELD_NAME)? {
+        None | Some("srgb") => TextureColorSpace::Srgb,
+        Some("linear") => TextureColorSpace::Linear,
+        Some(other) => {
+            let pe = ParseError {
+                msg: format!("unknown color space '{}'", other),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match ImageTexture::new_from_file(&scene_directory.join(filename), filter, wrap, color_space) {
+        Ok(texture) => Ok(Arc::new(texture)),
+        Err(msg) => {
+            let pe = ParseError {
+                msg: format!("could not load image '{}': {}", filename, msg),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_checker_texture(json: &serde_json::Value, previous_textures: &TextureMap) -> Result<Arc<CheckerTexture>, ParseError> {
+    let even = get_nested_texture(json, EVEN_FIELD_NAME, previous_textures)?;
+    let odd = get_nested_texture(json, ODD_FIELD_NAME, previous_textures)?;
+    let scale = get_optional_number(json, SCALE_FIELD_NAME)?.unwrap_or(DEFAULT_SCALE);
+
+    let domain = match get_optional_string(json, DOMAIN_FIELD_NAME)? {
+        None | Some("surface") => CheckerDomain::Surface,
+        Some("solid") => CheckerDomain::Solid,
+        Some(other) => {
+            let pe = ParseError {
+                msg: format!("unknown checker domain '{}'", other),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let info = CheckerTextureInfo {
+        even,
+        odd,
+        scale,
+        domain,
+    };
+    Ok(Arc::new(CheckerTexture::new(info)))
+}
+
+fn parse_noise_texture(
+    json: &serde_json::Value, 
+    kind_name: &str, 
+    previous_textures: &TextureMap
+) -> Result<Arc<NoiseTexture>, ParseError> {
+    let low = match json.get(LOW_FIELD_NAME) {
+        Some(_) => get_nested_texture(json, LOW_FIELD_NAME, previous_textures)?,
+        None => Arc::new(ConstantTexture::new_from_rgb(Color3::black())),
+    };
+    let high = match json.get(HIGH_FIELD_NAME) {
+        Some(_) => get_nested_texture(json, HIGH_FIELD_NAME, previous_textures)?,
+        None => Arc::new(ConstantTexture::new_from_rgb(Color3::white())),
+    };
+    let scale = get_optional_number(json, SCALE_FIELD_NAME)?.unwrap_or(DEFAULT_SCALE);
+    let octaves = get_optional_number(json, OCTAVES_FIELD_NAME)?
+        .map(|o| o as u32)
+        .unwrap_or(DEFAULT_OCTAVES);
+    let distortion = get_optional_number(json, DISTORTION_FIELD_NAME)?;
+    let seed = get_optional_number(json, SEED_FIELD_NAME)?
+        .map(|s| s as u32)
+        .unwrap_or(DEFAULT_SEED);
+
+    let pattern = match kind_name {
+        TURBULENCE_KIND => NoisePattern::Turbulence { octaves },
+        MARBLE_KIND => NoisePattern::Marble { 
+            octaves, 
+            distortion: distortion.unwrap_or(DEFAULT_MARBLE_DISTORTION) 
+        },
+        WOOD_KIND => NoisePattern::Wood { 
+            octaves, 
+            distortion: distortion.unwrap_or(DEFAULT_WOOD_DISTORTION) 
+        },
+        _ => NoisePattern::Noise,
+    };
+
+    let info = NoiseTextureInfo {
+        low,
+        high,
+        pattern,
+        scale,
+        seed,
+    };
+    Ok(Arc::new(NoiseTexture::new(info)))
+}
+
+/// The texture named by the value of `field_name`, which must have been listed 
+/// before the texture referring to it.
+fn get_nested_texture(
+    json: &serde_json::Value, 
+    field_name: &str, 
+    previous_textures: &TextureMap
+) -> Result<Arc<dyn TextureLike>, ParseError> {
+    match get_optional_string(json, field_name)? {
+        Some(name) => previous_textures.get(name),
+        None => {
+            let pe = ParseError {
+                msg: format!("could not find required field '{}' in texture", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_optional_number(json: &serde_json::Value, field_name: &str) -> Result<Option<Float>, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Number(n) if n.as_f64().is_some_and(|n| n >= 0.0) => {
+            Ok(n.as_f64().map(|n| n as Float))
+        },
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in texture must be a nonnegative number", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_optional_string<'a>(json: &'a serde_json::Value, field_name: &str) -> Result<Option<&'a str>, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) => Ok(Some(s.as_str())),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in texture must be a string", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
 }
 
 // S==== TESTS {{{1
@@ -102,77 +102,126 @@ fn new_for_viewer_from_json(json: &serde_json::Value) -> Result<Transform, Parse
 }
 
 fn new_from_simple_sequence_json(json: &serde_json::Value) -> Result<Transform, ParseError> {
-    // Convert json to map
-    let map: serde_json::Map<String, serde_json::Value>;
-    if let serde_json::Value::Object(obj) = json {
-        map = obj.clone();
-    } else {
-        let parse_error = ParseError {
-            msg: "json passed to `new_from_simple_sequence_json()` is not a map".to_string(),
-            json: json.clone()
-        };
-        return Err(parse_error);
-    }
+    // Older scene files give the simple transforms as one map, which (being a map)
+    // applies them in the order of its keys: rotation, scale, translation.
+    let simples: Vec<serde_json::Value> = match json {
+        serde_json::Value::Array(arr) => arr.clone(),
+        serde_json::Value::Object(obj) => obj
+            .iter()
+            .map(|(key, value)| serde_json::json!({ key.clone(): value.clone() }))
+            .collect(),
+        _ => {
+            let parse_error = ParseError {
+                msg: "json passed to `new_from_simple_sequence_json()` is neither an array nor a map".to_string(),
+                json: json.clone()
+            };
+            return Err(parse_error);
+        }
+    };
 
     let mut sequence: Vec<Matrix4TransformKind> = Vec::new();
 
     // Handle each simple transform in sequence
-    for key in map.keys() {
-        match key.as_str() {
-            "rotation" => {
-                let parsed: Result<SimpleRotation, _> 
-                    = serde_json::from_value(json["rotation"].clone());
-                
-                if parsed.is_err() { 
-                    let parse_error = ParseError {
-                        msg: "could not parse rotation".to_string(),
-                        json: json.clone(),
-                    };
-                    return Err(parse_error); 
-                }
-                let simple_rotation = parsed.unwrap();
-
-                sequence.push(Matrix4TransformKind::AxisRotation(simple_rotation.into()));
-            }
-            "translation" => {
-                let parsed: Result<Vec3, _> = serde_json::from_value(json["translation"].clone());
-
-                if parsed.is_err() { 
-                    let parse_error = ParseError {
-                        msg: "could not parse translation".to_string(),
-                        json: json.clone(),
-                    };
-                    return Err(parse_error); 
-                }
-                let simple_translation = parsed.unwrap();
-
-                sequence.push(Matrix4TransformKind::Translation(simple_translation));
+    for simple in simples.iter() {
+        sequence.push(new_simple_transform_from_json(simple)?);
+    }
+
+    let matrix = Matrix4::new_from_sequence(&sequence);
+    Ok(Transform::new_from_matrix(&matrix))
+}
+
+/// Parses one element of a "simple sequence", which is a map with exactly one key.
+fn new_simple_transform_from_json(json: &serde_json::Value) -> Result<Matrix4TransformKind, ParseError> {
+    let key = match json {
+        serde_json::Value::Object(obj) if obj.len() == 1 => obj.keys().next().unwrap().clone(),
+        _ => {
+            let parse_error = ParseError {
+                msg: "simple transform must be a map with exactly one key".to_string(),
+                json: json.clone(),
+            };
+            return Err(parse_error);
+        }
+    };
+
+    match key.as_str() {
+        "rotation" => {
+            let parsed: Result<SimpleRotation, _> 
+                = serde_json::from_value(json["rotation"].clone());
+            
+            if parsed.is_err() { 
+                let parse_error = ParseError {
+                    msg: "could not parse rotation".to_string(),
+                    json: json.clone(),
+                };
+                return Err(parse_error); 
             }
-            "scale" => {
-                let parsed: Result<Vec3, _> = serde_json::from_value(json["scale"].clone());
-
-                if parsed.is_err() { 
-                    let parse_error = ParseError {
-                        msg: "could not parse scale".to_string(),
-                        json: json.clone(),
-                    };
-                    return Err(parse_error); 
-                }
-                let simple_scale = parsed.unwrap();
-
-                sequence.push(Matrix4TransformKind::Translation(simple_scale));
+            let simple_rotation = parsed.unwrap();
+
+            Ok(Matrix4TransformKind::AxisRotation(simple_rotation.into()))
+        }
+        "translation" => {
+            let parsed: Result<Vec3, _> = serde_json::from_value(json["translation"].clone());
+
+            if parsed.is_err() { 
+                let parse_error = ParseError {
+                    msg: "could not parse translation".to_string(),
+                    json: json.clone(),
+                };
+                return Err(parse_error); 
             }
-            other => {
+            let simple_translation = parsed.unwrap();
+
+            Ok(Matrix4TransformKind::Translation(simple_translation))
+        }
+        "scale" => {
+            let parsed: Result<Vec3, _> = serde_json::from_value(json["scale"].clone());
+
+            if parsed.is_err() { 
                 let parse_error = ParseError {
-                    msg: format!("unknown simple transform type {}", other),
+                    msg: "could not parse scale".to_string(),
                     json: json.clone(),
                 };
-                return Err(parse_error);
+                return Err(parse_error); 
             }
+            let simple_scale = parsed.unwrap();
+
+            Ok(Matrix4TransformKind::Scale(simple_scale))
+        }
+        other => {
+            let parse_error = ParseError {
+                msg: format!("unknown simple transform type {}", other),
+                json: json.clone(),
+            };
+            Err(parse_error)
         }
     }
-
-    let matrix = Matrix4::new_from_sequence(&sequence);
-    Ok(Transform::new_from_matrix(&matrix))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::vector::Point3;
+
+    #[test]
+    fn simple_sequences_can_be_arrays_or_maps() {
+        let array = serde_json::json!({
+            "simple sequence": [
+                { "rotation": { "axis": [0, 0, 1], "angle": 90 } },
+                { "scale": [2, 2, 2] },
+                { "translation": [1, 0, 0] }
+            ]
+        });
+        let map = serde_json::json!({
+            "simple sequence": {
+                "translation": [1, 0, 0],
+                "rotation": { "axis": [0, 0, 1], "angle": 90 },
+                "scale": [2, 2, 2]
+            }
+        });
+
+        for json in [array, map] {
+            let point = new_from_json(&json).unwrap().point_to_global(&Point3::new(1.0, 0.0, 0.0));
+            assert!((point.x() - 1.0).abs() < 1e-5 && (point.y() - 2.0).abs() < 1e-5 && point.z().abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! The CIE 1931 standard observer and illuminant D65, which relate spectra to the
+//! colors we see.
+
+use crate::utility::math::{float::Float, vector::Color3};
+
+/// The shortest and longest wavelengths (in nanometers) we render.
+pub const MIN_WAVELENGTH: Float = 360.0;
+pub const MAX_WAVELENGTH: Float = 830.0;
+
+/// $\int \bar{y}(\lambda) d\lambda$ over the rendered wavelengths, which is the
+/// luminance $Y$ of the constant spectrum 1.
+pub const CIE_Y_INTEGRAL: Float = 106.922_08;
+
+/// The relative spectral power of illuminant D65 (average daylight, the white of
+/// sRGB) every 10 nanometers from `MIN_WAVELENGTH` to `MAX_WAVELENGTH`.
+const D65: [Float; 48] = [
+    46.64, 52.09, 49.98, 54.65, 82.75, 91.49, 93.43, 86.68, 104.86, 117.01,
+    117.81, 114.86, 115.92, 108.81, 109.35, 107.80, 104.79, 107.69, 104.41, 104.05,
+    100.00, 96.33, 95.79, 88.69, 90.01, 89.60, 87.70, 83.29, 83.70, 80.03,
+    80.21, 82.28, 78.28, 69.72, 71.61, 74.35, 61.60, 69.89, 75.09, 63.59,
+    46.42, 66.81, 63.38, 64.30, 59.45, 51.96, 57.44, 60.31,
+];
+
+/// The luminance $Y$ of `D65` as tabulated, so that dividing by it gives D65 a
+/// luminance of 1.
+const D65_Y: Float = 98.852_02;
+
+/// From CIE XYZ to linear sRGB, whose white is D65.
+const XYZ_TO_SRGB: [[Float; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+/// The CIE 1931 color matching functions $(\bar{x}, \bar{y}, \bar{z})$ at
+/// `wavelength`, with the multi-lobe Gaussian fit of Wyman, Sloan and Shirley
+/// ("Simple Analytic Approximations to the CIE XYZ Color Matching Functions").
+pub fn color_matching(wavelength: Float) -> (Float, Float, Float) {
+    let lobe = |mean: Float, sigma_below: Float, sigma_above: Float| {
+        let sigma = if wavelength < mean { sigma_below } else { sigma_above };
+        let t = (wavelength - mean) / sigma;
+        (-0.5 * t * t).exp()
+    };
+
+    let x = 1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+        - 0.065 * lobe(501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+/// Illuminant D65 at `wavelength`, scaled to a luminance of 1, so that it is the
+/// spectrum of sRGB's white (1, 1, 1).
+pub fn d65(wavelength: Float) -> Float {
+    let position = ((wavelength - MIN_WAVELENGTH) / 10.0).clamp(0.0, (D65.len() - 1) as Float);
+    let index = usize::min(position as usize, D65.len() - 2);
+    let fraction = position - (index as Float);
+
+    ((1.0 - fraction) * D65[index] + fraction * D65[index + 1]) / D65_Y
+}
+
+pub fn xyz_to_srgb(x: Float, y: Float, z: Float) -> Color3 {
+    let row = |r: &[Float; 3]| r[0] * x + r[1] * y + r[2] * z;
+    Color3::new(row(&XYZ_TO_SRGB[0]), row(&XYZ_TO_SRGB[1]), row(&XYZ_TO_SRGB[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Integrates `f` over the rendered wavelengths with the midpoint rule.
+    fn integrate(f: impl Fn(Float) -> Float) -> Float {
+        let steps = (MAX_WAVELENGTH - MIN_WAVELENGTH) as usize;
+        (0..steps).map(|i| f(MIN_WAVELENGTH + (i as Float) + 0.5)).sum()
+    }
+
+    #[test]
+    fn d65_is_srgb_white() {
+        assert!((integrate(|l| color_matching(l).1) - CIE_Y_INTEGRAL).abs() < 1e-2);
+
+        let x = integrate(|l| d65(l) * color_matching(l).0) / CIE_Y_INTEGRAL;
+        let y = integrate(|l| d65(l) * color_matching(l).1) / CIE_Y_INTEGRAL;
+        let z = integrate(|l| d65(l) * color_matching(l).2) / CIE_Y_INTEGRAL;
+        let white = xyz_to_srgb(x, y, z);
+        for channel in [white.x(), white.y(), white.z()] {
+            assert!((channel - 1.0).abs() < 5e-3, "{:?}", white);
+        }
+    }
+}
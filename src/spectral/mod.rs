@@ -0,0 +1,33 @@
+//! Spectral rendering. Rather than red, green and blue, each camera ray carries a
+//! few wavelengths of light (see `wavelengths::SampledWavelengths`), and the
+//! components of the `Spectrum`s along its path are the values at those
+//! wavelengths. The RGB colors of the scene are turned into spectra as they are
+//! looked up (see `upsampling`), and the light arriving at the camera is turned
+//! back into a color with the CIE color matching functions (see `cie`).
+//!
+//! This is what makes wavelength-dependent effects, like the dispersion of light
+//! by glass, possible.
+
+pub mod cie;
+pub mod upsampling;
+pub mod wavelengths;
+
+use crate::{light::Spectrum, utility::math::vector::Color3};
+use wavelengths::SampledWavelengths;
+
+/// The spectrum of light with color `rgb` at `wavelengths`, or the color itself 
+/// when rendering in RGB.
+pub fn illuminant(wavelengths: Option<&SampledWavelengths>, rgb: &Color3) -> Spectrum {
+    match wavelengths {
+        Some(wavelengths) => wavelengths.illuminant(rgb),
+        None => rgb.clone(),
+    }
+}
+
+/// Like `illuminant()`, for a reflectance.
+pub fn reflectance(wavelengths: Option<&SampledWavelengths>, rgb: &Color3) -> Spectrum {
+    match wavelengths {
+        Some(wavelengths) => wavelengths.reflectance(rgb),
+        None => rgb.clone(),
+    }
+}
@@ -0,0 +1,107 @@
+//! Turning the RGB colors of a scene (from its textures, lights and environment)
+//! into spectra, with the method of Smits ("An RGB-to-Spectrum Conversion for
+//! Reflectances"): the spectrum is a sum of smooth spectra for white, the
+//! primaries and their complements, so that a grey stays flat and the spectrum of
+//! a reflectance in $[0,1]$ stays (roughly) in $[0,1]$.
+
+use crate::utility::math::{float::Float, vector::Color3};
+use super::cie;
+
+/// Smits' spectra, sampled at `NUM_SAMPLES` wavelengths evenly spaced from
+/// `FIRST_WAVELENGTH` to `LAST_WAVELENGTH`.
+const NUM_SAMPLES: usize = 10;
+const FIRST_WAVELENGTH: Float = 380.0;
+const LAST_WAVELENGTH: Float = 720.0;
+
+const WHITE: [Float; NUM_SAMPLES] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const CYAN: [Float; NUM_SAMPLES] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const MAGENTA: [Float; NUM_SAMPLES] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const YELLOW: [Float; NUM_SAMPLES] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const RED: [Float; NUM_SAMPLES] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const GREEN: [Float; NUM_SAMPLES] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const BLUE: [Float; NUM_SAMPLES] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+/// The spectrum of a reflectance (e.g. the albedo of a texture) with color `rgb`
+/// at `wavelength`.
+pub fn reflectance(rgb: &Color3, wavelength: Float) -> Float {
+    let at = |spectrum: &[Float; NUM_SAMPLES]| interpolate(spectrum, wavelength);
+    let (r, g, b) = (rgb.x(), rgb.y(), rgb.z());
+
+    // White up to the smallest component, plus the secondary color (for the two
+    // larger components) up to the middle one, plus the primary for the rest.
+    if r <= g && r <= b {
+        if g <= b {
+            r * at(&WHITE) + (g - r) * at(&CYAN) + (b - g) * at(&BLUE)
+        } else {
+            r * at(&WHITE) + (b - r) * at(&CYAN) + (g - b) * at(&GREEN)
+        }
+    } else if g <= r && g <= b {
+        if r <= b {
+            g * at(&WHITE) + (r - g) * at(&MAGENTA) + (b - r) * at(&BLUE)
+        } else {
+            g * at(&WHITE) + (b - g) * at(&MAGENTA) + (r - b) * at(&RED)
+        }
+    } else if r <= g {
+        b * at(&WHITE) + (r - b) * at(&YELLOW) + (g - r) * at(&GREEN)
+    } else {
+        b * at(&WHITE) + (g - b) * at(&YELLOW) + (r - g) * at(&RED)
+    }
+}
+
+/// The spectrum of light (e.g. from a light source or the environment) with color
+/// `rgb` at `wavelength`. White light is D65, the white of sRGB, so that a white
+/// surface lit by it is white rather than tinted.
+pub fn illuminant(rgb: &Color3, wavelength: Float) -> Float {
+    reflectance(rgb, wavelength) * cie::d65(wavelength)
+}
+
+/// Linear interpolation between the samples, constant beyond the first and last.
+fn interpolate(spectrum: &[Float; NUM_SAMPLES], wavelength: Float) -> Float {
+    let spacing = (LAST_WAVELENGTH - FIRST_WAVELENGTH) / ((NUM_SAMPLES - 1) as Float);
+    let position = ((wavelength - FIRST_WAVELENGTH) / spacing).clamp(0.0, (NUM_SAMPLES - 1) as Float);
+    let index = usize::min(position as usize, NUM_SAMPLES - 2);
+    let fraction = position - (index as Float);
+
+    (1.0 - fraction) * spectrum[index] + fraction * spectrum[index + 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The color of the spectrum of a reflectance with color `rgb` under white light.
+    fn round_trip(rgb: &Color3) -> Color3 {
+        let mut xyz = (0.0, 0.0, 0.0);
+        let mut wavelength = cie::MIN_WAVELENGTH + 0.5;
+        while wavelength < cie::MAX_WAVELENGTH {
+            let value = illuminant(rgb, wavelength) / cie::CIE_Y_INTEGRAL;
+            let (x, y, z) = cie::color_matching(wavelength);
+            xyz = (xyz.0 + value * x, xyz.1 + value * y, xyz.2 + value * z);
+            wavelength += 1.0;
+        }
+        cie::xyz_to_srgb(xyz.0, xyz.1, xyz.2)
+    }
+
+    #[test]
+    fn colors_survive_the_round_trip() {
+        // Greys are exact, saturated colors are off by a few percent.
+        let cases = [
+            (Color3::new(1.0, 1.0, 1.0), 5e-3),
+            (Color3::new(0.5, 0.5, 0.5), 5e-3),
+            (Color3::new(0.2, 0.5, 0.8), 0.05),
+            (Color3::new(0.8, 0.1, 0.1), 0.05),
+            (Color3::new(0.1, 0.8, 0.1), 0.08),
+        ];
+        for (rgb, tolerance) in cases {
+            let result = round_trip(&rgb);
+            assert!((result.x() - rgb.x()).abs() < tolerance, "{:?} became {:?}", rgb, result);
+            assert!((result.y() - rgb.y()).abs() < tolerance, "{:?} became {:?}", rgb, result);
+            assert!((result.z() - rgb.z()).abs() < tolerance, "{:?} became {:?}", rgb, result);
+        }
+
+        for wavelength in [400.0, 550.0, 700.0] {
+            let reflectance = reflectance(&Color3::new(0.9, 0.2, 0.6), wavelength);
+            assert!((0.0..=1.0).contains(&reflectance));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::math::{float::Float, vector::Color3},
+    light::Spectrum
+};
+use super::{cie, upsampling};
+
+// E==== IMPORTS }}}1
+
+/// How many wavelengths a ray carries, one per component of a `Spectrum`.
+pub const NUM_WAVELENGTHS: usize = 3;
+
+/// The wavelengths (in nanometers) a camera ray carries in spectral mode, with the
+/// densities they were sampled with. The first is the "hero" wavelength, the others
+/// are rotations of it through the visible range (hero wavelength sampling): each on
+/// its own is an unbiased estimate, and together they cover the spectrum evenly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampledWavelengths {
+    lambdas: [Float; NUM_WAVELENGTHS],
+    pdfs: [Float; NUM_WAVELENGTHS],
+}
+
+impl SampledWavelengths {
+    /// Samples the wavelengths roughly in proportion to how visible they are (with
+    /// the density of Radziszewski et al., as in PBRT), which makes for less color
+    /// noise than sampling them uniformly. `u` is uniformly distributed in $[0,1)$.
+    pub fn sample_visible(u: Float) -> Self {
+        let mut lambdas = [0.0; NUM_WAVELENGTHS];
+        let mut pdfs = [0.0; NUM_WAVELENGTHS];
+        for i in 0..NUM_WAVELENGTHS {
+            let u = (u + (i as Float) / (NUM_WAVELENGTHS as Float)).fract();
+            lambdas[i] = sample_visible_wavelength(u);
+            pdfs[i] = visible_wavelength_pdf(lambdas[i]);
+        }
+
+        Self { lambdas, pdfs }
+    }
+
+    pub fn hero(&self) -> Float {
+        self.lambdas[0]
+    }
+
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.pdfs[1..].iter().all(|pdf| *pdf == 0.0)
+    }
+
+    /// Keeps only the hero wavelength, for when the path goes on to scatter
+    /// differently for each wavelength (e.g. a refraction with dispersion) and the
+    /// others can no longer follow it. The remaining components of the spectra of
+    /// the path are then meaningless: the integrator drops them, and scales the
+    /// hero's component by `NUM_WAVELENGTHS` so that it stands for all of the
+    /// wavelengths when the camera ray's wavelengths convert it with `to_rgb()`.
+    pub fn terminate_secondary(&self) -> Self {
+        let mut to_return = *self;
+        for pdf in &mut to_return.pdfs[1..] {
+            *pdf = 0.0;
+        }
+        to_return
+    }
+
+    /// The spectrum of a reflectance with color `rgb` at the wavelengths.
+    pub fn reflectance(&self, rgb: &Color3) -> Spectrum {
+        self.map(|lambda| upsampling::reflectance(rgb, lambda))
+    }
+
+    /// The spectrum of light with color `rgb` at the wavelengths.
+    pub fn illuminant(&self, rgb: &Color3) -> Spectrum {
+        self.map(|lambda| upsampling::illuminant(rgb, lambda))
+    }
+
+    /// The color of the light with `spectrum` at the wavelengths: the Monte Carlo
+    /// estimate of its CIE XYZ coordinates, converted to linear sRGB.
+    pub fn to_rgb(self, spectrum: &Spectrum) -> Color3 {
+        let values = [spectrum.x(), spectrum.y(), spectrum.z()];
+        let mut xyz = [0.0; 3];
+        for ((lambda, pdf), value) in self.lambdas.iter().zip(&self.pdfs).zip(values) {
+            if *pdf == 0.0 {
+                continue;
+            }
+
+            let (x, y, z) = cie::color_matching(*lambda);
+            let weight = value / pdf;
+            xyz[0] += weight * x;
+            xyz[1] += weight * y;
+            xyz[2] += weight * z;
+        }
+
+        let scale = 1.0 / ((NUM_WAVELENGTHS as Float) * cie::CIE_Y_INTEGRAL);
+        cie::xyz_to_srgb(scale * xyz[0], scale * xyz[1], scale * xyz[2])
+    }
+
+    fn map(&self, f: impl Fn(Float) -> Float) -> Spectrum {
+        Spectrum::new(f(self.lambdas[0]), f(self.lambdas[1]), f(self.lambdas[2]))
+    }
+}
+
+/// The inverse of the cumulative distribution of `visible_wavelength_pdf()`.
+fn sample_visible_wavelength(u: Float) -> Float {
+    538.0 - 138.888_89 * Float::atanh(0.856_910_6 - 1.827_502 * u)
+}
+
+fn visible_wavelength_pdf(lambda: Float) -> Float {
+    if !(cie::MIN_WAVELENGTH..=cie::MAX_WAVELENGTH).contains(&lambda) {
+        return 0.0;
+    }
+
+    let cosh = Float::cosh(0.0072 * (lambda - 538.0));
+    0.003_939_804 / (cosh * cosh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::rng::RandomNumberGenerator;
+
+    #[test]
+    fn white_light_is_white() {
+        let mut rng = RandomNumberGenerator::from_seed(5);
+        let num_samples = 20_000;
+        let white = Color3::new(1.0, 1.0, 1.0);
+        let (mut sum, mut hero_sum) = (Color3::new(0.0, 0.0, 0.0), Color3::new(0.0, 0.0, 0.0));
+        for _ in 0..num_samples {
+            let wavelengths = SampledWavelengths::sample_visible(rng.next_float());
+            assert!(wavelengths.lambdas.iter().all(|lambda| (cie::MIN_WAVELENGTH..=cie::MAX_WAVELENGTH).contains(lambda)));
+            sum = sum + wavelengths.to_rgb(&wavelengths.illuminant(&white));
+
+            // As the path tracer weights the hero wavelength after dispersion.
+            let hero = wavelengths.terminate_secondary();
+            assert!(hero.is_secondary_terminated() && !wavelengths.is_secondary_terminated());
+            let hero_value = (NUM_WAVELENGTHS as Float) * hero.illuminant(&white).x();
+            hero_sum = hero_sum + wavelengths.to_rgb(&Spectrum::new(hero_value, 0.0, 0.0));
+        }
+
+        for estimate in [sum, hero_sum] {
+            let mean = (1.0 / num_samples as Float) * estimate;
+            for channel in [mean.x(), mean.y(), mean.z()] {
+                assert!((channel - 1.0).abs() < 0.02, "{:?}", mean);
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 //! Reading and writting to image formats
 
+use std::{fmt::{Debug, Display}, fs::File, io::BufWriter, path::Path};
 use image;
 use serde::Deserialize;
-use super::math::{vector::Color3, float::Float};
+use super::{math::{vector::Color3, float::{self, Float}, statistics::RunningStatistics}, tone_mapping::DisplayTransform};
+use crate::{light::{luminance, linear_to_srgb}, filters::traits::FilterLike};
 
 // S==== ASSOCIATED TYPES {{{1
 
@@ -18,20 +20,21 @@ impl IntoIterator for Resolution {
 
     fn into_iter(self) -> Self::IntoIter {
         PixelIterator {
-            pixel: Pixel { x: 0, y: 0 },
-            resolution: self.clone(),
+            pixel: None,
+            resolution: self,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Pixel {
     pub x: u32,
     pub y: u32,
 }
 
 pub struct PixelIterator {
-    pixel: Pixel,
+    /// The most recently returned pixel, or `None` if iteration has not started.
+    pixel: Option<Pixel>,
     resolution: Resolution,
 }
 
@@ -39,30 +42,105 @@ impl Iterator for PixelIterator {
     type Item = Pixel;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pixel.x < self.resolution.width - 1 { 
-            self.pixel = Pixel { x: self.pixel.x + 1, y: self.pixel.y };
-            return Some(self.pixel.clone());
+        if self.resolution.width == 0 || self.resolution.height == 0 {
+            return None;
         }
 
-        if self.pixel.y < self.resolution.height - 1 {
-            self.pixel = Pixel { x: 0, y: self.pixel.y + 1 };
-            return Some(self.pixel.clone());
+        let next_pixel = match &self.pixel {
+            None => Pixel { x: 0, y: 0 },
+            Some(pixel) if pixel.x < self.resolution.width - 1 => {
+                Pixel { x: pixel.x + 1, y: pixel.y }
+            },
+            Some(pixel) if pixel.y < self.resolution.height - 1 => {
+                Pixel { x: 0, y: pixel.y + 1 }
+            },
+            Some(_) => { return None; }
+        };
+
+        self.pixel = Some(next_pixel.clone());
+        Some(next_pixel)
+    }
+}
+
+impl Resolution {
+    /// The position of `pixel` in the order the resolution iterates over its pixels.
+    pub fn pixel_index(&self, pixel: &Pixel) -> usize {
+        (pixel.y as usize) * (self.width as usize) + (pixel.x as usize)
+    }
+
+    /// Splits the image into tiles of (at most) `tile_size` by `tile_size` pixels. 
+    /// Tiles along the top and right edges are smaller when the resolution is not 
+    /// a multiple of `tile_size`. The tiles are ordered row by row, starting from 
+    /// the bottom left.
+    pub fn tiles(&self, tile_size: u32) -> Vec<Tile> {
+        assert!(tile_size > 0, "tiles must contain at least one pixel");
+
+        let mut to_return = Vec::new();
+        for y in (0..self.height).step_by(tile_size as usize) {
+            for x in (0..self.width).step_by(tile_size as usize) {
+                to_return.push(Tile {
+                    origin: Pixel { x, y },
+                    width: u32::min(tile_size, self.width - x),
+                    height: u32::min(tile_size, self.height - y),
+                });
+            }
         }
 
-        return None;
+        to_return
+    }
+}
+
+/// A rectangular block of pixels, which is the unit of work when rendering in 
+/// parallel.
+#[derive(Clone, Debug)]
+pub struct Tile {
+    /// The bottom left pixel of the tile.
+    pub origin: Pixel,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    /// The pixels of the tile, row by row starting from the bottom left, in image 
+    /// (not tile-local) coordinates.
+    pub fn pixels(&self) -> impl Iterator<Item = Pixel> + '_ {
+        let local = Resolution { width: self.width, height: self.height };
+        local.into_iter().map(move |pixel| Pixel {
+            x: self.origin.x + pixel.x,
+            y: self.origin.y + pixel.y,
+        })
     }
 }
 
+/// The color computed for one sample of a pixel.
+pub struct PixelSample {
+    pub pixel: Pixel,
+    /// Where in the pixel the sample was taken, with $(0, 0)$ the bottom left corner 
+    /// and $(1, 1)$ the top right one.
+    pub offset: (Float, Float),
+    pub color: Color3,
+}
+
+/// The samples computed for (some of) the pixels of a tile.
+pub struct RenderedTile {
+    pub tile: Tile,
+    pub samples: Vec<PixelSample>,
+}
+
 // E==== ASSOCIATED TYPES }}}1
 
 // S==== IMAGE {{{1
 
+/// The pixels of an `Image`, whose channels are `Float`s so that samples are 
+/// accumulated in the precision they are computed in.
+type PixelBuffer = image::ImageBuffer<image::Rgb<Float>, Vec<Float>>;
+
 /// Representation of an image, for decoding, writing, and encoding. Our convention is to 
 /// begin counting on the bottom left, which we denote (0, 0), as is common in math. 
 #[derive(Clone)]
 pub struct Image {
     resolution: Resolution,
-    internal: image::Rgb32FImage,
+    internal: PixelBuffer,
 }
 
 impl Image {
@@ -75,10 +153,14 @@ impl Image {
 
         Image {
             resolution,
-            internal: image::Rgb32FImage::new(width, height),
+            internal: PixelBuffer::new(width, height),
         }
     }
 
+    pub fn resolution(&self) -> &Resolution {
+        &self.resolution
+    }
+
     /// Set the pixel (x, y) to color. Recall that (0, 0) is in the bottom left.
     pub fn set_pixel_color(&mut self, pixel: &Pixel, color: Color3) {
         assert!(pixel.x < self.resolution.width && pixel.y < self.resolution.height, "out of bounds index");
@@ -102,65 +184,308 @@ impl Image {
     }
 
     /// Saves the image buffer to a file, whose encoding is deduced from the filename 
-    /// (so include the extension in `filename`).
-    pub fn save_to_file(&self, filename: &str) -> Result<(), String> {
-        match self.internal.save(filename) {
-            Ok(_) => { return Ok(()); }
-            Err(e) => { return Err(e.to_string()); }
+    /// (so include the extension in `filename`). See `OutputFormat` for the supported 
+    /// extensions. Low dynamic range formats apply `display` and store the result 
+    /// sRGB encoded, while the floating point formats store the linear values as 
+    /// they are.
+    pub fn save_to_file(&self, filename: &str, display: &DisplayTransform) -> Result<(), ImageSaveError> {
+        let format = OutputFormat::new_from_filename(filename)?;
+
+        let result = match format {
+            OutputFormat::Png | OutputFormat::Jpeg => {
+                self.to_display_image(display).save_with_format(filename, format.to_image_format())
+            },
+            OutputFormat::OpenExr => {
+                self.to_rgb32f_image().save_with_format(filename, format.to_image_format())
+            },
+            OutputFormat::RadianceHdr => self.save_to_hdr_file(filename),
+        };
+
+        result.map_err(|e| ImageSaveError::Encoding {
+            filename: filename.to_string(),
+            msg: e.to_string(),
+        })
+    }
+
+    fn to_display_image(&self, display: &DisplayTransform) -> image::RgbImage {
+        let encode = |linear: Float| (255.0 * linear_to_srgb(linear) + 0.5) as u8;
+
+        image::RgbImage::from_fn(self.resolution.width, self.resolution.height, |x, y| {
+            let pixel = self.internal.get_pixel(x, y).0;
+            let color = display.apply(&Color3::new(pixel[0], pixel[1], pixel[2]));
+            image::Rgb([encode(color.x()), encode(color.y()), encode(color.z())])
+        })
+    }
+
+    /// The floating point formats store single precision values.
+    fn to_rgb32f_image(&self) -> image::Rgb32FImage {
+        image::Rgb32FImage::from_fn(self.resolution.width, self.resolution.height, |x, y| {
+            let pixel = self.internal.get_pixel(x, y).0;
+            image::Rgb([float::to_f32(pixel[0]), float::to_f32(pixel[1]), float::to_f32(pixel[2])])
+        })
+    }
+
+    /// The `image` crate can only write Radiance HDR through its encoder directly.
+    fn save_to_hdr_file(&self, filename: &str) -> image::ImageResult<()> {
+        let file = File::create(filename).map_err(image::ImageError::IoError)?;
+        let encoder = image::codecs::hdr::HdrEncoder::new(BufWriter::new(file));
+        let pixels: Vec<image::Rgb<f32>> = self.to_rgb32f_image().pixels().copied().collect();
+
+        encoder.encode(&pixels, self.resolution.width as usize, self.resolution.height as usize)
+    }
+}
+
+/// The file formats an `Image` can be saved as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    OpenExr,
+    RadianceHdr,
+}
+
+impl OutputFormat {
+    /// Deduces the format from the (case-insensitive) extension of `filename`.
+    pub fn new_from_filename(filename: &str) -> Result<Self, ImageSaveError> {
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => Ok(OutputFormat::Png),
+            Some("jpg") | Some("jpeg") => Ok(OutputFormat::Jpeg),
+            Some("exr") => Ok(OutputFormat::OpenExr),
+            Some("hdr") => Ok(OutputFormat::RadianceHdr),
+            _ => Err(ImageSaveError::UnsupportedFormat(filename.to_string())),
         }
     }
+
+    /// Whether the format stores unclamped floating point values.
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(self, OutputFormat::OpenExr | OutputFormat::RadianceHdr)
+    }
+
+    fn to_image_format(self) -> image::ImageFormat {
+        match self {
+            OutputFormat::Png => image::ImageFormat::Png,
+            OutputFormat::Jpeg => image::ImageFormat::Jpeg,
+            OutputFormat::OpenExr => image::ImageFormat::OpenExr,
+            OutputFormat::RadianceHdr => image::ImageFormat::Hdr,
+        }
+    }
+}
+
+pub enum ImageSaveError {
+    /// The filename's extension does not correspond to any `OutputFormat`.
+    UnsupportedFormat(String),
+    /// Encoding or writing the file failed.
+    Encoding { filename: String, msg: String },
+}
+
+impl Display for ImageSaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageSaveError::UnsupportedFormat(filename) => write!(
+                f, "cannot deduce an image format from '{}' (expected .png, .jpg, .jpeg, .exr or .hdr)", filename
+            ),
+            ImageSaveError::Encoding { filename, msg } => write!(
+                f, "failed to write image '{}': {}", filename, msg
+            ),
+        }
+    }
+}
+
+impl Debug for ImageSaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
 }
 
 // E==== IMAGE }}}1
 
 // S==== IMAGE BUFFER {{{1
 
+/// The running, filter-weighted sum of the samples around each pixel along with the 
+/// sum of the weights, and statistics of the luminance of the samples taken in each 
+/// pixel to judge how converged it is. Pixels may have different numbers of samples 
+/// (see `AdaptiveSampling`).
+#[derive(Clone)]
 pub struct ImageBuffer {
     image: Image,
-    samples: u32,
+    /// Indexed by `Resolution::pixel_index`, like `statistics`.
+    weights: Vec<Float>,
+    statistics: Vec<RunningStatistics>,
+    passes: u32,
 }
 
 impl ImageBuffer {
+    /// The total filter weight below which a pixel counts as having no samples (see 
+    /// `average_samples()`). A sample at a pixel's center weighs about 1.
+    pub const MIN_PIXEL_WEIGHT: Float = 1e-3;
+
     pub fn new(resolution: Resolution) -> Self {
+        let num_pixels = (resolution.width as usize) * (resolution.height as usize);
         Self {
             image: Image::new(resolution),
-            samples: 0
+            weights: vec![0.0; num_pixels],
+            statistics: vec![RunningStatistics::default(); num_pixels],
+            passes: 0
         }
     }
 
-    pub fn num_samples(&self) -> u32 {
-        self.samples
+    /// Restores a buffer from the per-pixel sums, weights and statistics after 
+    /// `passes` passes, listed in the order `resolution` iterates over its pixels (as 
+    /// `sums()`, `weights()` and `statistics()` return them).
+    pub fn new_from_parts(
+        resolution: Resolution, 
+        sums: &[Color3], 
+        weights: Vec<Float>,
+        statistics: Vec<RunningStatistics>, 
+        passes: u32
+    ) -> Self {
+        let num_pixels = (resolution.width as usize) * (resolution.height as usize);
+        assert!(
+            sums.len() == num_pixels && weights.len() == num_pixels && statistics.len() == num_pixels, 
+            "expected one sum, weight and set of statistics per pixel"
+        );
+
+        let mut image = Image::new(resolution.clone());
+        for (pixel, sum) in resolution.into_iter().zip(sums.iter()) {
+            image.set_pixel_color(&pixel, sum.clone());
+        }
+
+        Self { image, weights, statistics, passes }
     }
 
-    pub fn add_sample(&mut self, image: Image) {
-        for pixel in image.resolution.clone().into_iter() {
-            self.image.set_pixel_color(
-                &pixel,
-                self.image.get_pixel_color(&pixel) + image.get_pixel_color(&pixel)
-            );
+    pub fn resolution(&self) -> &Resolution {
+        &self.image.resolution
+    }
+
+    /// How many times `add_sample` was called.
+    pub fn num_passes(&self) -> u32 {
+        self.passes
+    }
+
+    pub fn num_pixel_samples(&self, pixel: &Pixel) -> u32 {
+        self.statistics[self.resolution().pixel_index(pixel)].count()
+    }
+
+    /// The average number of samples per pixel.
+    pub fn samples_per_pixel(&self) -> Float {
+        let total: u64 = self.statistics.iter().map(|s| s.count() as u64).sum();
+        (total as Float) / (self.statistics.len().max(1) as Float)
+    }
+
+    /// The sum of the samples of each pixel, in the order `resolution()` iterates over 
+    /// the pixels.
+    pub fn sums(&self) -> Vec<Color3> {
+        self.image.resolution.clone()
+            .into_iter()
+            .map(|pixel| self.image.get_pixel_color(&pixel))
+            .collect()
+    }
+
+    /// The sum of the filter weights of the samples around each pixel, in the order 
+    /// `resolution()` iterates over the pixels.
+    pub fn weights(&self) -> &[Float] {
+        &self.weights
+    }
+
+    /// The statistics of the luminance of the samples of each pixel, in the order 
+    /// `resolution()` iterates over the pixels.
+    pub fn statistics(&self) -> &[RunningStatistics] {
+        &self.statistics
+    }
+
+    /// Adds the samples of one pass, with at most one sample per pixel. Each sample 
+    /// counts towards every pixel whose center is within the radius of `filter`, 
+    /// weighted by the filter.
+    pub fn add_sample(&mut self, tiles: &[RenderedTile], filter: &dyn FilterLike) {
+        let radius = filter.radius();
+        let width = self.resolution().width as i64;
+        let height = self.resolution().height as i64;
+
+        for rendered_tile in tiles.iter() {
+            for sample in rendered_tile.samples.iter() {
+                let x = (sample.pixel.x as Float) + sample.offset.0;
+                let y = (sample.pixel.y as Float) + sample.offset.1;
+
+                // The pixels whose centers, at half-integer coordinates, are in range.
+                let x_range = i64::max((x - 0.5 - radius).ceil() as i64, 0)
+                    ..=i64::min((x - 0.5 + radius).floor() as i64, width - 1);
+                let y_range = i64::max((y - 0.5 - radius).ceil() as i64, 0)
+                    ..=i64::min((y - 0.5 + radius).floor() as i64, height - 1);
+
+                for py in y_range {
+                    for px in x_range.clone() {
+                        let pixel = Pixel { x: px as u32, y: py as u32 };
+                        let weight = filter.evaluate(
+                            (px as Float) + 0.5 - x, 
+                            (py as Float) + 0.5 - y
+                        );
+                        if weight == 0.0 {
+                            continue;
+                        }
+
+                        self.image.set_pixel_color(
+                            &pixel,
+                            self.image.get_pixel_color(&pixel) + weight * &sample.color
+                        );
+                        let index = self.resolution().pixel_index(&pixel);
+                        self.weights[index] += weight;
+                    }
+                }
+
+                let index = self.resolution().pixel_index(&sample.pixel);
+                self.statistics[index].add(luminance(&sample.color));
+            }
         }
         
-        self.samples += 1;
+        self.passes += 1;
     }
 
+    /// The weighted average of the samples around each pixel. Pixels without any yet 
+    /// are black, as are pixels whose weights add up to at most `MIN_PIXEL_WEIGHT`: 
+    /// filters with negative lobes (e.g. Mitchell's) can leave a pixel with a tiny or 
+    /// negative total weight, which would blow up or flip its color.
     pub fn average_samples(&self) -> Image {
         let mut to_return = self.image.clone();
 
         for pixel in to_return.resolution.clone().into_iter() {
+            let weight = self.weights[self.resolution().pixel_index(&pixel)];
+            if weight <= Self::MIN_PIXEL_WEIGHT {
+                to_return.set_pixel_color(&pixel, Color3::new(0.0, 0.0, 0.0));
+                continue;
+            }
+
             to_return.set_pixel_color(
                 &pixel,
-                to_return.get_pixel_color(&pixel) / (self.samples as Float)
+                to_return.get_pixel_color(&pixel) / weight
             );
         }
 
         to_return
     }
 
+    /// A grey image showing the number of samples of each pixel, where white is 
+    /// `max_samples` samples.
+    pub fn sample_count_map(&self, max_samples: u32) -> Image {
+        let mut to_return = Image::new(self.resolution().clone());
+
+        for pixel in self.resolution().clone().into_iter() {
+            let value = (self.num_pixel_samples(&pixel) as Float) / (max_samples.max(1) as Float);
+            to_return.set_pixel_color(&pixel, Color3::new(value, value, value));
+        }
+
+        to_return
+    }
+
     /// Saves the image buffer to a file, whose encoding is deduced from the filename 
-    /// (so include the extension in `filename`).
-    pub fn save_to_file(&self, filename: &str) -> Result<(), String> {
+    /// (so include the extension in `filename`). See `Image::save_to_file`.
+    pub fn save_to_file(&self, filename: &str, display: &DisplayTransform) -> Result<(), ImageSaveError> {
         let image = self.average_samples();
-        image.save_to_file(filename)
+        image.save_to_file(filename, display)
     }
 }
 
@@ -169,14 +494,133 @@ impl ImageBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{filters::{tent::TentFilter, mitchell::MitchellFilter}, utility::tone_mapping::ToneMapping};
 
     #[test]
     fn resolution_iterator() {
-        let resolution = Resolution { width: 5, height: 5 };
+        // Every pixel once, row by row, starting with $(0,0)$.
+        let resolution = Resolution { width: 5, height: 3 };
+        let pixels: Vec<Pixel> = resolution.into_iter().collect();
+        assert_eq!(pixels.len(), 15);
+        for (i, pixel) in pixels.iter().enumerate() {
+            assert!(pixel.x == i as u32 % 5 && pixel.y == i as u32 / 5);
+        }
+
+        assert_eq!(Resolution { width: 0, height: 3 }.into_iter().count(), 0);
+    }
+
+    #[test]
+    fn tiles_cover_image() {
+        let resolution = Resolution { width: 10, height: 7 };
+        let tiles = resolution.tiles(4);
+        assert_eq!(tiles.len(), 6);
+
+        let mut covered = vec![0; 70];
+        for tile in tiles.iter() {
+            for pixel in tile.pixels() {
+                covered[(pixel.y * 10 + pixel.x) as usize] += 1;
+            }
+        }
+        assert!(covered.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn samples_are_splatted_with_the_filter() {
+        let resolution = Resolution { width: 3, height: 3 };
+        let mut image_buffer = ImageBuffer::new(resolution.clone());
+        let sample = PixelSample {
+            pixel: Pixel { x: 1, y: 1 },
+            offset: (0.75, 0.5),
+            color: Color3::new(1.0, 2.0, 3.0),
+        };
+        let tile = Tile { origin: Pixel { x: 0, y: 0 }, width: 3, height: 3 };
+        image_buffer.add_sample(&[RenderedTile { tile, samples: vec![sample] }], &TentFilter::new(1.0));
+
+        // A quarter of a pixel right of the center: the right neighbour is three 
+        // quarters of a pixel away, the other neighbours a pixel or more.
+        let weights = image_buffer.weights();
+        let weight = |x: u32, y: u32| weights[resolution.pixel_index(&Pixel { x, y })];
+        assert!((weight(1, 1) - 0.75).abs() < 1e-6);
+        assert!((weight(2, 1) - 0.25).abs() < 1e-6);
+        assert_eq!(weights.iter().filter(|w| **w != 0.0).count(), 2);
+
+        // Only the pixel the sample was taken in counts it as a sample, but the 
+        // neighbour averages to the same color.
+        assert_eq!(image_buffer.num_pixel_samples(&Pixel { x: 2, y: 1 }), 0);
+        let color = image_buffer.average_samples().get_pixel_color(&Pixel { x: 2, y: 1 });
+        assert!((color.y() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pixels_in_a_negative_lobe_are_black() {
+        let resolution = Resolution { width: 3, height: 1 };
+        let mut image_buffer = ImageBuffer::new(resolution.clone());
+        let sample = PixelSample {
+            pixel: Pixel { x: 0, y: 0 },
+            offset: (0.0, 0.5),
+            color: Color3::new(1.0, 2.0, 3.0),
+        };
+        let tile = Tile { origin: Pixel { x: 0, y: 0 }, width: 3, height: 1 };
+        image_buffer.add_sample(&[RenderedTile { tile, samples: vec![sample] }], &MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0));
+
+        // The middle pixel's center is 1.5 pixels from the sample, where the filter 
+        // is negative.
+        let middle = Pixel { x: 1, y: 0 };
+        assert!(image_buffer.weights()[resolution.pixel_index(&middle)] < 0.0);
+        let average = image_buffer.average_samples();
+        let color = average.get_pixel_color(&middle);
+        assert!(color.x() == 0.0 && color.y() == 0.0 && color.z() == 0.0);
+        assert!((average.get_pixel_color(&Pixel { x: 0, y: 0 }).y() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn output_format_from_filename() {
+        assert_eq!(OutputFormat::new_from_filename("a/b.png").unwrap(), OutputFormat::Png);
+        assert_eq!(OutputFormat::new_from_filename("b.JPG").unwrap(), OutputFormat::Jpeg);
+        assert_eq!(OutputFormat::new_from_filename("b.jpeg").unwrap(), OutputFormat::Jpeg);
+        assert_eq!(OutputFormat::new_from_filename("b.exr").unwrap(), OutputFormat::OpenExr);
+        assert_eq!(OutputFormat::new_from_filename("b.hdr").unwrap(), OutputFormat::RadianceHdr);
+        assert!(OutputFormat::new_from_filename("b.tiff").is_err());
+        assert!(OutputFormat::new_from_filename("b").is_err());
+    }
+
+    #[test]
+    fn low_dynamic_range_output_is_tone_mapped_and_srgb_encoded() {
+        let resolution = Resolution { width: 3, height: 1 };
+        let mut image = Image::new(resolution);
+        image.set_pixel_color(&Pixel { x: 0, y: 0 }, Color3::new(0.0, 0.5, 1.0));
+        image.set_pixel_color(&Pixel { x: 1, y: 0 }, Color3::new(0.25, 0.25, 0.25));
+        image.set_pixel_color(&Pixel { x: 2, y: 0 }, Color3::new(3.0, 3.0, 3.0));
+
+        let clamped = image.to_display_image(&DisplayTransform::default());
+        assert_eq!(clamped.get_pixel(0, 0).0, [0, 188, 255]);
+        assert_eq!(clamped.get_pixel(2, 0).0, [255, 255, 255]);
+
+        let display = DisplayTransform { exposure: 1.0, tone_mapping: ToneMapping::Reinhard };
+        let mapped = image.to_display_image(&display);
+        // 0.25 is exposed to 0.5, which Reinhard maps to 1/3.
+        assert_eq!(mapped.get_pixel(1, 0).0, [156, 156, 156]);
+        assert!(mapped.get_pixel(2, 0).0[0] < 255);
+    }
+
+    #[test]
+    fn save_in_every_format() {
+        let resolution = Resolution { width: 4, height: 3 };
+        let mut image = Image::new(resolution.clone());
         for pixel in resolution.into_iter() {
-            print!("({},{}) ", pixel.x, pixel.y);
+            image.set_pixel_color(&pixel, Color3::new(pixel.x as Float, 0.5, 2.0));
+        }
+
+        let directory = std::env::temp_dir();
+        for extension in ["png", "jpg", "exr", "hdr"] {
+            let filename = directory.join(format!("mirth_save_test.{}", extension));
+            let filename = filename.to_str().unwrap();
+            image.save_to_file(filename, &DisplayTransform::default()).unwrap();
+
+            let read_back = image::open(filename).unwrap();
+            assert_eq!((read_back.width(), read_back.height()), (4, 3));
+            std::fs::remove_file(filename).unwrap();
         }
-        print!("\n");
     }
 }
 
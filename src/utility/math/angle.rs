@@ -14,14 +14,31 @@ impl Angle {
     pub fn as_degrees(&self) -> Float {
         match self.units {
             AngleUnits::Degrees => self.amount,
-            AngleUnits::Radians => Float::to_radians(self.amount),
+            AngleUnits::Radians => Float::to_degrees(self.amount),
         }
     }
 
     pub fn as_radians(&self) -> Float {
         match self.units {
-            AngleUnits::Degrees => Float::to_degrees(self.amount),
+            AngleUnits::Degrees => Float::to_radians(self.amount),
             AngleUnits::Radians => self.amount,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::float::FloatConstants;
+
+    #[test]
+    fn degrees_and_radians_convert() {
+        let right_angle = Angle { amount: 90.0, units: AngleUnits::Degrees };
+        assert!((right_angle.as_radians() - 0.5 * Float::get_pi()).abs() < 1e-6);
+        assert_eq!(right_angle.as_degrees(), 90.0);
+
+        let half_turn = Angle { amount: Float::get_pi(), units: AngleUnits::Radians };
+        assert!((half_turn.as_degrees() - 180.0).abs() < 1e-4);
+        assert_eq!(half_turn.as_radians(), Float::get_pi());
+    }
+}
@@ -0,0 +1,143 @@
+use super::{vector::Point3, float::Float, ray::Ray3};
+
+/// An axis-aligned bounding box, given by its minimal and maximal corners. A box 
+/// with some `min` coordinate greater than the corresponding `max` coordinate is 
+/// empty.
+#[derive(Clone, Debug)]
+pub struct BoundingBox {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl BoundingBox {
+    /// The box containing nothing. It is the identity for `union()`.
+    pub fn empty() -> Self {
+        Self {
+            min: Point3::new(Float::INFINITY, Float::INFINITY, Float::INFINITY),
+            max: Point3::new(Float::NEG_INFINITY, Float::NEG_INFINITY, Float::NEG_INFINITY),
+        }
+    }
+
+    /// The smallest box containing both points.
+    pub fn new_from_points(p0: &Point3, p1: &Point3) -> Self {
+        Self::empty().union_point(p0).union_point(p1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min.component(axis) > self.max.component(axis))
+    }
+
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [Point3; 8] {
+        let pick = |i: usize, axis: usize| {
+            if i & (1 << axis) == 0 { self.min.component(axis) } else { self.max.component(axis) }
+        };
+        std::array::from_fn(|i| Point3::new(pick(i, 0), pick(i, 1), pick(i, 2)))
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        Self {
+            min: Point3::new(
+                Float::min(self.min.x(), other.min.x()),
+                Float::min(self.min.y(), other.min.y()),
+                Float::min(self.min.z(), other.min.z()),
+            ),
+            max: Point3::new(
+                Float::max(self.max.x(), other.max.x()),
+                Float::max(self.max.y(), other.max.y()),
+                Float::max(self.max.z(), other.max.z()),
+            ),
+        }
+    }
+
+    pub fn union_point(&self, point: &Point3) -> Self {
+        self.union(&BoundingBox { min: point.clone(), max: point.clone() })
+    }
+
+    /// Grows the box by `amount` in every direction. Useful for flat shapes, whose 
+    /// boxes would otherwise have no volume.
+    pub fn padded(&self, amount: Float) -> Self {
+        Self {
+            min: Point3::new(self.min.x() - amount, self.min.y() - amount, self.min.z() - amount),
+            max: Point3::new(self.max.x() + amount, self.max.y() + amount, self.max.z() + amount),
+        }
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (&self.min + &self.max)
+    }
+
+    pub fn surface_area(&self) -> Float {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let d = &self.max - &self.min;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    /// The axis (0, 1, 2 for x, y, z) along which the box is longest.
+    pub fn largest_extent_axis(&self) -> usize {
+        let d = &self.max - &self.min;
+        if d.x() >= d.y() && d.x() >= d.z() { 0 }
+        else if d.y() >= d.z() { 1 }
+        else { 2 }
+    }
+
+    /// The "slab" test: returns the range of $t$ over which `ray` is inside the box, 
+    /// clipped to the ray's own range, or `None` if that range is empty.
+    pub fn intersect(&self, ray: &Ray3) -> Option<(Float, Float)> {
+        let mut t_enter = ray.min_t;
+        let mut t_exit = ray.max_t;
+
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction.component(axis);
+            let origin = ray.origin.component(axis);
+
+            let mut t0 = (self.min.component(axis) - origin) * inverse_direction;
+            let mut t1 = (self.max.component(axis) - origin) * inverse_direction;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // When the ray is parallel to the slab and starts on its boundary, the 
+            // above is 0 * inf = NaN. `max`/`min` ignore NaN, treating the ray as 
+            // inside the slab, which is the conservative answer.
+            t_enter = Float::max(t_enter, t0);
+            t_exit = Float::min(t_exit, t1);
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+
+        Some((t_enter, t_exit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::math::vector::Vec3;
+
+    #[test]
+    fn ray_box_intersection() {
+        let bounding_box = BoundingBox::new_from_points(
+            &Point3::new(-1.0, -1.0, -1.0), &Point3::new(1.0, 1.0, 1.0)
+        );
+
+        let hit = Ray3::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let (t_enter, t_exit) = bounding_box.intersect(&hit).unwrap();
+        assert!((t_enter - 4.0).abs() < 1e-5 && (t_exit - 6.0).abs() < 1e-5);
+
+        let miss = Ray3::new(Point3::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(bounding_box.intersect(&miss).is_none());
+
+        let mut too_short = hit.clone();
+        too_short.max_t = 3.0;
+        assert!(bounding_box.intersect(&too_short).is_none());
+
+        // a flat box, hit head-on
+        let flat = BoundingBox::new_from_points(&Point3::new(-1.0, -1.0, 0.0), &Point3::new(1.0, 1.0, 0.0));
+        assert!(flat.intersect(&hit).is_some());
+    }
+}
@@ -0,0 +1,145 @@
+//! Piecewise constant probability distributions, for sampling proportionally to 
+//! tabulated weights (e.g. the brightness of the texels of an image).
+
+use super::float::Float;
+
+/// A distribution on $[0,1)$ whose density is constant on each of $n$ equally wide 
+/// intervals, proportional to the given weights.
+pub struct Distribution1D {
+    weights: Vec<Float>,
+    /// `cdf[i]` is the probability of falling in the first `i` intervals, so 
+    /// there are $n+1$ entries.
+    cdf: Vec<Float>,
+    /// The sum of the weights divided by $n$, i.e. the integral of the weights as a 
+    /// function on $[0,1)$.
+    integral: Float,
+}
+
+impl Distribution1D {
+    /// `weights` must be nonempty and nonnegative. If they are all zero, the 
+    /// distribution is uniform.
+    pub fn new(weights: Vec<Float>) -> Self {
+        assert!(!weights.is_empty());
+
+        let n = weights.len() as Float;
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        cdf.push(0.0);
+        for (i, weight) in weights.iter().enumerate() {
+            cdf.push(cdf[i] + weight / n);
+        }
+
+        let integral = cdf[weights.len()];
+        if integral == 0.0 {
+            for (i, value) in cdf.iter_mut().enumerate() {
+                *value = (i as Float) / n;
+            }
+        } else {
+            for value in cdf.iter_mut() {
+                *value /= integral;
+            }
+        }
+
+        Self { weights, cdf, integral }
+    }
+
+    pub fn integral(&self) -> Float {
+        self.integral
+    }
+
+    /// Maps `u`, uniformly distributed in $[0,1)$, to a sample of the distribution. 
+    /// Returns the sample, its density and the index of its interval.
+    pub fn sample(&self, u: Float) -> (Float, Float, usize) {
+        // The last interval whose cdf is at most `u`, skipping empty intervals.
+        let index = self.cdf
+            .partition_point(|&value| value <= u)
+            .clamp(1, self.weights.len()) - 1;
+
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0.0 { (u - self.cdf[index]) / width } else { 0.0 };
+        let x = ((index as Float) + offset.clamp(0.0, 1.0)) / (self.weights.len() as Float);
+
+        (Float::min(x, 1.0 - Float::EPSILON), self.pdf(x), index)
+    }
+
+    /// The density at `x` in $[0,1)$.
+    pub fn pdf(&self, x: Float) -> Float {
+        let n = self.weights.len();
+        let index = ((x * n as Float) as usize).min(n - 1);
+        if self.integral == 0.0 {
+            return 1.0;
+        }
+
+        self.weights[index] / self.integral
+    }
+}
+
+/// A distribution on $[0,1)^2$ whose density is constant on each cell of a grid, 
+/// proportional to the given weights. We sample a row from the marginal 
+/// distribution and then a column from the distribution of that row.
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// `weights` are given row by row, and there must be `width * height` of them.
+    pub fn new(weights: &[Float], width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0 && weights.len() == width * height);
+
+        let rows: Vec<Distribution1D> = weights
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.integral()).collect());
+
+        Self { rows, marginal }
+    }
+
+    /// Maps `u`, uniformly distributed in $[0,1)^2$, to a sample $(x,y)$ of the 
+    /// distribution, where $y$ picks the row. Also returns the density there.
+    pub fn sample(&self, u: (Float, Float)) -> ((Float, Float), Float) {
+        let (y, pdf_y, row) = self.marginal.sample(u.1);
+        let (x, pdf_x, _) = self.rows[row].sample(u.0);
+
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    /// The density at $(x,y)$ in $[0,1)^2$.
+    pub fn pdf(&self, (x, y): (Float, Float)) -> Float {
+        let num_rows = self.rows.len();
+        let row = ((y * num_rows as Float) as usize).min(num_rows - 1);
+
+        self.marginal.pdf(y) * self.rows[row].pdf(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::rng::RandomNumberGenerator;
+
+    #[test]
+    fn samples_follow_the_weights() {
+        // Weights 0, 1, 0, 3: a quarter of the samples in the second cell and the 
+        // rest in the last.
+        let distribution = Distribution2D::new(&[0.0, 1.0, 0.0, 3.0], 2, 2);
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let mut counts = [0; 4];
+        let num_samples = 10000;
+
+        for _ in 0..num_samples {
+            let ((x, y), pdf) = distribution.sample((rng.next_float(), rng.next_float()));
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            assert!((pdf - distribution.pdf((x, y))).abs() < 1e-3);
+
+            let cell = 2 * ((y * 2.0) as usize) + ((x * 2.0) as usize);
+            counts[cell] += 1;
+        }
+
+        assert_eq!(counts[0] + counts[2], 0);
+        let fraction = (counts[1] as Float) / (num_samples as Float);
+        assert!((fraction - 0.25).abs() < 0.02, "fraction = {}", fraction);
+        // Three quarters of the probability on a quarter of the area.
+        assert!((distribution.pdf((0.75, 0.75)) - 3.0).abs() < 1e-3);
+    }
+}
@@ -1,7 +1,48 @@
 
-/// The type used for most global calculations. Should probably either be `f32` or `f64`.
+/// The type used for most global calculations: `f32`, or `f64` with the `f64` 
+/// feature.
+#[cfg(not(feature = "f64"))]
 pub type Float = f32;
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+/// The tolerance of comparisons with zero, and how far along a ray intersections 
+/// must be to count.
+#[cfg(not(feature = "f64"))]
 pub static FLOAT_ERR: Float = 0.00001;
+#[cfg(feature = "f64")]
+pub static FLOAT_ERR: Float = 0.000_000_01;
+
+/// `x` as an `f64`, which holds any `Float` exactly.
+#[cfg(not(feature = "f64"))]
+pub fn to_f64(x: Float) -> f64 {
+    f64::from(x)
+}
+#[cfg(feature = "f64")]
+pub fn to_f64(x: Float) -> f64 {
+    x
+}
+
+/// `x` rounded to the nearest `f32`, e.g. for formats that store single precision.
+#[cfg(not(feature = "f64"))]
+pub fn to_f32(x: Float) -> f32 {
+    x
+}
+#[cfg(feature = "f64")]
+pub fn to_f32(x: Float) -> f32 {
+    x as f32
+}
+
+/// Half the distance from 1 to the next `Float`: the relative error of a single 
+/// correctly rounded operation is at most this.
+pub const MACHINE_EPSILON: Float = 0.5 * Float::EPSILON;
+
+/// $\gamma_n = n\epsilon / (1 - n\epsilon)$, which bounds the relative error 
+/// accumulated by `n` rounded operations (see PBRT, "Managing Rounding Error").
+pub fn gamma(n: u32) -> Float {
+    let n_epsilon = (n as Float) * MACHINE_EPSILON;
+    n_epsilon / (1.0 - n_epsilon)
+}
 
 pub trait FloatConstants {
     fn get_pi() -> Self;
@@ -67,7 +108,47 @@ impl SignCheckable for Float {
     }
 
     fn is_zero(&self) -> bool {
-        !(self.is_negative()) && !(self.is_positive())
+        // Qualified, as `f64` has a (deprecated) `is_negative()` of its own.
+        !(SignCheckable::is_negative(self)) && !(self.is_positive())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::rng::RandomNumberGenerator;
+
+    #[test]
+    fn rounding_error_bounds() {
+        // Half an ulp of 1, in either precision: 1 + MACHINE_EPSILON is a tie, which 
+        // rounds (to even) back to 1.
+        let expected = match Float::kind() {
+            KindOfFloat::Float32 => (2.0 as Float).powi(-24),
+            KindOfFloat::Float64 => (2.0 as Float).powi(-53),
+        };
+        assert_eq!(MACHINE_EPSILON, expected);
+        assert_eq!(1.0 + MACHINE_EPSILON, 1.0);
+        assert_ne!(1.0 + 2.0 * MACHINE_EPSILON, 1.0);
+
+        assert_eq!(gamma(0), 0.0);
+        assert!(gamma(1) >= MACHINE_EPSILON);
+        assert!((1..10).all(|n| gamma(n + 1) > gamma(n) && gamma(n) >= (n as Float) * MACHINE_EPSILON));
+
+        // The exact errors of a sum and of a product (recovered without rounding) are 
+        // within a single rounding of the result.
+        let mut rng = RandomNumberGenerator::from_seed(6);
+        for _ in 0..10_000 {
+            let (a, b) = (rng.next_float() * 1000.0 - 500.0, rng.next_float() - 0.5);
+
+            let sum = a + b;
+            let b_virtual = sum - a;
+            let sum_error = (a - (sum - b_virtual)) + (b - b_virtual);
+            assert!(sum_error.abs() <= gamma(1) * sum.abs());
+
+            let product = a * b;
+            let product_error = a.mul_add(b, -product);
+            assert!(product_error.abs() <= gamma(1) * product.abs());
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use cgmath::{SquareMatrix, Transform};
+use cgmath::{Matrix, SquareMatrix, Transform};
 
-use super::{float::Float, vector::{Point3, Vec3}, angle::{Angle, AngleUnits}};
+use super::{float::{Float, gamma}, vector::{Point3, Vec3}, angle::{Angle, AngleUnits}};
 
 
 #[derive(Clone, Debug)]
@@ -27,6 +27,22 @@ impl Matrix4 {
 
         Vec3::new(xformed_vec.x, xformed_vec.y, xformed_vec.z)
     }
+
+    /// Transforms `point`, whose coordinates are off by at most `error`, returning 
+    /// the transformed point and a (conservative) bound on the error of its 
+    /// coordinates, which includes the rounding error of the transform itself.
+    pub fn transform_point_with_error(&self, point: &Point3, error: &Vec3) -> (Point3, Vec3) {
+        // cgmath matrices are indexed by column, then row.
+        let m = |row: usize, column: usize| self.internal[column][row];
+        let row_error = |row: usize| {
+            let rounding = (0..3).map(|i| Float::abs(m(row, i) * point.component(i))).sum::<Float>()
+                + Float::abs(m(row, 3));
+            let propagated: Float = (0..3).map(|i| Float::abs(m(row, i)) * error.component(i)).sum();
+            gamma(3) * rounding + (1.0 + gamma(3)) * propagated
+        };
+
+        (self.transform_point(point), Vec3::new(row_error(0), row_error(1), row_error(2)))
+    }
 }
 
 // S==== CONSTRUCTING TRANSFORMATIONS {{{2
@@ -136,6 +152,12 @@ impl Matrix4 {
 
     
 
+    pub fn transpose(&self) -> Self {
+        Matrix4 {
+            internal: self.internal.transpose()
+        }
+    }
+
     pub fn inverse(&self) -> Self {
         let new_internal = self.internal.invert()
             .expect("tried to invert a noninvertible matrix");
@@ -5,4 +5,7 @@ pub mod vector;
 pub mod ray;
 pub mod orthonormal_basis;
 pub mod matrix;
+pub mod bounding_box;
 
+pub mod distribution;
+pub mod statistics;
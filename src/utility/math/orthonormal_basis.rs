@@ -1,5 +1,5 @@
 use super::{
-    vector::{Vec3, cross}, 
+    vector::{Vec3, cross, dot}, 
     float::Float
 };
 
@@ -40,5 +40,11 @@ impl OrthonormalBasis {
     pub fn vector_from_local(&self, v: Vec3) -> Vec3 {
         (v.x() * &self.x_axis) + (v.y() * &self.y_axis) + (v.z() * &self.z_axis)
     }
+
+    /// Takes a vector in global coordinates and returns its coordinates wrt this 
+    /// orthonormal basis. The inverse of `vector_from_local()`.
+    pub fn vector_to_local(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(dot(v, &self.x_axis), dot(v, &self.y_axis), dot(v, &self.z_axis))
+    }
 }
 
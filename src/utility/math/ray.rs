@@ -1,3 +1,4 @@
+use crate::spectral::wavelengths::SampledWavelengths;
 use super::{vector::{Point3, Vec3}, float::{Float, FLOAT_ERR}};
 
 
@@ -7,6 +8,9 @@ pub struct Ray3 {
     pub direction: Vec3,
     pub min_t: Float,
     pub max_t: Float,
+    /// The wavelengths of light the ray carries in spectral mode, or `None` when
+    /// rendering in RGB.
+    pub wavelengths: Option<SampledWavelengths>,
 }
 
 impl Ray3 {
@@ -16,6 +20,7 @@ impl Ray3 {
             direction,
             min_t: FLOAT_ERR,
             max_t: Float::INFINITY,
+            wavelengths: None,
         }
     }
 
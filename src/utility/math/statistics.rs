@@ -0,0 +1,91 @@
+//! Estimating how converged a Monte Carlo estimate is from its samples.
+
+use super::float::Float;
+
+/// The mean and variance of a stream of values, updated one value at a time with
+/// Welford's algorithm, which (unlike keeping the sum of squares) does not lose
+/// precision when the variance is small compared to the mean.
+#[derive(Clone, Debug, Default)]
+pub struct RunningStatistics {
+    count: u32,
+    mean: Float,
+    /// The sum of the squared differences from the mean.
+    m2: Float,
+}
+
+impl RunningStatistics {
+    /// Restores the statistics from what the accessors returned.
+    pub fn new_from_parts(count: u32, mean: Float, m2: Float) -> Self {
+        Self { count, mean, m2 }
+    }
+
+    pub fn add(&mut self, value: Float) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / (self.count as Float);
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Float {
+        self.mean
+    }
+
+    pub fn m2(&self) -> Float {
+        self.m2
+    }
+
+    /// The unbiased sample variance; zero with fewer than two values.
+    pub fn variance(&self) -> Float {
+        if self.count < 2 {
+            return 0.0;
+        }
+        self.m2 / ((self.count - 1) as Float)
+    }
+
+    /// The standard error of the mean relative to the mean, i.e. roughly how far off
+    /// the mean is as a fraction of itself. Infinite if there are fewer than two
+    /// values, since nothing is known yet.
+    pub fn relative_error(&self) -> Float {
+        if self.count < 2 {
+            return Float::INFINITY;
+        }
+
+        let standard_error = (self.variance() / (self.count as Float)).sqrt();
+        if standard_error == 0.0 {
+            0.0
+        } else {
+            standard_error / self.mean.abs()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_two_pass_formulas() {
+        let values: [Float; 6] = [1000.5, 1001.0, 999.25, 1000.0, 1002.75, 998.5];
+        let mut statistics = RunningStatistics::default();
+        for value in values {
+            statistics.add(value);
+        }
+
+        let n = values.len() as Float;
+        let mean = values.iter().sum::<Float>() / n;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<Float>() / (n - 1.0);
+
+        assert_eq!(statistics.count(), 6);
+        assert!((statistics.mean() - mean).abs() < 1e-3);
+        assert!((statistics.variance() - variance).abs() < 1e-3 * variance);
+        assert!((statistics.relative_error() - (variance / n).sqrt() / mean).abs() < 1e-6);
+
+        let constant = RunningStatistics::new_from_parts(4, 0.0, 0.0);
+        assert_eq!(constant.relative_error(), 0.0);
+        assert!(RunningStatistics::default().relative_error().is_infinite());
+    }
+}
@@ -47,6 +47,9 @@ impl Vec3 {
     /// Retrieve the z coordinate.
     pub fn z(&self) -> Float { self.internal.z }
 
+    /// Retrieve the coordinate along `axis`, where 0, 1, 2 are x, y, z respectively.
+    pub fn component(&self, axis: usize) -> Float { self.internal[axis] }
+
     /// Change the x coordinate to the specified value.
     pub fn set_x(&mut self, x: Float) { self.internal.x = x; }
     /// Change the y coordinate to the specified value.
@@ -59,11 +62,20 @@ impl Vec3 {
         self
     }
 
+    pub fn length(&self) -> Float {
+        self.internal.magnitude()
+    }
+
     pub fn normalize_to(mut self, magnitude: Float) -> Self {
         self.internal = self.internal.normalize_to(magnitude);
         self
     }
 
+    /// The vector of the absolute values of the coordinates.
+    pub fn abs(&self) -> Self {
+        Vec3::new(self.x().abs(), self.y().abs(), self.z().abs())
+    }
+
     pub fn are_equal(v1: &Vec3, v2: &Vec3) -> bool {
         (v1.x() - v2.x()).is_zero()
         && (v1.y() - v2.y()).is_zero()
@@ -81,6 +93,28 @@ pub fn cross(v1: &Vec3, v2: &Vec3) -> Vec3 {
     }
 }
 
+/// The mirror image of `v` in the plane with unit normal `normal`, e.g. the 
+/// direction a perfect mirror reflects `v` into.
+pub fn reflect(v: &Vec3, normal: &Vec3) -> Vec3 {
+    v - (2.0 * dot(v, normal)) * normal
+}
+
+/// The direction the unit vector `v` continues in after passing through a surface 
+/// with unit normal `normal` (on the side `v` comes from), by Snell's law. 
+/// `eta_ratio` is the index of refraction on the side of `v` divided by that on the 
+/// other side. Returns `None` if there is total internal reflection.
+pub fn refract(v: &Vec3, normal: &Vec3, eta_ratio: Float) -> Option<Vec3> {
+    let cos_incident = Float::min(-dot(v, normal), 1.0);
+    let sin2_transmitted = eta_ratio * eta_ratio * (1.0 - cos_incident * cos_incident);
+    if sin2_transmitted > 1.0 {
+        return None;
+    }
+
+    let perpendicular = eta_ratio * (v + cos_incident * normal);
+    let parallel = (-Float::sqrt(1.0 - sin2_transmitted)) * normal;
+    Some(perpendicular + parallel)
+}
+
 impl Default for Vec3 {
     fn default() -> Self {
         Vec3::new(0.0, 0.0, 0.0)
@@ -187,6 +221,24 @@ impl ops::Mul<&Vec3> for Float {
     }
 }
 
+// Vec3 * Vec3 (componentwise, mostly useful for colors)
+impl ops::Mul<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        Vec3::new(self.x() * rhs.x(), self.y() * rhs.y(), self.z() * rhs.z())
+    }
+}
+
+// &Vec3 * &Vec3 (componentwise, mostly useful for colors)
+impl ops::Mul<&Vec3> for &Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: &Vec3) -> Self::Output {
+        Vec3::new(self.x() * rhs.x(), self.y() * rhs.y(), self.z() * rhs.z())
+    }
+}
+
 // Vec3 / Float 
 impl ops::Div<Float> for Vec3 {
     type Output = Vec3;
@@ -208,10 +260,10 @@ impl ops::Div<Float> for &Vec3 {
 // E==== OPERATOR OVERLOADS }}}1
 
 pub type Color3 = Vec3;
-impl From<Color3> for image::Rgb<f32> {
+impl From<Color3> for image::Rgb<Float> {
     fn from(color: Color3) -> Self {
         image::Rgb {
-            0:[color.x().into(), color.y().into(), color.z().into()]
+            0:[color.x(), color.y(), color.z()]
         }
     }
 }
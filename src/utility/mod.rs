@@ -4,4 +4,5 @@ pub mod math;
 pub mod image;
 pub mod rng;
 pub mod scene_parser;
+pub mod tone_mapping;
 
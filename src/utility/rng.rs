@@ -1,9 +1,9 @@
 //! A thread-local reproducible pseudo random number generator.
 //!
 //! In a multithreaded context, one struct should be passed to each thread.
-//! A construction method can deterministically produce these. Thus, 
-//! a program using these generators is only reproducible when using the same 
-//! number of threads.
+//! `new_stream()` deterministically produces these from a single seed, so a
+//! program that assigns streams to units of work (rather than to threads) is
+//! reproducible no matter how many threads it uses.
 
 use rand_core::RngCore;
 use rand_pcg;
@@ -20,6 +20,20 @@ impl RandomNumberGenerator {
         }
     }
 
+    /// A generator for the `stream`-th independent stream of numbers belonging to
+    /// `seed`. The same `(seed, stream)` pair always produces the same numbers, while
+    /// different pairs produce (for all practical purposes) uncorrelated ones.
+    pub fn new_stream(seed: u32, stream: u64) -> Self {
+        // Neighbouring seeds of a linear generator are correlated, so we scramble
+        // the pair into the 128-bit state.
+        let high = splitmix64(seed as u64 ^ splitmix64(stream));
+        let low = splitmix64(high ^ stream);
+
+        RandomNumberGenerator {
+            internal: rand_pcg::Pcg64Mcg::new(((high as u128) << 64) | (low as u128)),
+        }
+    }
+
     pub fn next_float(&mut self) -> Float {
         match Float::kind() {
             KindOfFloat::Float32 => {
@@ -31,6 +45,37 @@ impl RandomNumberGenerator {
                 (random_int as Float) / (u64::MAX as Float)
             },
         }
-    } 
+    }
 }
 
+/// The finalizer of the SplitMix64 generator, which thoroughly mixes the bits of `x`.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_reproducible_and_distinct() {
+        let first: Vec<Float> = {
+            let mut rng = RandomNumberGenerator::new_stream(1, 7);
+            (0..8).map(|_| rng.next_float()).collect()
+        };
+        let again: Vec<Float> = {
+            let mut rng = RandomNumberGenerator::new_stream(1, 7);
+            (0..8).map(|_| rng.next_float()).collect()
+        };
+        let other: Vec<Float> = {
+            let mut rng = RandomNumberGenerator::new_stream(1, 8);
+            (0..8).map(|_| rng.next_float()).collect()
+        };
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+    }
+}
@@ -0,0 +1,219 @@
+//! Turning the linear radiance the renderer computes into colors a display can show.
+//!
+//! Rendered values are unbounded, while low dynamic range images store values in
+//! $[0,1]$. A `DisplayTransform` scales the image by its exposure and then compresses
+//! it into that range with a `ToneMapping` operator. The result is still linear;
+//! encoding it (e.g. with `light::linear_to_srgb`) is up to the image format.
+
+use super::math::{float::Float, vector::Color3};
+use crate::light::luminance;
+
+/// How values above 1 are brought into $[0,1]$.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapping {
+    /// Clips each channel, so everything brighter than 1 is white.
+    Clamp,
+    /// Maps the luminance $L$ to $L / (1 + L)$, keeping the hue. Never reaches white.
+    Reinhard,
+    /// Reinhard's operator adjusted so that luminance `white_point` (and above) maps
+    /// to white.
+    ExtendedReinhard { white_point: Float },
+    /// Stephen Hill's fit of the ACES reference rendering and sRGB output transforms,
+    /// which has a filmic toe and shoulder and desaturates highlights.
+    AcesFilmic,
+    /// An approximation of Blender's AgX, which compresses a fixed range of stops
+    /// in a log encoding with a sigmoid. Very bright colors fade to white smoothly
+    /// rather than clipping to a saturated primary.
+    Agx,
+}
+
+impl ToneMapping {
+    /// Maps a linear color (after exposure) to a linear color in $[0,1]$.
+    pub fn apply(&self, color: &Color3) -> Color3 {
+        let mapped = match self {
+            ToneMapping::Clamp => color.clone(),
+            ToneMapping::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+            ToneMapping::ExtendedReinhard { white_point } => {
+                let white_squared = white_point * white_point;
+                scale_luminance(color, |l| l * (1.0 + l / white_squared) / (1.0 + l))
+            },
+            ToneMapping::AcesFilmic => aces_filmic(color),
+            ToneMapping::Agx => agx(color),
+        };
+
+        Color3::new(
+            mapped.x().clamp(0.0, 1.0),
+            mapped.y().clamp(0.0, 1.0),
+            mapped.z().clamp(0.0, 1.0),
+        )
+    }
+}
+
+/// What is done to the rendered image before it is written to a low dynamic range
+/// format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayTransform {
+    /// In stops: the image is scaled by $2^{exposure}$ before tone mapping.
+    pub exposure: Float,
+    pub tone_mapping: ToneMapping,
+}
+
+impl Default for DisplayTransform {
+    /// Leaves the values as they are, apart from clamping.
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tone_mapping: ToneMapping::Clamp,
+        }
+    }
+}
+
+impl DisplayTransform {
+    pub fn apply(&self, color: &Color3) -> Color3 {
+        let exposed = Float::powf(2.0, self.exposure) * color;
+        self.tone_mapping.apply(&exposed)
+    }
+}
+
+/// Rescales `color` so that its luminance $L$ becomes `f(L)`.
+fn scale_luminance(color: &Color3, f: impl Fn(Float) -> Float) -> Color3 {
+    let l = luminance(color);
+    if l <= 0.0 {
+        return Color3::new(0.0, 0.0, 0.0);
+    }
+    (f(l) / l) * color
+}
+
+/// Multiplies `color` by a matrix given row by row.
+fn transform(matrix: &[[Float; 3]; 3], color: &Color3) -> Color3 {
+    let row = |r: &[Float; 3]| r[0] * color.x() + r[1] * color.y() + r[2] * color.z();
+    Color3::new(row(&matrix[0]), row(&matrix[1]), row(&matrix[2]))
+}
+
+fn map_channels(color: &Color3, f: impl Fn(Float) -> Float) -> Color3 {
+    Color3::new(f(color.x()), f(color.y()), f(color.z()))
+}
+
+// S==== ACES {{{1
+
+/// Linear sRGB to the ACES rendering space, including the reference rendering
+/// transform's saturation adjustment.
+const ACES_INPUT: [[Float; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+
+/// The output transform's space back to linear sRGB.
+const ACES_OUTPUT: [[Float; 3]; 3] = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+];
+
+fn aces_filmic(color: &Color3) -> Color3 {
+    let rendering_space = transform(&ACES_INPUT, color);
+    let fitted = map_channels(&rendering_space, |v| {
+        (v * (v + 0.0245786) - 0.000090537) / (v * (0.983729 * v + 0.432951) + 0.238081)
+    });
+    transform(&ACES_OUTPUT, &fitted)
+}
+
+// E==== ACES }}}1
+
+// S==== AGX {{{1
+
+/// Linear sRGB to AgX's slightly desaturated working space, so that very bright
+/// saturated colors head towards white.
+const AGX_INPUT: [[Float; 3]; 3] = [
+    [0.8424791, 0.0784336, 0.07922375],
+    [0.04232824, 0.8784686, 0.07916613],
+    [0.04237565, 0.0784336, 0.879143],
+];
+
+/// The inverse of `AGX_INPUT`.
+const AGX_OUTPUT: [[Float; 3]; 3] = [
+    [1.196879, -0.09802088, -0.09902974],
+    [-0.05289685, 1.151903, -0.09896118],
+    [-0.05297164, -0.09804345, 1.151074],
+];
+
+/// The range of stops (relative to 1) that the sigmoid maps to $[0,1]$.
+const AGX_MIN_EV: Float = -12.47393;
+const AGX_MAX_EV: Float = 4.026069;
+
+fn agx(color: &Color3) -> Color3 {
+    let working_space = transform(&AGX_INPUT, color);
+    let curved = map_channels(&working_space, |v| {
+        let stops = Float::log2(v.max(1e-10)).clamp(AGX_MIN_EV, AGX_MAX_EV);
+        let x = (stops - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+        // A polynomial fit of AgX's default contrast curve, whose output is
+        // (roughly) gamma 2.2 encoded.
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        let encoded = 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x
+            + 0.4298 * x2 + 0.1191 * x - 0.00232;
+        Float::powf(encoded.max(0.0), 2.2)
+    });
+    transform(&AGX_OUTPUT, &curved)
+}
+
+// E==== AGX }}}1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ToneMapping; 5] = [
+        ToneMapping::Clamp,
+        ToneMapping::Reinhard,
+        ToneMapping::ExtendedReinhard { white_point: 4.0 },
+        ToneMapping::AcesFilmic,
+        ToneMapping::Agx,
+    ];
+
+    fn grey(value: Float) -> Color3 {
+        Color3::new(value, value, value)
+    }
+
+    #[test]
+    fn operators_are_monotonic_and_bounded() {
+        for tone_mapping in ALL {
+            let mut previous = -1.0;
+            for i in 0..=200 {
+                let value = 0.001 * Float::powf(1.06, i as Float);
+                let mapped = tone_mapping.apply(&grey(value));
+                assert!((0.0..=1.0).contains(&mapped.x()), "{:?} at {}", tone_mapping, value);
+                assert!(mapped.x() >= previous, "{:?} decreases at {}", tone_mapping, value);
+                previous = mapped.x();
+            }
+            assert!(tone_mapping.apply(&grey(0.0)).x() < 0.01, "{:?} lifts black", tone_mapping);
+        }
+    }
+
+    #[test]
+    fn operators_preserve_neutral_colors() {
+        for tone_mapping in ALL {
+            for value in [0.05, 0.18, 1.0, 6.0] {
+                let mapped = tone_mapping.apply(&grey(value));
+                assert!((mapped.x() - mapped.y()).abs() < 1e-3, "{:?} at {}", tone_mapping, value);
+                assert!((mapped.y() - mapped.z()).abs() < 1e-3, "{:?} at {}", tone_mapping, value);
+            }
+        }
+    }
+
+    #[test]
+    fn exposure_and_white_point() {
+        let reinhard = ToneMapping::Reinhard;
+        assert!((reinhard.apply(&grey(1.0)).x() - 0.5).abs() < 1e-5);
+
+        let extended = ToneMapping::ExtendedReinhard { white_point: 4.0 };
+        assert!((extended.apply(&grey(4.0)).x() - 1.0).abs() < 1e-5);
+        assert!(extended.apply(&grey(2.0)).x() < 1.0);
+
+        let display = DisplayTransform { exposure: 1.0, tone_mapping: ToneMapping::Clamp };
+        assert!((display.apply(&grey(0.25)).x() - 0.5).abs() < 1e-6);
+        assert_eq!(display.apply(&grey(0.75)).x(), 1.0);
+    }
+}